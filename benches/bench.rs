@@ -23,7 +23,7 @@ fn bench_fasterpam(b: &mut Bencher) {
 	}
 	b.iter(|| {
 		let mut med = vec![0, 1, 2, 3, 4];
-		let (loss, assignment, _, _): (i32, _, _, _) = fasterpam(&mat, &mut med, 100);
+		let (loss, assignment, _, _): (i32, _, _, _) = fasterpam(&mat, &mut med, 0, 100);
 		black_box(loss);
 		black_box(assignment);
 	});
@@ -42,7 +42,7 @@ fn bench_rand_fasterpam(b: &mut Bencher) {
 	}
 	b.iter(|| {
 		let mut med = vec![0, 1, 2, 3, 4];
-		let (loss, assignment, _, _): (i32, _, _, _) = rand_fasterpam(&mat, &mut med, 100, &mut rng);
+		let (loss, assignment, _, _): (i32, _, _, _) = rand_fasterpam(&mat, &mut med, 0, 100, &mut rng);
 		black_box(loss);
 		black_box(assignment);
 	});
@@ -62,7 +62,7 @@ fn bench_par_fasterpam(b: &mut Bencher) {
 	}
 	b.iter(|| {
 		let mut med = vec![0, 1, 2, 3, 4];
-		let (loss, assignment, _, _): (i32, _, _, _) = par_fasterpam(&mat, &mut med, 100, &mut rng);
+		let (loss, assignment, _, _): (i32, _, _, _) = par_fasterpam(&mat, &mut med, 0, 100, &mut rng);
 		black_box(loss);
 		black_box(assignment);
 	});
@@ -81,7 +81,7 @@ fn bench_fastpam1(b: &mut Bencher) {
 	}
 	b.iter(|| {
 		let mut med = vec![0, 1, 2, 3, 4];
-		let (loss, assignment, _, _): (i32, _, _, _) = fastpam1(&mat, &mut med, 100);
+		let (loss, assignment, _, _): (i32, _, _, _) = fastpam1(&mat, &mut med, 0, 100);
 		black_box(loss);
 		black_box(assignment);
 	});
@@ -100,7 +100,45 @@ fn bench_pam_swap(b: &mut Bencher) {
 	}
 	b.iter(|| {
 		let mut med = vec![0, 1, 2, 3, 4];
-		let (loss, assignment, _, _): (i32, _, _, _) = pam_swap(&mat, &mut med, 100);
+		let (loss, assignment, _, _): (i32, _, _, _) = pam_swap(&mat, &mut med, 0, 100);
+		black_box(loss);
+		black_box(assignment);
+	});
+}
+
+/// Random matrix of size n, for comparing FasterPAM and PAM SWAP on the same data
+fn random_matrix(rng: &mut StdRng, n: usize) -> Array2<i32> {
+	let mut mat = Array2::<i32>::from_elem((n, n), 0);
+	for i in 0..n {
+		for j in (i + 1)..n {
+			let v = rng.gen_range(1..10000);
+			mat[[i, j]] = v;
+			mat[[j, i]] = v;
+		}
+	}
+	mat
+}
+
+/// FasterPAM needs O(n²) per iteration, PAM SWAP O(n²k); compare with bench_pam_swap_500
+#[bench]
+fn bench_fasterpam_500(b: &mut Bencher) {
+	let mut rng = StdRng::seed_from_u64(42);
+	let mat = random_matrix(&mut rng, 500);
+	b.iter(|| {
+		let mut med = (0..10).collect::<Vec<_>>();
+		let (loss, assignment, _, _): (i64, _, _, _) = fasterpam(&mat, &mut med, 0, 100);
+		black_box(loss);
+		black_box(assignment);
+	});
+}
+
+#[bench]
+fn bench_pam_swap_500(b: &mut Bencher) {
+	let mut rng = StdRng::seed_from_u64(42);
+	let mat = random_matrix(&mut rng, 500);
+	b.iter(|| {
+		let mut med = (0..10).collect::<Vec<_>>();
+		let (loss, assignment, _, _): (i64, _, _, _) = pam_swap(&mat, &mut med, 0, 100);
 		black_box(loss);
 		black_box(assignment);
 	});
@@ -160,3 +198,165 @@ fn bench_alternating(b: &mut Bencher) {
 		black_box(assignment);
 	});
 }
+
+/// Larger random matrix, on which the parallel versions can scale
+fn large_matrix(rng: &mut StdRng) -> Array2<i32> {
+	let n = SIZE * 20;
+	let mut mat = Array2::<i32>::from_elem((n, n), 0);
+	for i in 0..n {
+		for j in (i + 1)..n {
+			let v = rng.gen_range(1..10000);
+			mat[[i, j]] = v;
+			mat[[j, i]] = v;
+		}
+	}
+	mat
+}
+
+#[bench]
+fn bench_alternating_large(b: &mut Bencher) {
+	let mut rng = StdRng::seed_from_u64(42);
+	let mat = large_matrix(&mut rng);
+	b.iter(|| {
+		let mut med = (0..20).collect::<Vec<_>>();
+		let (loss, assignment, _): (i64, _, _) = alternating(&mat, &mut med, 100);
+		black_box(loss);
+		black_box(assignment);
+	});
+}
+
+/// Compare with bench_alternating_large. Besides using multiple threads, the parallel
+/// version only visits the members of each cluster when choosing the new medoids.
+#[cfg(feature = "parallel")]
+#[bench]
+fn bench_par_alternating_large(b: &mut Bencher) {
+	let mut rng = StdRng::seed_from_u64(42);
+	let mat = large_matrix(&mut rng);
+	b.iter(|| {
+		let mut med = (0..20).collect::<Vec<_>>();
+		let (loss, assignment, _): (i64, _, _) = par_alternating(&mat, &mut med, 100);
+		black_box(loss);
+		black_box(assignment);
+	});
+}
+
+/// Well-separated clusters with Manhattan distance, where pruning is effective
+fn clustered_matrix(rng: &mut StdRng) -> Array2<i32> {
+	let points = (0..SIZE * 3)
+		.map(|i| (i as i32 % 10 * 100 + rng.gen_range(0..20), i as i32 % 10 / 2 * 100 + rng.gen_range(0..20)))
+		.collect::<Vec<_>>();
+	let n = points.len();
+	Array2::from_shape_fn((n, n), |(i, j)| (points[i].0 - points[j].0).abs() + (points[i].1 - points[j].1).abs())
+}
+
+#[bench]
+fn bench_pammedsil_swap(b: &mut Bencher) {
+	let mut rng = StdRng::seed_from_u64(42);
+	let mat = clustered_matrix(&mut rng);
+	b.iter(|| {
+		let mut med = vec![0, 10, 20, 30, 40, 50, 60, 70, 80, 90]; // all in the same cluster
+		let (loss, assignment, _, _): (f64, _, _, _) = pammedsil_swap(&mat, &mut med, 100);
+		black_box(loss);
+		black_box(assignment);
+	});
+}
+
+#[bench]
+fn bench_pammedsil_swap_pruned(b: &mut Bencher) {
+	let mut rng = StdRng::seed_from_u64(42);
+	let mat = clustered_matrix(&mut rng);
+	b.iter(|| {
+		let mut med = vec![0, 10, 20, 30, 40, 50, 60, 70, 80, 90]; // all in the same cluster
+		let (loss, assignment, _, _): (f64, _, _, _) = pammedsil_swap_pruned(&mat, &mut med, 100);
+		black_box(loss);
+		black_box(assignment);
+	});
+}
+
+/// Random lower triangular matrix, for comparing the storage layouts
+fn random_lower_triangle(rng: &mut StdRng) -> arrayadapter::LowerTriangle<f64> {
+	let n = SIZE * 10;
+	arrayadapter::LowerTriangle { n, data: (0..n * (n - 1) / 2).map(|_| rng.gen_range(1.0..10000.0)).collect() }
+}
+
+#[bench]
+fn bench_silhouette(b: &mut Bencher) {
+	let mut rng = StdRng::seed_from_u64(42);
+	let mat = random_lower_triangle(&mut rng);
+	let assi = (0..mat.n).map(|i| i % 5).collect::<Vec<_>>();
+	b.iter(|| {
+		let (sil, _): (f64, _) = silhouette(&mat, &assi, false);
+		black_box(sil);
+	});
+}
+
+/// Compare with bench_silhouette. The silhouette does not use SIMD instructions, but
+/// scalar access via `get`, so the alignment alone is not expected to make a difference.
+#[bench]
+fn bench_silhouette_aligned(b: &mut Bencher) {
+	let mut rng = StdRng::seed_from_u64(42);
+	let mat = arrayadapter::AlignedLowerTriangle::<_, 64>::from_lower_triangle(&random_lower_triangle(&mut rng));
+	let assi = (0..mat.len()).map(|i| i % 5).collect::<Vec<_>>();
+	b.iter(|| {
+		let (sil, _): (f64, _) = silhouette(&mat, &assi, false);
+		black_box(sil);
+	});
+}
+
+/// Large dense matrix that does not fit into the cache
+fn random_square_matrix(rng: &mut StdRng) -> arrayadapter::SquareMatrix<f64> {
+	let n = SIZE * 20;
+	let mut data = vec![0.; n * n];
+	for i in 0..n {
+		for j in (i + 1)..n {
+			let v = rng.gen_range(1.0..10000.0);
+			data[i * n + j] = v;
+			data[j * n + i] = v;
+		}
+	}
+	arrayadapter::SquareMatrix::new(n, data)
+}
+
+#[bench]
+fn bench_silhouette_large(b: &mut Bencher) {
+	let mut rng = StdRng::seed_from_u64(42);
+	let mat = random_square_matrix(&mut rng);
+	let assi = (0..mat.n).map(|i| i % 5).collect::<Vec<_>>();
+	b.iter(|| {
+		let (sil, _): (f64, _) = silhouette(&mat, &assi, false);
+		black_box(sil);
+	});
+}
+
+/// Scattered cells of a large lower triangular matrix that does not fit into the cache
+fn scattered_queries(rng: &mut StdRng) -> (arrayadapter::LowerTriangle<f64>, Vec<(usize, usize)>) {
+	let n = SIZE * 30;
+	let mat = arrayadapter::LowerTriangle { n, data: (0..n * (n - 1) / 2).map(|_| rng.gen_range(1.0..10000.0)).collect() };
+	let queries = (0..100_000).map(|_| (rng.gen_range(0..n), rng.gen_range(0..n))).collect();
+	(mat, queries)
+}
+
+#[bench]
+fn bench_get_scattered(b: &mut Bencher) {
+	let mut rng = StdRng::seed_from_u64(42);
+	let (mat, queries) = scattered_queries(&mut rng);
+	let mut out = Vec::with_capacity(queries.len());
+	b.iter(|| {
+		out.clear();
+		out.extend(queries.iter().map(|&(x, y)| mat.get(x, y)));
+		black_box(&out);
+	});
+}
+
+/// Compare with bench_get_scattered. The independent reads already overlap in the CPU,
+/// so prefetching does not help here; the batch avoids the branches of `get`.
+#[bench]
+fn bench_get_batch_scattered(b: &mut Bencher) {
+	let mut rng = StdRng::seed_from_u64(42);
+	let (mat, queries) = scattered_queries(&mut rng);
+	let mut out = Vec::with_capacity(queries.len());
+	b.iter(|| {
+		mat.get_batch(&queries, &mut out);
+		black_box(&out);
+	});
+}
@@ -60,6 +60,200 @@ where
 	(loss, assi, iter)
 }
 
+/// Assign each object to the nearest medoid.
+///
+/// Ties are broken as in the algorithms: the earlier medoid wins, but a medoid is
+/// always assigned to itself.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+///
+/// returns a tuple containing:
+/// * the loss (total deviation)
+/// * the cluster assignment
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let (loss, assi): (i64, _) = kmedoids::assign_to_medoids(&data, &[0, 3]);
+/// assert_eq!(assi, vec![0, 0, 0, 1]);
+/// ```
+pub fn assign_to_medoids<M, N, L>(mat: &M, med: &[usize]) -> (L, Vec<usize>)
+where
+	N: PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let mut assi = vec![usize::MAX; mat.len()];
+	let loss = assign_nearest(mat, med, &mut assi);
+	(loss, assi)
+}
+
+/// Choose the best medoid of one cluster of an assignment.
+///
+/// This chooses the member of the cluster with the smallest sum of distances to the
+/// other members, as the Alternating algorithm does in each iteration. It can be used
+/// to update the medoids after modifying an assignment manually, e.g., after merging
+/// clusters, without running a full SWAP. On ties, the member with the lowest index is chosen.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `assi` - the cluster assignment
+/// * `cluster` - the cluster to choose the medoid for
+///
+/// returns a tuple containing:
+/// * the new medoid
+/// * the loss of the cluster (sum of distances to the medoid)
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when the cluster is empty
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let (med, loss): (_, i64) = kmedoids::reelect_medoid_for_cluster(&data, &[0, 0, 0, 1], 0);
+/// assert_eq!((med, loss), (0, 3));
+/// ```
+pub fn reelect_medoid_for_cluster<M, N, L>(mat: &M, assi: &[usize], cluster: usize) -> (usize, L)
+where
+	N: PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive,
+	M: ArrayAdapter<N>,
+{
+	let members = assi.iter().enumerate().filter(|&(_, &a)| a == cluster).map(|(i, _)| i).collect::<Vec<_>>();
+	assert!(!members.is_empty(), "cluster must not be empty");
+	optimal_single_medoid(mat, &members)
+}
+
+/// Choose the best medoid of a set of points.
+///
+/// This is the point with the smallest sum of distances to the other points, i.e., the
+/// optimal solution for k = 1 on the points. Every distance is read once and added to the
+/// sums of both points, so this needs only m(m-1)/2 reads for m points, and does not visit
+/// the other points of the data set as [`reelect_medoid_for_cluster`] would. On ties, the
+/// first point in the list is chosen.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `cluster_points` - the points to choose from, e.g., the members of a cluster
+///
+/// returns a tuple containing:
+/// * the chosen medoid
+/// * the sum of distances to the medoid
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when there are no points
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let (med, loss): (_, i64) = kmedoids::optimal_single_medoid(&data, &[1, 2, 3]);
+/// assert_eq!((med, loss), (1, 9));
+/// ```
+pub fn optimal_single_medoid<M, N, L>(mat: &M, cluster_points: &[usize]) -> (usize, L)
+where
+	N: PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive,
+	M: ArrayAdapter<N>,
+{
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(!cluster_points.is_empty(), "at least one point is required");
+	let mut sums = vec![L::zero(); cluster_points.len()];
+	// each sum is accumulated in the order of the points, as with one pass per candidate
+	for (b, &pb) in cluster_points.iter().enumerate() {
+		for (a, &pa) in cluster_points[..b].iter().enumerate() {
+			let d = L::from(mat.get(pb, pa));
+			sums[a] += d;
+			sums[b] += d;
+		}
+	}
+	let mut best = 0;
+	for (j, &sum) in sums.iter().enumerate().skip(1) {
+		if sum < sums[best] {
+			best = j;
+		}
+	}
+	(cluster_points[best], sums[best])
+}
+
+/// Refine medoids by moving each to the discrete geometric median of its cluster.
+///
+/// For distances derived from a Euclidean space, the best center of a cluster is its
+/// geometric median, which the Weiszfeld algorithm approximates with coordinates. On a
+/// distance matrix, the discrete analogue is the member with the smallest sum of distances
+/// to all members. Starting with the given assignment, each medoid is replaced with this
+/// point, then all points are reassigned to the nearest medoid, for up to `n_iter` rounds
+/// or until no medoid changes (i.e., [`alternating`] with a warm start).
+///
+/// This never increases the loss. It can improve the result of a greedy or approximate
+/// method, but not a local optimum of PAM SWAP or FasterPAM: these already consider
+/// replacing each medoid with every member of its cluster.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the list of medoids, updated in place
+/// * `assi` - the cluster assignment of the medoids
+/// * `n_iter` - the maximum number of rounds
+///
+/// returns the loss (total deviation) after the refinement
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+/// * panics when the assignment does not have length N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = vec![1, 3];
+/// let loss: i64 = kmedoids::refine_medoids_geometric_median(&data, &mut meds, &[0, 0, 0, 1], 10);
+/// assert_eq!((loss, meds), (3, vec![0, 3]));
+/// ```
+pub fn refine_medoids_geometric_median<M, N, L>(mat: &M, meds: &mut [usize], assi: &[usize], n_iter: usize) -> L
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(assi.len() == mat.len(), "assignment must have length N");
+	let mut assi = assi.to_vec();
+	for _ in 0..n_iter {
+		let mut changed = false;
+		for i in 0..meds.len() {
+			changed |= choose_medoid_within_partition::<M, N, L>(mat, &assi, meds, i).0;
+		}
+		if !changed {
+			break;
+		}
+		assign_nearest::<M, N, L>(mat, meds, &mut assi);
+	}
+	assign_nearest(mat, meds, &mut assi)
+}
+
 /// Assign each to the nearest medoid, return loss
 #[inline]
 #[allow(clippy::or_fun_call)] // zero() is fine
@@ -76,17 +270,10 @@ where
 	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
 	assert!(k <= n, "k must be at most N");
 	debug_assert!(data.len() == n, "data not preallocated");
-	let firstcenter = med[0];
 	data.iter_mut()
 		.enumerate()
 		.map(|(i, di)| {
-			let mut best = (0, mat.get(i, firstcenter));
-			for (m, &mm) in med.iter().enumerate().skip(1) {
-				let dm = mat.get(i, mm);
-				if dm < best.1 || i == mm {
-					best = (m, dm);
-				}
-			}
+			let best = nearest_medoid(mat, med, i);
 			*di = best.0;
 			L::from(best.1)
 		})
@@ -94,10 +281,27 @@ where
 		.unwrap_or(L::zero())
 }
 
+/// Find the nearest medoid of object i, a medoid is always nearest to itself
+#[inline]
+pub(crate) fn nearest_medoid<M, N>(mat: &M, med: &[usize], i: usize) -> (usize, N)
+where
+	N: PartialOrd + Copy,
+	M: ArrayAdapter<N>,
+{
+	let mut best = (0, mat.get(i, med[0]));
+	for (m, &mm) in med.iter().enumerate().skip(1) {
+		let dm = mat.get(i, mm);
+		if dm < best.1 || i == mm {
+			best = (m, dm);
+		}
+	}
+	best
+}
+
 #[cfg(test)]
 mod tests {
 	// TODO: use a larger, much more interesting example.
-	use crate::{alternating, arrayadapter::LowerTriangle, silhouette, util::assert_array};
+	use crate::{canonicalize, alternating, arrayadapter::LowerTriangle, assign_to_medoids, optimal_single_medoid, pam, refine_medoids_geometric_median, reelect_medoid_for_cluster, silhouette, util::assert_array};
 
 	#[test]
 	fn test_alternating() {
@@ -106,12 +310,82 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![0, 1];
-		let (loss, assi, n_iter): (i64, _, _) = alternating(&data, &mut meds, 10);
+		let (loss, mut assi, n_iter): (i64, _, _) = alternating(&data, &mut meds, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		assert_eq!(n_iter, 3, "iterations not as expected");
 		assert_eq!(loss, 4, "loss not as expected");
-		assert_array(assi, vec![1, 1, 1, 0, 0], "assignment not as expected");
-		assert_array(meds, vec![3, 0], "medoids not as expected");
+		assert_array(assi, vec![0, 0, 0, 1, 1], "assignment not as expected");
+		assert_array(meds, vec![0, 3], "medoids not as expected");
 		assert_eq!(sil, 0.7522494172494172, "Silhouette not as expected");
 	}
+
+	#[test]
+	fn test_reelect_medoid_for_cluster() {
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let mut rng = StdRng::seed_from_u64(3);
+		let data = LowerTriangle { n: 50, data: (0..1225).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>() };
+		let (loss, mut assi, meds, _, _): (f64, _, _, _, _) = pam(&data, 4, 100);
+		// the medoids of a converged PAM run are already optimal
+		let mut total = 0.;
+		for (c, &m) in meds.iter().enumerate() {
+			let (med, closs): (_, f64) = reelect_medoid_for_cluster(&data, &assi, c);
+			assert_eq!(med, m, "medoid not as expected");
+			total += closs;
+		}
+		assert!((total - loss).abs() < 1e-10, "loss not as expected");
+		// after merging two clusters, the merged cluster gets a new medoid
+		assi.iter_mut().filter(|a| **a == 1).for_each(|a| *a = 0);
+		let (med, _): (_, f64) = reelect_medoid_for_cluster(&data, &assi, 0);
+		assert_eq!(assi[med], 0, "medoid not in the cluster");
+	}
+
+	#[test]
+	fn test_optimal_single_medoid() {
+		use crate::arrayadapter::ArrayAdapter;
+		use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+		let mut rng = StdRng::seed_from_u64(4);
+		// small integer distances, to have many ties
+		let data = LowerTriangle { n: 40, data: (0..780).map(|_| rng.gen_range(1..5)).collect::<Vec<u32>>() };
+		for m in [1, 2, 7, 25, 40] {
+			let mut points = rand::seq::index::sample(&mut rng, 40, m).into_vec();
+			points.shuffle(&mut rng);
+			let sum = |j: usize| points.iter().map(|&i| data.get(j, i) as i64).sum::<i64>();
+			let mut expect = (points[0], sum(points[0]));
+			for &j in points.iter() {
+				if sum(j) < expect.1 {
+					expect = (j, sum(j));
+				}
+			}
+			let result: (_, i64) = optimal_single_medoid(&data, &points);
+			assert_eq!(result, expect, "medoid not as expected");
+		}
+		// with all points, the same as pam with k = 1
+		let (loss, _, meds, _, _): (i64, _, _, _, _) = pam(&data, 1, 10);
+		let result: (_, i64) = optimal_single_medoid(&data, &(0..40).collect::<Vec<_>>());
+		assert_eq!(result, (meds[0], loss), "medoid not as expected");
+	}
+
+	#[test]
+	fn test_refine_medoids_geometric_median() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		// a converged PAM result cannot be improved
+		let (loss, assi, mut meds, _, _): (i64, _, _, _, _) = pam(&data, 2, 100);
+		let expect = meds.clone();
+		let rloss: i64 = refine_medoids_geometric_median(&data, &mut meds, &assi, 10);
+		assert_eq!((rloss, meds), (loss, expect), "PAM result not as expected");
+		// but a poor solution is improved
+		let mut meds = vec![1, 4];
+		let (iloss, assi): (i64, _) = assign_to_medoids(&data, &meds);
+		let rloss: i64 = refine_medoids_geometric_median(&data, &mut meds, &assi, 10);
+		assert!(rloss < iloss, "loss not improved");
+		assert_eq!((rloss, meds), (loss, vec![0, 4]), "refined result not as expected");
+		// zero rounds keep the medoids
+		let mut meds = vec![1, 4];
+		let rloss: i64 = refine_medoids_geometric_median(&data, &mut meds, &assi, 0);
+		assert_eq!((rloss, meds), (iloss, vec![1, 4]), "unrefined result not as expected");
+	}
 }
@@ -0,0 +1,1326 @@
+use crate::arrayadapter::ArrayAdapter;
+use core::ops::{Div, Sub};
+use num_traits::Zero;
+use std::convert::From;
+
+/// Compute the distances of all objects to all medoids.
+///
+/// The columns are in the order of the medoids as given, i.e., column `m` contains
+/// the distances to `meds[m]`, and hence the argmin of each row is the cluster
+/// assignment. See [`distances_to_medoids_vec`] for a version without `ndarray`.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+///
+/// returns an n x k matrix of distances
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let dist = kmedoids::distances_to_medoids(&data, &[0, 3]);
+/// assert_eq!(dist, ndarray::arr2(&[[0,3],[1,5],[2,6],[3,0]]));
+/// ```
+#[cfg(feature = "ndarray")]
+pub fn distances_to_medoids<M, N>(mat: &M, meds: &[usize]) -> ndarray::Array2<N>
+where
+	N: Copy,
+	M: ArrayAdapter<N>,
+{
+	let rows = distances_to_medoids_vec(mat, meds);
+	ndarray::Array2::from_shape_vec((rows.len(), meds.len()), rows.into_iter().flatten().collect()).unwrap()
+}
+
+/// Compute the distances of all objects to all medoids, as one vector per object.
+///
+/// Entry `m` of row `i` is the distance of object `i` to `meds[m]`, as in
+/// [`distances_to_medoids`], which returns an `ndarray::Array2` instead.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+///
+/// returns an n x k matrix of distances, as one vector per object
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = kmedoids::arrayadapter::LowerTriangle { n: 4, data: vec![1, 2, 4, 3, 5, 6] };
+/// let dist = kmedoids::distances_to_medoids_vec(&data, &[0, 3]);
+/// assert_eq!(dist, vec![vec![0, 3], vec![1, 5], vec![2, 6], vec![3, 0]]);
+/// ```
+pub fn distances_to_medoids_vec<M, N>(mat: &M, meds: &[usize]) -> Vec<Vec<N>>
+where
+	N: Copy,
+	M: ArrayAdapter<N>,
+{
+	check_medoids(mat, meds);
+	(0..mat.len()).map(|i| meds.iter().map(|&m| mat.get(i, m)).collect()).collect()
+}
+
+/// Compute the distances between all pairs of medoids.
+///
+/// Row and column `m` correspond to `meds[m]`, i.e., to cluster `m`. The matrix is
+/// symmetric, and the diagonal is zero. See [`medoid_distance_matrix_vec`] for a
+/// version without `ndarray`.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+///
+/// returns a k x k matrix of distances
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let dist = kmedoids::medoid_distance_matrix(&data, &[0, 2, 3]);
+/// assert_eq!(dist, ndarray::arr2(&[[0,2,3],[2,0,6],[3,6,0]]));
+/// ```
+#[cfg(feature = "ndarray")]
+pub fn medoid_distance_matrix<M, N>(mat: &M, meds: &[usize]) -> ndarray::Array2<N>
+where
+	N: Zero + Copy,
+	M: ArrayAdapter<N>,
+{
+	let rows = medoid_distance_matrix_vec(mat, meds);
+	ndarray::Array2::from_shape_vec((meds.len(), meds.len()), rows.into_iter().flatten().collect()).unwrap()
+}
+
+/// Compute the distances between all pairs of medoids, as one vector per medoid.
+///
+/// The same as [`medoid_distance_matrix`], which returns an `ndarray::Array2` instead.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+///
+/// returns a k x k matrix of distances, as one vector per medoid
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = kmedoids::arrayadapter::LowerTriangle { n: 4, data: vec![1, 2, 4, 3, 5, 6] };
+/// let dist = kmedoids::medoid_distance_matrix_vec(&data, &[0, 2, 3]);
+/// assert_eq!(dist, vec![vec![0, 2, 3], vec![2, 0, 6], vec![3, 6, 0]]);
+/// ```
+pub fn medoid_distance_matrix_vec<M, N>(mat: &M, meds: &[usize]) -> Vec<Vec<N>>
+where
+	N: Zero + Copy,
+	M: ArrayAdapter<N>,
+{
+	check_medoids(mat, meds);
+	let k = meds.len();
+	let mut dist = vec![vec![N::zero(); k]; k];
+	for a in 1..k {
+		for b in 0..a {
+			let d = mat.get(meds[a], meds[b]);
+			dist[a][b] = d;
+			dist[b][a] = d;
+		}
+	}
+	dist
+}
+
+/// Compute the graph of the clusters, with an edge for every pair of medoids
+/// with a distance of at most the threshold.
+///
+/// The nodes are the positions in the medoid list, i.e., the cluster labels.
+/// Every edge `(a, b, d)` is listed once with `a < b`, ordered by `a` and then `b`.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+/// * `threshold` - the maximum distance of an edge, or `None` for all pairs
+///
+/// returns the edge list, with the two cluster labels and their medoid distance
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let edges = kmedoids::medoid_graph(&data, &[0, 2, 3], Some(3));
+/// assert_eq!(edges, vec![(0, 1, 2), (0, 2, 3)]);
+/// ```
+pub fn medoid_graph<M, N>(mat: &M, meds: &[usize], threshold: Option<N>) -> Vec<(usize, usize, N)>
+where
+	N: PartialOrd + Copy,
+	M: ArrayAdapter<N>,
+{
+	check_medoids(mat, meds);
+	(0..meds.len()).flat_map(|a| medoid_edges(mat, meds, threshold, a)).collect()
+}
+
+/// Edges from the medoid at position a to all later medoids, not exposed (use medoid_graph)
+pub(crate) fn medoid_edges<M, N>(mat: &M, meds: &[usize], threshold: Option<N>, a: usize) -> Vec<(usize, usize, N)>
+where
+	N: PartialOrd + Copy,
+	M: ArrayAdapter<N>,
+{
+	((a + 1)..meds.len())
+		.map(|b| (a, b, mat.get(meds[a], meds[b])))
+		.filter(|e| match threshold { Some(t) => e.2 <= t, None => true })
+		.collect()
+}
+
+/// Compute the minimum spanning tree of the medoids.
+///
+/// The nodes are the positions in the medoid list, i.e., the cluster labels, and the
+/// tree connects them with the smallest total medoid distance. Every edge `(a, b, d)` is
+/// listed once with `a < b`, ordered by distance, so that nearby clusters come first, as in
+/// a single-linkage hierarchy of the clusters. Uses Prim's algorithm in O(k²) time.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+///
+/// returns the k - 1 edges of the tree, with the two cluster labels and their medoid distance
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let edges = kmedoids::medoid_mst(&data, &[0, 2, 3]);
+/// assert_eq!(edges, vec![(0, 1, 2), (0, 2, 3)]);
+/// ```
+pub fn medoid_mst<M, N>(mat: &M, meds: &[usize]) -> Vec<(usize, usize, N)>
+where
+	N: PartialOrd + Copy,
+	M: ArrayAdapter<N>,
+{
+	check_medoids(mat, meds);
+	let k = meds.len();
+	if k == 0 {
+		return vec![];
+	}
+	// nearest tree node and distance of every node not yet in the tree
+	let mut near = (0..k).map(|b| (0, mat.get(meds[0], meds[b]))).collect::<Vec<_>>();
+	let mut in_tree = vec![false; k];
+	in_tree[0] = true;
+	let mut edges = Vec::with_capacity(k - 1);
+	for _ in 1..k {
+		let mut b = usize::MAX;
+		for c in 0..k {
+			if !in_tree[c] && (b == usize::MAX || near[c].1 < near[b].1) {
+				b = c;
+			}
+		}
+		in_tree[b] = true;
+		let (a, d) = near[b];
+		edges.push((a.min(b), a.max(b), d));
+		for c in 0..k {
+			if !in_tree[c] {
+				let dc = mat.get(meds[b], meds[c]);
+				if dc < near[c].1 {
+					near[c] = (b, dc);
+				}
+			}
+		}
+	}
+	// stable, so ties keep the order in which they were added
+	edges.sort_by(|x, y| x.2.partial_cmp(&y.2).unwrap_or(std::cmp::Ordering::Equal));
+	edges
+}
+
+/// Compute a one-hot cluster membership matrix.
+///
+/// The entry at row `i` and column `c` is 1 if object `i` is assigned to cluster `c`.
+/// Objects labeled as noise (`usize::MAX`) are not assigned to any cluster, and their
+/// row is all zero. See [`membership_matrix_vec`] for a version without `ndarray`.
+///
+/// * `assi` - the cluster assignment
+/// * `k` - the number of clusters
+///
+/// returns an n x k matrix of membership indicators
+///
+/// ## Panics
+///
+/// * panics when a label is neither less than k nor `usize::MAX`
+///
+/// ## Example
+/// ```
+/// let member = kmedoids::membership_matrix(&[0, 1, usize::MAX, 1], 2);
+/// assert_eq!(member, ndarray::arr2(&[[1,0],[0,1],[0,0],[0,1]]));
+/// ```
+#[cfg(feature = "ndarray")]
+pub fn membership_matrix(assi: &[usize], k: usize) -> ndarray::Array2<u8> {
+	let rows = membership_matrix_vec(assi, k);
+	ndarray::Array2::from_shape_vec((assi.len(), k), rows.into_iter().flatten().collect()).unwrap()
+}
+
+/// Compute a one-hot cluster membership matrix, as one vector per object.
+///
+/// The same as [`membership_matrix`], which returns an `ndarray::Array2` instead.
+/// Rows of objects labeled as noise (`usize::MAX`) are all zero.
+///
+/// * `assi` - the cluster assignment
+/// * `k` - the number of clusters
+///
+/// returns an n x k matrix of membership indicators, as one vector per object
+///
+/// ## Panics
+///
+/// * panics when a label is neither less than k nor `usize::MAX`
+///
+/// ## Example
+/// ```
+/// let member = kmedoids::membership_matrix_vec(&[0, 1, usize::MAX], 2);
+/// assert_eq!(member, vec![vec![1, 0], vec![0, 1], vec![0, 0]]);
+/// ```
+pub fn membership_matrix_vec(assi: &[usize], k: usize) -> Vec<Vec<u8>> {
+	check_labels(assi, k);
+	assi.iter().map(|&a| (0..k).map(|c| if a == c { 1 } else { 0 }).collect()).collect()
+}
+
+/// Encode a cluster assignment as a one-hot matrix.
+///
+/// Unlike [`membership_matrix`], noise labels are not allowed, so that every row
+/// contains exactly one 1. The inverse is [`onehot_to_assignments`].
+///
+/// * `assi` - the cluster assignment
+/// * `k` - the number of clusters
+///
+/// returns an n x k matrix, with entry (i, c) = 1 iff object `i` is in cluster `c`
+///
+/// ## Panics
+///
+/// * panics when a label is not less than k
+///
+/// ## Example
+/// ```
+/// let onehot = kmedoids::assignments_to_onehot(&[0, 1, 1], 2);
+/// assert_eq!(onehot, ndarray::arr2(&[[1,0],[0,1],[0,1]]));
+/// ```
+#[cfg(feature = "ndarray")]
+pub fn assignments_to_onehot(assi: &[usize], k: usize) -> ndarray::Array2<u8> {
+	assert!(assi.iter().all(|&a| a < k), "invalid cluster label, must be less than k");
+	membership_matrix(assi, k)
+}
+
+/// Decode a one-hot matrix into a cluster assignment.
+///
+/// This is the inverse of [`assignments_to_onehot`]. Rows that are all zero, as produced
+/// by [`membership_matrix`] for noise, are decoded as noise (`usize::MAX`).
+///
+/// * `mat` - an n x k matrix of zeros and ones, with at most one 1 per row
+///
+/// returns the cluster assignment
+///
+/// ## Panics
+///
+/// * panics when an entry is neither 0 nor 1, or a row contains more than one 1
+///
+/// ## Example
+/// ```
+/// let onehot = ndarray::arr2(&[[1,0],[0,1],[0,1]]);
+/// assert_eq!(kmedoids::onehot_to_assignments(onehot.view()), vec![0, 1, 1]);
+/// ```
+#[cfg(feature = "ndarray")]
+pub fn onehot_to_assignments(mat: ndarray::ArrayView2<u8>) -> Vec<usize> {
+	mat.rows()
+		.into_iter()
+		.map(|r| {
+			assert!(r.iter().all(|&v| v <= 1), "one-hot matrix must contain only zeros and ones");
+			assert!(r.iter().filter(|&&v| v == 1).count() <= 1, "one-hot matrix must contain at most one 1 per row");
+			r.iter().position(|&v| v == 1).unwrap_or(usize::MAX)
+		})
+		.collect()
+}
+
+/// Nearest and second nearest medoid of a point, and the resulting assignment margin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margin<N, L> {
+	/// the nearest medoid (position in the medoid list, i.e., the cluster label)
+	pub near: usize,
+	/// the distance to the nearest medoid, d1
+	pub near_dist: N,
+	/// the second nearest medoid (`usize::MAX` if k = 1)
+	pub seco: usize,
+	/// the distance to the second nearest medoid, d2 (zero if k = 1)
+	pub seco_dist: N,
+	/// the absolute margin d2 - d1
+	pub margin: L,
+	/// the ratio d1 / d2 as used by the medoid silhouette, 0 if d1 = 0
+	pub ratio: L,
+}
+
+/// Compute the assignment margin of every point.
+///
+/// For each point, this gives the nearest and second nearest medoid, as well as
+/// the margin d2 - d1 and the ratio d1 / d2 used by the MSC algorithms. Small margins
+/// (ratios close to 1) indicate ambiguous points. Ties are broken as in the algorithms:
+/// the earlier medoid wins, but a medoid is always nearest to itself.
+/// The medoid silhouette of a point is 1 minus its ratio.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the margins (use a float type)
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+///
+/// returns the margin information of each point
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0, or a medoid index is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let margins: Vec<kmedoids::Margin<_, f64>> = kmedoids::assignment_margins(&data, &[0, 3]);
+/// assert_eq!(margins[1].near, 0);
+/// assert_eq!(margins[1].ratio, 0.2);
+/// ```
+pub fn assignment_margins<M, N, L>(mat: &M, meds: &[usize]) -> Vec<Margin<N, L>>
+where
+	N: Zero + PartialOrd + Copy,
+	L: Div<Output = L> + Sub<Output = L> + Zero + Copy + From<N>,
+	M: ArrayAdapter<N>,
+{
+	check_medoids(mat, meds);
+	assert!(!meds.is_empty(), "k must be at least 1");
+	(0..mat.len())
+		.map(|i| {
+			let (mut near, mut seco) = ((0, mat.get(i, meds[0])), (usize::MAX, N::zero()));
+			for (m, &me) in meds.iter().enumerate().skip(1) {
+				let d = mat.get(i, me);
+				if d < near.1 || i == me {
+					seco = near;
+					near = (m, d);
+				} else if seco.0 == usize::MAX || d < seco.1 {
+					seco = (m, d);
+				}
+			}
+			let (d1, d2) = (<L as From<N>>::from(near.1), <L as From<N>>::from(seco.1));
+			let ratio = if N::is_zero(&near.1) || N::is_zero(&seco.1) { L::zero() } else { d1 / d2 };
+			Margin {
+				near: near.0,
+				near_dist: near.1,
+				seco: seco.0,
+				seco_dist: seco.1,
+				margin: if seco.0 == usize::MAX { L::zero() } else { d2 - d1 },
+				ratio,
+			}
+		})
+		.collect()
+}
+
+/// Compute the confidence of the cluster assignment of every point.
+///
+/// The confidence is `(d2 - d1) / d2`, where d1 is the distance to the assigned medoid,
+/// and d2 is the distance to the nearest other medoid. It is 0 if the point is equidistant
+/// to both, and approaches 1 if the assigned medoid is much closer than any other.
+/// For the nearest medoid assignment of the algorithms, the confidence is in [0, 1], and
+/// it equals the medoid silhouette of the point; it is negative if a point is assigned to
+/// a farther medoid. If d2 is zero, the confidence is 0, and with a single medoid it is 1.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the confidence (use a float type)
+/// * `mat` - a pairwise distance matrix
+/// * `assi` - the cluster assignment
+/// * `meds` - the medoid list
+///
+/// returns the confidence of each point
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0, or a medoid index is not less than N
+/// * panics when the assignment does not have length N, or a label is not less than k
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let conf: Vec<f64> = kmedoids::assignment_confidence(&data, &[0, 0, 0, 1], &[0, 3]);
+/// assert_eq!(conf[1], 0.8);
+/// ```
+pub fn assignment_confidence<M, N, L>(mat: &M, assi: &[usize], meds: &[usize]) -> Vec<L>
+where
+	N: Zero + PartialOrd + Copy,
+	L: num_traits::Float + From<N>,
+	M: ArrayAdapter<N>,
+{
+	check_medoids(mat, meds);
+	assert!(!meds.is_empty(), "k must be at least 1");
+	assert_eq!(assi.len(), mat.len(), "assignment must have length N");
+	assert!(assi.iter().all(|&a| a < meds.len()), "invalid cluster label, must be less than k");
+	assi.iter()
+		.enumerate()
+		.map(|(i, &a)| {
+			let d1 = mat.get(i, meds[a]);
+			let d2 = meds.iter().enumerate().filter(|&(m, _)| m != a).map(|(_, &me)| mat.get(i, me)).reduce(|x, y| if y < x { y } else { x });
+			match d2 {
+				None => L::one(), // single medoid
+				Some(d2) if N::is_zero(&d2) => L::zero(),
+				Some(d2) => {
+					let (d1, d2) = (<L as From<N>>::from(d1), <L as From<N>>::from(d2));
+					(d2 - d1) / d2
+				}
+			}
+		})
+		.collect()
+}
+
+/// Find the m nearest medoids of every point.
+///
+/// This generalizes the nearest, second and third nearest medoid tracking of the
+/// algorithms, and uses the same tie-breaking: the earlier medoid wins, but a medoid
+/// is always nearest to itself. It uses a small insertion buffer of size m, rather
+/// than sorting all k distances of each point.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+/// * `m` - the number of nearest medoids to find (all medoids if m >= k)
+///
+/// returns for each point the m nearest medoids (position in the medoid list, i.e.,
+/// the cluster label) and their distances, sorted by distance
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let near = kmedoids::nearest_medoids(&data, &[0, 1, 3], 2);
+/// assert_eq!(near[2], vec![(0, 2), (1, 4)]);
+/// ```
+pub fn nearest_medoids<M, N>(mat: &M, meds: &[usize], m: usize) -> Vec<Vec<(usize, N)>>
+where
+	N: PartialOrd + Copy,
+	M: ArrayAdapter<N>,
+{
+	check_medoids(mat, meds);
+	(0..mat.len()).map(|i| select_nearest(mat, meds, i, m)).collect()
+}
+
+/// Find the topk nearest medoids of every point, using a binary heap.
+///
+/// Each point keeps a max-heap of the topk nearest medoids seen so far, so this needs
+/// O(n k log topk) time, which is preferable to [`nearest_medoids`] for large topk.
+/// The tie-breaking is the same: the earlier medoid wins, but a medoid is always
+/// nearest to itself. With `topk = 1`, this gives the same assignment as
+/// [`assign_to_medoids`](crate::assign_to_medoids).
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+/// * `topk` - the number of nearest medoids to find (all medoids if topk >= k)
+///
+/// returns for each point the topk nearest medoids (position in the medoid list, i.e.,
+/// the cluster label), sorted by distance
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let near = kmedoids::assign_topk_medoids(&data, &[0, 1, 3], 2);
+/// assert_eq!(near[2], vec![0, 1]);
+/// ```
+pub fn assign_topk_medoids<M, N>(mat: &M, meds: &[usize], topk: usize) -> Vec<Vec<usize>>
+where
+	N: PartialOrd + Copy,
+	M: ArrayAdapter<N>,
+{
+	check_medoids(mat, meds);
+	let topk = topk.min(meds.len());
+	let mut heap = std::collections::BinaryHeap::with_capacity(topk + 1);
+	(0..mat.len())
+		.map(|i| {
+			heap.clear();
+			if topk == 0 {
+				return Vec::new();
+			}
+			for (c, &me) in meds.iter().enumerate() {
+				let cand = HeapEntry { own: i == me, d: mat.get(i, me), c };
+				if heap.len() < topk {
+					heap.push(cand);
+				} else if cand < *heap.peek().unwrap() {
+					heap.pop();
+					heap.push(cand);
+				}
+			}
+			let mut near = heap.drain().collect::<Vec<_>>();
+			near.sort_unstable();
+			near.iter().map(|e| e.c).collect()
+		})
+		.collect()
+}
+
+/// Heap entry of [`assign_topk_medoids`], ordered by distance, with the own medoid
+/// first, and the earlier medoid on ties
+struct HeapEntry<N> {
+	own: bool,
+	d: N,
+	c: usize,
+}
+impl<N: PartialOrd> Ord for HeapEntry<N> {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		other.own.cmp(&self.own)
+			.then_with(|| self.d.partial_cmp(&other.d).unwrap_or(std::cmp::Ordering::Equal))
+			.then_with(|| self.c.cmp(&other.c))
+	}
+}
+impl<N: PartialOrd> PartialOrd for HeapEntry<N> {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+impl<N: PartialOrd> PartialEq for HeapEntry<N> {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == std::cmp::Ordering::Equal
+	}
+}
+impl<N: PartialOrd> Eq for HeapEntry<N> {}
+
+/// Find the nearest medoids of a single query point, e.g., for recommendations.
+///
+/// Uses the same tie-breaking as [`nearest_medoids`]: the earlier medoid wins, but a
+/// medoid is always nearest to itself. With `k_near = 1`, this is the cluster of the
+/// point in [`assign_to_medoids`](crate::assign_to_medoids). The distances to all
+/// medoids are partially sorted, in O(k + k_near log k_near) time, which is preferable
+/// to [`nearest_medoids`] for a large `k_near`.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `query` - the query point
+/// * `meds` - the medoid list
+/// * `k_near` - the number of nearest medoids to find (all medoids if k_near >= k)
+///
+/// returns the k_near nearest medoids (position in the medoid list, i.e., the cluster
+/// label) and their distances, sorted by distance
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index or the query is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let near = kmedoids::k_nearest_medoids(&data, 2, &[0, 1, 3], 2);
+/// assert_eq!(near, vec![(0, 2), (1, 4)]);
+/// ```
+pub fn k_nearest_medoids<M, N>(mat: &M, query: usize, meds: &[usize], k_near: usize) -> Vec<(usize, N)>
+where
+	N: PartialOrd + Copy,
+	M: ArrayAdapter<N>,
+{
+	check_medoids(mat, meds);
+	assert!(query < mat.len(), "invalid query, must be less than N");
+	let k_near = k_near.min(meds.len());
+	if k_near == 0 {
+		return Vec::new();
+	}
+	let mut cand = meds.iter().enumerate().map(|(c, &me)| (query != me, mat.get(query, me), c)).collect::<Vec<_>>();
+	// the own medoid first, then by distance, then the earlier medoid
+	let order = |a: &(bool, N, usize), b: &(bool, N, usize)| {
+		a.0.cmp(&b.0)
+			.then(a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+			.then(a.2.cmp(&b.2))
+	};
+	if k_near < cand.len() {
+		cand.select_nth_unstable_by(k_near - 1, order);
+		cand.truncate(k_near);
+	}
+	cand.sort_unstable_by(order);
+	cand.into_iter().map(|(_, d, c)| (c, d)).collect()
+}
+
+/// Select the m nearest medoids of a point, by insertion into a small buffer
+#[inline]
+pub(crate) fn select_nearest<M, N>(mat: &M, meds: &[usize], i: usize, m: usize) -> Vec<(usize, N)>
+where
+	N: PartialOrd + Copy,
+	M: ArrayAdapter<N>,
+{
+	let m = m.min(meds.len());
+	let mut buf = Vec::<(usize, N)>::with_capacity(m);
+	if m == 0 {
+		return buf;
+	}
+	for (c, &me) in meds.iter().enumerate() {
+		let d = mat.get(i, me);
+		// the medoid itself always comes first, otherwise earlier medoids win ties
+		let pos = if i == me { 0 } else { buf.iter().position(|p| d < p.1).unwrap_or(buf.len()) };
+		if pos < m {
+			if buf.len() == m {
+				buf.pop();
+			}
+			buf.insert(pos, (c, d));
+		}
+	}
+	buf
+}
+
+/// Compute how often each point is chosen as a medoid, over multiple random restarts.
+///
+/// Runs FasterPAM `n_runs` times with random initial medoids, and reports for every
+/// point the fraction of runs in which it is one of the final medoids. Points with a
+/// high stability are "core medoids" of the data, while an unstable medoid indicates
+/// that the clustering depends on the random initialization.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * type `R` - random number generator type
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `n_runs` - the number of runs
+/// * `maxiter` - the maximum number of iterations allowed in each run
+/// * `rng` - random number generator for the initial medoids
+///
+/// returns for each point the fraction of runs with this point as medoid
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+/// * panics when n_runs is 0
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let stability = kmedoids::medoid_stability::<_, _, i64, _>(&data, 2, 10, 100, &mut rand::thread_rng());
+/// println!("Stability is: {:?}", stability);
+/// ```
+#[cfg(feature = "rand")]
+pub fn medoid_stability<M, N, L, R>(mat: &M, k: usize, n_runs: usize, maxiter: usize, rng: &mut R) -> Vec<f64>
+where
+	N: Zero + PartialOrd + Copy,
+	L: core::ops::AddAssign + num_traits::Signed + Zero + PartialOrd + Copy + From<N> + num_traits::FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+	R: rand::Rng,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n_runs > 0, "at least one run is required");
+	let mut count = vec![0usize; n];
+	for _ in 0..n_runs {
+		let mut meds = crate::random_initialization(n, k, rng);
+		let _: (L, _, _, _) = crate::fasterpam(mat, &mut meds, 0, maxiter);
+		for &m in meds.iter() {
+			count[m] += 1;
+		}
+	}
+	count.iter().map(|&c| c as f64 / n_runs as f64).collect()
+}
+
+/// Convergence statistics of multiple runs, see [`convergence_rate_analysis`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConvergenceStats {
+	/// the average number of iterations until convergence (or maxiter)
+	pub mean_iterations_to_convergence: f64,
+	/// the (population) standard deviation of the number of iterations
+	pub std_iterations: f64,
+	/// the fraction of runs finding the best loss of all runs
+	pub fraction_reaching_optimal: f64,
+	/// the average relative gap |loss - best| / |best| to the best loss of all runs
+	pub mean_suboptimality_gap: f64,
+}
+
+/// Analyze the convergence of a k-medoids algorithm over random restarts.
+///
+/// The algorithm is run `n_runs` times from random initial medoids. Because the true optimum
+/// is not known, the best loss of all runs (by the objective) is used as the reference for
+/// the fraction of runs reaching the optimum and for the suboptimality gap (which is 0 if the
+/// best loss is 0). This is useful to compare initialization strategies or algorithm variants.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss
+/// * type `R` - random number generator type
+/// * type `F` - the algorithm
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `n_runs` - the number of runs
+/// * `maxiter` - the maximum number of iterations allowed in each run
+/// * `objective` - whether the loss is minimized, or maximized as the medoid silhouette
+/// * `rng` - random number generator for the initial medoids
+/// * `algorithm` - runs the algorithm on the matrix from the given initial medoids, with
+///   the given maxiter, and returns the loss and the number of iterations
+///
+/// returns the convergence statistics
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+/// * panics when n_runs is 0
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let stats = kmedoids::convergence_rate_analysis(&data, 2, 10, 100, kmedoids::Objective::Minimize, &mut rand::thread_rng(), |mat, meds, maxiter| {
+///     let (loss, _, n_iter, _): (i64, _, _, _) = kmedoids::fasterpam(mat, meds, 0, maxiter);
+///     (loss, n_iter)
+/// });
+/// println!("Mean iterations: {}", stats.mean_iterations_to_convergence);
+/// ```
+#[cfg(feature = "rand")]
+pub fn convergence_rate_analysis<M, N, L, R, F>(
+	mat: &M,
+	k: usize,
+	n_runs: usize,
+	maxiter: usize,
+	objective: crate::Objective,
+	rng: &mut R,
+	mut algorithm: F,
+) -> ConvergenceStats
+where
+	L: PartialOrd + Copy + num_traits::ToPrimitive,
+	M: ArrayAdapter<N>,
+	R: rand::Rng,
+	F: FnMut(&M, &mut Vec<usize>, usize) -> (L, usize),
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n_runs > 0, "at least one run is required");
+	let runs = (0..n_runs)
+		.map(|_| {
+			let mut meds = crate::random_initialization(n, k, rng);
+			algorithm(mat, &mut meds, maxiter)
+		})
+		.collect::<Vec<_>>();
+	let best = runs.iter().map(|r| r.0).reduce(|a, b| if objective.is_better(b, a) { b } else { a }).unwrap();
+	let best_f = best.to_f64().unwrap();
+	let nf = n_runs as f64;
+	let mean = runs.iter().map(|r| r.1 as f64).sum::<f64>() / nf;
+	let var = runs.iter().map(|r| (r.1 as f64 - mean).powi(2)).sum::<f64>() / nf;
+	let n_optimal = runs.iter().filter(|r| r.0 == best).count();
+	let gap = if best_f == 0. {
+		0.
+	} else {
+		runs.iter().map(|r| (r.0.to_f64().unwrap() - best_f).abs() / best_f.abs()).sum::<f64>() / nf
+	};
+	ConvergenceStats {
+		mean_iterations_to_convergence: mean,
+		std_iterations: var.sqrt(),
+		fraction_reaching_optimal: n_optimal as f64 / nf,
+		mean_suboptimality_gap: gap,
+	}
+}
+
+/// Compute the Hausdorff distance between two sets of medoids.
+///
+/// This is the largest distance from a medoid of one set to the nearest medoid of the
+/// other set, i.e., how far the two solutions differ in the original distance space.
+/// It is 0 if the sets are equal, and independent of the order of the medoids.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `meds_a` - the first medoid list
+/// * `meds_b` - the second medoid list
+/// * `mat` - a pairwise distance matrix
+///
+/// returns the Hausdorff distance of the medoid sets
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index is not less than N
+/// * panics when a medoid list is empty
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// assert_eq!(kmedoids::medoid_set_distance(&[0, 3], &[1, 3], &data), 1.);
+/// ```
+pub fn medoid_set_distance<M, N>(meds_a: &[usize], meds_b: &[usize], mat: &M) -> f64
+where
+	N: PartialOrd + Copy + num_traits::ToPrimitive,
+	M: ArrayAdapter<N>,
+{
+	check_medoids(mat, meds_a);
+	check_medoids(mat, meds_b);
+	assert!(!meds_a.is_empty() && !meds_b.is_empty(), "medoid lists must not be empty");
+	// largest distance of a medoid in a to its nearest medoid in b
+	let directed = |a: &[usize], b: &[usize]| {
+		a.iter()
+			.map(|&i| b.iter().map(|&j| mat.get(i, j).to_f64().unwrap()).fold(f64::INFINITY, f64::min))
+			.fold(0., f64::max)
+	};
+	directed(meds_a, meds_b).max(directed(meds_b, meds_a))
+}
+
+/// Compute the Jaccard index of two sets of medoids.
+///
+/// This is the number of medoids in both sets divided by the number of medoids in either
+/// set, i.e., 1 for equal sets and 0 for disjoint sets. Duplicates and the order are ignored.
+/// Unlike [`medoid_set_distance`], this does not consider how close different medoids are.
+///
+/// * `meds_a` - the first medoid list
+/// * `meds_b` - the second medoid list
+///
+/// returns the Jaccard index of the medoid sets, 1 if both are empty
+///
+/// ## Example
+/// ```
+/// assert_eq!(kmedoids::medoid_set_jaccard(&[0, 3, 5], &[3, 4, 5]), 0.5);
+/// ```
+pub fn medoid_set_jaccard(meds_a: &[usize], meds_b: &[usize]) -> f64 {
+	let a = meds_a.iter().collect::<std::collections::HashSet<_>>();
+	let b = meds_b.iter().collect::<std::collections::HashSet<_>>();
+	let union = a.union(&b).count();
+	if union == 0 {
+		return 1.;
+	}
+	a.intersection(&b).count() as f64 / union as f64
+}
+
+/// Validate the medoid indexes
+pub(crate) fn check_medoids<M, N>(mat: &M, meds: &[usize])
+where
+	M: ArrayAdapter<N>,
+{
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	let n = mat.len();
+	assert!(meds.iter().all(|&m| m < n), "invalid medoid index, must be less than N");
+}
+
+/// Validate the cluster labels
+fn check_labels(assi: &[usize], k: usize) {
+	assert!(
+		assi.iter().all(|&a| a < k || a == usize::MAX),
+		"invalid cluster label, must be less than k or usize::MAX for noise"
+	);
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		arrayadapter::{ArrayAdapter, LowerTriangle}, assign_topk_medoids, assignment_confidence, assignment_margins, distances_to_medoids_vec, fasterpam, k_nearest_medoids,
+		medoid_distance_matrix_vec, medoid_graph, medoid_mst, medoid_set_distance, medoid_set_jaccard, medoid_silhouette, membership_matrix_vec, nearest_medoids, util::assert_array, Margin,
+	};
+
+	#[test]
+	#[cfg(feature = "ndarray")]
+	fn test_distances_to_medoids() {
+		use crate::{distances_to_medoids, membership_matrix};
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let mut meds = vec![0, 1, 2];
+		let (_, assi, _, _): (i64, _, _, _) = fasterpam(&data, &mut meds, 0, 10);
+		let dist = distances_to_medoids(&data, &meds);
+		assert_eq!(dist.shape(), &[5, 3], "shape not as expected");
+		// argmin of each row must be the assignment
+		let argmin = dist.rows().into_iter()
+			.map(|r| (0..r.len()).fold(0, |b, m| if r[m] < r[b] { m } else { b }))
+			.collect::<Vec<_>>();
+		assert_array(argmin, assi.clone(), "assignment not as expected");
+
+		let member = membership_matrix(&assi, meds.len());
+		assert_eq!(member.shape(), &[5, 3], "shape not as expected");
+		for (i, r) in member.rows().into_iter().enumerate() {
+			assert_eq!(r.sum(), 1, "membership not as expected");
+			assert_eq!(r[assi[i]], 1, "membership not as expected");
+		}
+		// the same as the vector versions
+		assert_eq!(dist.rows().into_iter().map(|r| r.to_vec()).collect::<Vec<_>>(), distances_to_medoids_vec(&data, &meds), "distances not as expected");
+		assert_eq!(member.rows().into_iter().map(|r| r.to_vec()).collect::<Vec<_>>(), membership_matrix_vec(&assi, meds.len()), "membership not as expected");
+	}
+
+	#[test]
+	fn test_distances_to_medoids_vec() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let mut meds = vec![0, 1, 2];
+		let (_, assi, _, _): (i64, _, _, _) = fasterpam(&data, &mut meds, 0, 10);
+		let dist = distances_to_medoids_vec(&data, &meds);
+		assert_eq!((dist.len(), dist[0].len()), (5, 3), "shape not as expected");
+		for (i, r) in dist.iter().enumerate() {
+			assert!(r.iter().enumerate().all(|(m, &d)| d == data.get(i, meds[m])), "distance not as expected");
+		}
+		// argmin of each row must be the assignment
+		let argmin = dist.iter().map(|r| (0..r.len()).fold(0, |b, m| if r[m] < r[b] { m } else { b })).collect::<Vec<_>>();
+		assert_array(argmin, assi.clone(), "assignment not as expected");
+		assert_eq!(distances_to_medoids_vec(&data, &[]), vec![Vec::<u32>::new(); 5], "no medoids not as expected");
+
+		let member = membership_matrix_vec(&assi, meds.len());
+		assert_eq!(member.len(), 5, "shape not as expected");
+		for (i, r) in member.iter().enumerate() {
+			assert_eq!(r.len(), 3, "shape not as expected");
+			assert_eq!(r.iter().map(|&x| x as usize).sum::<usize>(), 1, "membership not as expected");
+			assert_eq!(r[assi[i]], 1, "membership not as expected");
+		}
+		assert_eq!(membership_matrix_vec(&[1, usize::MAX], 2), vec![vec![0, 1], vec![0, 0]], "noise not as expected");
+	}
+
+	#[test]
+	fn test_assignment_confidence() {
+		// points on a line: 0 and 10 are medoids, 5 is in the middle, 1 is near 0
+		let points = [0i32, 10, 5, 1, 9];
+		let mut data = Vec::new();
+		for i in 1..points.len() {
+			for j in 0..i {
+				data.push((points[i] - points[j]).unsigned_abs());
+			}
+		}
+		let data = LowerTriangle { n: 5, data };
+		let meds = vec![0, 1];
+		let conf: Vec<f64> = assignment_confidence(&data, &[0, 1, 0, 0, 1], &meds);
+		assert_eq!(conf[0], 1., "medoid not confident");
+		assert_eq!(conf[2], 0., "equidistant point not as expected");
+		assert_eq!(conf[3], 8. / 9., "close point not as expected");
+		assert_eq!(conf[4], 8. / 9., "close point not as expected");
+		assert!(conf.iter().all(|&c| (0. ..=1.).contains(&c)), "confidence not in [0, 1]");
+		let conf: Vec<f64> = assignment_confidence(&data, &[0, 1, 0, 1, 1], &meds);
+		assert_eq!(conf[3], -8., "farther medoid not as expected");
+		// the medoid silhouette of the nearest assignment
+		let (_, assi) = crate::assign_to_medoids::<_, _, i64>(&data, &meds);
+		let (_, sil): (f64, _) = medoid_silhouette(&data, &meds, true);
+		let conf: Vec<f64> = assignment_confidence(&data, &assi, &meds);
+		assert_eq!(conf, sil, "not the medoid silhouette");
+		let conf: Vec<f64> = assignment_confidence(&data, &[0; 5], &[2]);
+		assert_eq!(conf, vec![1.; 5], "single medoid not as expected");
+	}
+
+	#[test]
+	#[cfg(feature = "ndarray")]
+	fn test_medoid_graph() {
+		use crate::medoid_distance_matrix;
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let meds = vec![0, 2, 4];
+		let dist = medoid_distance_matrix(&data, &meds);
+		assert_eq!(dist, ndarray::arr2(&[[0, 2, 7], [2, 0, 9], [7, 9, 0]]), "distances not as expected");
+		assert_eq!(dist, dist.t(), "not symmetric");
+		assert!(dist.diag().iter().all(|&d| d == 0), "diagonal not as expected");
+		assert_eq!(dist.rows().into_iter().map(|r| r.to_vec()).collect::<Vec<_>>(), medoid_distance_matrix_vec(&data, &meds), "distances not as expected");
+		assert_eq!(medoid_graph(&data, &meds, None), vec![(0, 1, 2), (0, 2, 7), (1, 2, 9)], "edges not as expected");
+		assert_eq!(medoid_graph(&data, &meds, Some(7)), vec![(0, 1, 2), (0, 2, 7)], "edges not as expected");
+		assert_eq!(medoid_graph(&data, &meds, Some(1)), vec![], "edges not as expected");
+		assert_eq!(medoid_graph(&data, &[3], None), vec![], "edges not as expected");
+	}
+
+	#[test]
+	fn test_medoid_distance_matrix_vec() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let dist = medoid_distance_matrix_vec(&data, &[0, 2, 4]);
+		assert_eq!(dist, vec![vec![0, 2, 7], vec![2, 0, 9], vec![7, 9, 0]], "distances not as expected");
+		assert_eq!(medoid_distance_matrix_vec(&data, &[3]), vec![vec![0]], "single medoid not as expected");
+	}
+
+	#[test]
+	fn test_medoid_mst() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let meds = vec![0, 2, 4];
+		let edges = medoid_mst(&data, &meds);
+		assert_eq!(edges, vec![(0, 1, 2), (0, 2, 7)], "edges not as expected");
+		assert_eq!(edges.iter().map(|e| e.2).sum::<u32>(), 9, "total weight not as expected");
+		// all medoids of a larger tree are reachable from the first
+		let meds = vec![4, 1, 3, 0, 2];
+		let edges = medoid_mst(&data, &meds);
+		assert_eq!(edges.len(), meds.len() - 1, "number of edges not as expected");
+		let mut reached = [true, false, false, false, false];
+		for _ in 0..meds.len() {
+			for &(a, b, _) in edges.iter() {
+				if reached[a] || reached[b] {
+					reached[a] = true;
+					reached[b] = true;
+				}
+			}
+		}
+		assert!(reached.iter().all(|&r| r), "tree not connected");
+		assert!(edges.windows(2).all(|w| w[0].2 <= w[1].2), "edges not ordered by distance");
+		assert_eq!(edges.iter().map(|e| e.2).sum::<u32>(), 1 + 1 + 2 + 4, "total weight not as expected");
+		assert_eq!(medoid_mst(&data, &[3]), vec![], "edges not as expected");
+	}
+
+	#[test]
+	fn test_medoid_set_comparison() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		assert_eq!(medoid_set_distance(&[0, 2], &[2, 0], &data), 0., "distance of equal sets not as expected");
+		assert_eq!(medoid_set_distance(&[0, 2], &[0, 4], &data), 7., "distance not as expected");
+		assert_eq!(medoid_set_distance(&[0, 4], &[0, 2], &data), 7., "distance not symmetric");
+		assert_eq!(medoid_set_distance(&[3], &[4], &data), 1., "distance not as expected");
+		assert_eq!(medoid_set_jaccard(&[0, 2, 4], &[4, 0, 2]), 1., "jaccard of equal sets not as expected");
+		assert_eq!(medoid_set_jaccard(&[0, 2], &[1, 3, 4]), 0., "jaccard of disjoint sets not as expected");
+		assert_eq!(medoid_set_jaccard(&[0, 2], &[0, 4]), 1. / 3., "jaccard not as expected");
+		assert_eq!(medoid_set_jaccard(&[], &[]), 1., "jaccard of empty sets not as expected");
+	}
+
+	#[test]
+	#[should_panic]
+	fn test_medoid_graph_invalid_medoid() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		medoid_graph(&data, &[0, 5], None);
+	}
+
+	#[test]
+	#[should_panic]
+	#[cfg(feature = "ndarray")]
+	fn test_membership_invalid_label() {
+		use crate::membership_matrix;
+		membership_matrix(&[0, 1, 2], 2);
+	}
+
+	#[test]
+	fn test_assignment_margins() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for k in 1..=3 {
+			let mut meds = (0..k).collect::<Vec<_>>();
+			let (_, assi, _, _): (i64, _, _, _) = fasterpam(&data, &mut meds, 0, 10);
+			let margins: Vec<Margin<_, f64>> = assignment_margins(&data, &meds);
+			let (_, sil): (f64, _) = medoid_silhouette(&data, &meds, true);
+			assert_array(margins.iter().map(|m| m.near).collect(), assi, "assignment not as expected");
+			for (i, m) in margins.iter().enumerate() {
+				assert_eq!(1. - m.ratio, sil[i], "ratio not as expected");
+				if k > 1 {
+					assert!(m.near_dist <= m.seco_dist, "distances not as expected");
+					assert_eq!(m.margin, (m.seco_dist - m.near_dist) as f64, "margin not as expected");
+				}
+			}
+			for &me in meds.iter() {
+				assert_eq!(margins[me].ratio, 0., "ratio of medoid not as expected");
+			}
+		}
+	}
+
+	#[test]
+	fn test_nearest_medoids() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let mut meds = vec![0, 1, 2];
+		let (_, assi, _, _): (i64, _, _, _) = fasterpam(&data, &mut meds, 0, 10);
+		let near = nearest_medoids(&data, &meds, 1);
+		assert_array(near.iter().map(|v| v[0].0).collect(), assi, "assignment not as expected");
+		let near = nearest_medoids(&data, &meds, 5);
+		for (i, v) in near.iter().enumerate() {
+			assert_eq!(v.len(), 3, "number of medoids not as expected");
+			assert!(v.windows(2).all(|w| w[0].1 <= w[1].1), "not sorted by distance");
+			assert!(v.iter().all(|&(c, d)| d == data.get(i, meds[c])), "distance not as expected");
+		}
+	}
+
+	#[test]
+	fn test_k_nearest_medoids() {
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let mut rng = StdRng::seed_from_u64(2);
+		// small integer distances, to have many ties
+		let data = LowerTriangle { n: 30, data: (0..435).map(|_| rng.gen_range(1..4)).collect::<Vec<u32>>() };
+		let meds = vec![7, 2, 19, 11, 25, 0];
+		let (_, assi) = crate::assign_to_medoids::<_, _, i64>(&data, &meds);
+		for i in 0..data.n {
+			let near = k_nearest_medoids(&data, i, &meds, 1);
+			assert_eq!(near, vec![(assi[i], data.get(i, meds[assi[i]]))], "nearest medoid not as expected");
+			let all = k_nearest_medoids(&data, i, &meds, meds.len());
+			assert_eq!(all.len(), meds.len(), "not all medoids returned");
+			assert!(all.windows(2).all(|w| w[0].1 <= w[1].1 || meds[w[0].0] == i), "medoids not in order");
+			assert_eq!(all, nearest_medoids(&data, &meds, 10)[i], "order not as nearest_medoids");
+			assert_eq!(k_nearest_medoids(&data, i, &meds, 3), all[..3].to_vec(), "partial result not as expected");
+		}
+		assert_eq!(k_nearest_medoids(&data, 3, &meds, 0), vec![], "empty result not as expected");
+	}
+
+	#[test]
+	fn test_nearest_medoids_ties() {
+		// object 1 has distance 0 to medoid 0, and object 2 has the same distance to all
+		let data = LowerTriangle {
+			n: 4,
+			data: vec![0, 1, 1, 2, 2, 2],
+		};
+		let meds = vec![0, 1, 3];
+		let near = nearest_medoids(&data, &meds, 3);
+		assert_eq!(near[1], vec![(1, 0), (0, 0), (2, 2)], "medoid not first");
+		assert_eq!(near[2], vec![(0, 1), (1, 1), (2, 2)], "ties not broken by medoid order");
+		// same convention as the nearest, second and third nearest medoid cache
+		let (_, reco): (f64, Vec<crate::util::Reco<u32>>) = crate::fastermsc::initial_assignment(&data, &meds);
+		for (i, v) in near.iter().enumerate() {
+			let expect = [reco[i].near, reco[i].seco, reco[i].third].map(|p| (p.i as usize, p.d));
+			assert_eq!(v[..], expect[..], "not consistent with Reco");
+		}
+	}
+
+	#[test]
+	#[cfg(feature = "rand")]
+	fn test_medoid_stability() {
+		use crate::medoid_stability;
+		use rand::{rngs::StdRng, SeedableRng};
+		// Three stars: a center with four satellites each, Manhattan distance
+		let offsets = [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)];
+		let points = (0..15).map(|i| (i as i32 / 5 * 100 + offsets[i % 5].0, offsets[i % 5].1)).collect::<Vec<(i32, i32)>>();
+		let mut data = Vec::with_capacity(15 * 14 / 2);
+		for i in 1..15 {
+			for j in 0..i {
+				data.push(((points[i].0 - points[j].0).abs() + (points[i].1 - points[j].1).abs()) as u32);
+			}
+		}
+		let data = LowerTriangle { n: 15, data };
+		let stability = medoid_stability::<_, _, i64, _>(&data, 3, 20, 100, &mut StdRng::seed_from_u64(1));
+		assert_eq!(stability.len(), 15, "length not as expected");
+		for (i, &s) in stability.iter().enumerate() {
+			if i % 5 == 0 {
+				assert_eq!(s, 1., "center not stable");
+			} else {
+				assert_eq!(s, 0., "satellite not as expected");
+			}
+		}
+	}
+
+	#[test]
+	#[cfg(feature = "rand")]
+	fn test_convergence_rate_analysis() {
+		use crate::{convergence_rate_analysis, fasterpam, fastermsc, Objective};
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let mut rng = StdRng::seed_from_u64(1);
+		let data = LowerTriangle {
+			n: 30,
+			data: (0..30 * 29 / 2).map(|_| rng.gen_range(1..100)).collect::<Vec<u32>>(),
+		};
+		let run_fasterpam = |mat: &LowerTriangle<u32>, meds: &mut Vec<usize>, maxiter| {
+			let (loss, _, n_iter, _): (i64, _, _, _) = fasterpam(mat, meds, 0, maxiter);
+			(loss, n_iter)
+		};
+		let stats = convergence_rate_analysis(&data, 3, 20, 10, Objective::Minimize, &mut rng, run_fasterpam);
+		assert!(stats.mean_iterations_to_convergence.is_finite(), "mean not finite");
+		assert!((1. ..=10.).contains(&stats.mean_iterations_to_convergence), "mean not within bounds");
+		assert!(stats.std_iterations.is_finite() && (0. ..=10.).contains(&stats.std_iterations), "std not within bounds");
+		assert!(stats.fraction_reaching_optimal > 0. && stats.fraction_reaching_optimal <= 1., "fraction not within bounds");
+		assert!(stats.mean_suboptimality_gap.is_finite() && stats.mean_suboptimality_gap >= 0., "gap not within bounds");
+
+		// the chosen algorithm is run, here maximizing the medoid silhouette
+		let (mut losses, mut iters) = (vec![], vec![]);
+		let stats = convergence_rate_analysis(&data, 3, 20, 10, Objective::Maximize, &mut StdRng::seed_from_u64(2), |mat, meds, maxiter| {
+			let (loss, _, n_iter, _): (f64, _, _, _) = fastermsc(mat, meds, 0, maxiter);
+			losses.push(loss);
+			iters.push(n_iter);
+			(loss, n_iter)
+		});
+		let best = losses.iter().cloned().fold(f64::MIN, f64::max);
+		assert_eq!(losses.len(), 20, "runs not as expected");
+		assert_eq!(stats.mean_iterations_to_convergence, iters.iter().sum::<usize>() as f64 / 20., "mean not as expected");
+		assert_eq!(stats.fraction_reaching_optimal, losses.iter().filter(|&&l| l == best).count() as f64 / 20., "fraction not as expected");
+		let gap = losses.iter().map(|l| (best - l) / best).sum::<f64>() / 20.;
+		assert!((stats.mean_suboptimality_gap - gap).abs() < 1e-12, "gap not as expected");
+
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let stats = convergence_rate_analysis(&data, 1, 5, 0, Objective::Minimize, &mut rng, run_fasterpam);
+		assert_eq!(stats.mean_iterations_to_convergence, 0., "mean not as expected");
+		assert_eq!(stats.std_iterations, 0., "std not as expected");
+	}
+
+	#[test]
+	#[cfg(feature = "ndarray")]
+	fn test_onehot() {
+		use crate::{assignments_to_onehot, membership_matrix, onehot_to_assignments};
+		let assi = vec![2, 0, 1, 1, 2, 0, 0];
+		let onehot = assignments_to_onehot(&assi, 3);
+		assert_eq!(onehot.shape(), &[7, 3], "shape not as expected");
+		for r in onehot.rows() {
+			assert_eq!(r.sum(), 1, "row sum not as expected");
+		}
+		assert_array(onehot_to_assignments(onehot.view()), assi, "round trip not as expected");
+		let noise = membership_matrix(&[0, usize::MAX, 1], 2);
+		assert_array(onehot_to_assignments(noise.view()), vec![0, usize::MAX, 1], "noise not as expected");
+	}
+
+	#[test]
+	#[should_panic]
+	#[cfg(feature = "ndarray")]
+	fn test_onehot_noise_label() {
+		use crate::assignments_to_onehot;
+		assignments_to_onehot(&[0, usize::MAX], 2);
+	}
+
+	#[test]
+	#[cfg(feature = "rand")]
+	fn test_assign_topk_medoids() {
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let mut rng = StdRng::seed_from_u64(1);
+		let n = 50;
+		// few distinct values, to have many ties
+		let data = LowerTriangle { n, data: (0..n * (n - 1) / 2).map(|_| rng.gen_range(1..5u32)).collect() };
+		let meds = crate::random_initialization(n, 10, &mut rng);
+		let (_, assi): (i64, _) = crate::assign_to_medoids(&data, &meds);
+		let top1 = assign_topk_medoids(&data, &meds, 1);
+		assert_array(top1.iter().map(|t| t[0]).collect(), assi, "assignment not as expected");
+		for topk in [0, 3, 10, 20] {
+			let expect = nearest_medoids(&data, &meds, topk).iter().map(|v| v.iter().map(|x| x.0).collect()).collect::<Vec<Vec<_>>>();
+			assert_eq!(assign_topk_medoids(&data, &meds, topk), expect, "nearest medoids not as expected");
+		}
+	}
+}
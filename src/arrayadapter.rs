@@ -1,6 +1,9 @@
 //! Adapter trait for accessing different types of arrays.
 //!
-//! Includes adapters for `ndarray::Array2` and a serialized lower triangular matrix in a `Vec`.
+//! Includes adapters for `ndarray::Array2`, serialized full and lower triangular matrices in
+//! a `Vec` (also with aligned storage) or a fixed-size array, nested vectors, a memoizing
+//! adapter for distance functions, as well as a type-erased wrapper for runtime polymorphism,
+//! and a wrapper with prefetching.
 
 /// Adapter trait for accessing different types of arrays
 #[allow(clippy::len_without_is_empty)]
@@ -11,6 +14,181 @@ pub trait ArrayAdapter<N> {
 	fn is_square(&self) -> bool;
 	/// Get the contents at cell x,y
 	fn get(&self, x: usize, y: usize) -> N;
+	/// Hint that the contents at cell x,y will be needed soon
+	///
+	/// This does nothing by default; adapters with in-memory storage issue a prefetch
+	/// instruction where supported. Cells outside of the matrix are ignored.
+	#[inline]
+	fn prefetch(&self, _x: usize, _y: usize) {}
+	/// Get the contents of many cells at once, replacing the contents of `out`
+	///
+	/// By default, this calls [`ArrayAdapter::get`] for every cell. Adapters can override
+	/// this with a faster loop for many scattered cells, e.g., [`LowerTriangle`] computes
+	/// the storage positions without the unpredictable branches of `get`.
+	fn get_batch(&self, queries: &[(usize, usize)], out: &mut Vec<N>) {
+		out.clear();
+		out.extend(queries.iter().map(|&(x, y)| self.get(x, y)));
+	}
+	/// Iterate over the rows of the matrix
+	fn rows(&self) -> RowIter<'_, Self, N>
+	where
+		Self: Sized,
+	{
+		RowIter { mat: self, row: 0, _n: core::marker::PhantomData }
+	}
+	/// Iterate over the cells below the diagonal, as `(i, j, distance)` with `i > j`
+	///
+	/// The cells are in row-major order, which is the storage order of [`LowerTriangle`].
+	fn iter_triangle(&self) -> TriangleIter<'_, Self, N>
+	where
+		Self: Sized,
+	{
+		TriangleIter { mat: self, i: 1, j: 0, _n: core::marker::PhantomData }
+	}
+}
+
+/// Iterator over the rows of a matrix, see [`ArrayAdapter::rows`]
+///
+/// ## Example
+/// ```
+/// use kmedoids::arrayadapter::{ArrayAdapter, LowerTriangle};
+/// let data = LowerTriangle { n: 3, data: vec![1, 2, 3] };
+/// let sums = data.rows().map(|row| row.iter().sum()).collect::<Vec<u32>>();
+/// assert_eq!(sums, vec![3, 4, 5]);
+/// ```
+pub struct RowIter<'a, M, N> {
+	mat: &'a M,
+	row: usize,
+	_n: core::marker::PhantomData<N>,
+}
+impl<'a, M: ArrayAdapter<N>, N: 'a> Iterator for RowIter<'a, M, N> {
+	type Item = RowView<'a, N>;
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.row >= self.mat.len() {
+			return None;
+		}
+		self.row += 1;
+		let values = (0..self.mat.len()).map(|y| self.mat.get(self.row - 1, y)).collect();
+		Some(RowView { values, row: self.row - 1, _mat: core::marker::PhantomData })
+	}
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let rest = self.mat.len().saturating_sub(self.row);
+		(rest, Some(rest))
+	}
+}
+impl<'a, M: ArrayAdapter<N>, N: 'a> ExactSizeIterator for RowIter<'a, M, N> {}
+
+/// Iterator over the cells below the diagonal, see [`ArrayAdapter::iter_triangle`]
+///
+/// ## Example
+/// ```
+/// use kmedoids::arrayadapter::{ArrayAdapter, LowerTriangle};
+/// let data = ndarray::arr2(&[[0, 1, 2], [1, 0, 3], [2, 3, 0]]);
+/// let cells = data.iter_triangle().collect::<Vec<_>>();
+/// assert_eq!(cells, vec![(1, 0, 1), (2, 0, 2), (2, 1, 3)]);
+/// let data = LowerTriangle { n: 3, data: data.iter_triangle().map(|c| c.2).collect() };
+/// assert_eq!(data.data, vec![1, 2, 3]);
+/// ```
+pub struct TriangleIter<'a, M, N> {
+	mat: &'a M,
+	i: usize,
+	j: usize,
+	_n: core::marker::PhantomData<N>,
+}
+impl<M: ArrayAdapter<N>, N> Iterator for TriangleIter<'_, M, N> {
+	type Item = (usize, usize, N);
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.i >= self.mat.len() {
+			return None;
+		}
+		let (i, j) = (self.i, self.j);
+		self.j += 1;
+		if self.j == self.i {
+			self.i += 1;
+			self.j = 0;
+		}
+		Some((i, j, self.mat.get(i, j)))
+	}
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let n = self.mat.len();
+		let rest = if self.i >= n { 0 } else { triangle_size(n) - triangle_size(self.i) - self.j };
+		(rest, Some(rest))
+	}
+}
+impl<M: ArrayAdapter<N>, N> ExactSizeIterator for TriangleIter<'_, M, N> {}
+
+/// A single row of a matrix, with the values read with [`ArrayAdapter::get`]
+///
+/// As the adapters compute or convert the values on access, the values of the row are
+/// read once when the iterator reaches the row, so that the row can be indexed.
+///
+/// ## Example
+/// ```
+/// use kmedoids::arrayadapter::{ArrayAdapter, LowerTriangle};
+/// let data = LowerTriangle { n: 3, data: vec![1, 2, 3] };
+/// let row = data.rows().nth(2).unwrap();
+/// assert_eq!((row[0], row[1], row[2]), (2, 3, 0));
+/// ```
+pub struct RowView<'a, N> {
+	values: Vec<N>,
+	row: usize,
+	_mat: core::marker::PhantomData<&'a ()>,
+}
+impl<N: Copy> RowView<'_, N> {
+	/// Get the row number
+	pub fn row(&self) -> usize {
+		self.row
+	}
+	/// Get the length of the row
+	#[allow(clippy::len_without_is_empty)]
+	pub fn len(&self) -> usize {
+		self.values.len()
+	}
+	/// Get the value in column y
+	#[inline]
+	pub fn get(&self, y: usize) -> N {
+		self[y]
+	}
+	/// Iterate over the values of the row
+	pub fn iter(&self) -> impl Iterator<Item = N> + '_ {
+		self.values.iter().copied()
+	}
+}
+impl<N> core::ops::Index<usize> for RowView<'_, N> {
+	type Output = N;
+	/// Get the value in column y
+	///
+	/// ## Panics
+	///
+	/// * panics when y is not less than the length of the row
+	#[inline]
+	fn index(&self, y: usize) -> &N {
+		assert!(y < self.values.len(), "column {} out of range for a row of length {}", y, self.values.len());
+		&self.values[y]
+	}
+}
+
+/// Get the contents at cell i,j, checking that the matrix is symmetric there
+///
+/// In debug builds, this panics when `mat.get(i, j)` and `mat.get(j, i)` differ, which
+/// catches asymmetric matrices given to algorithms that assume symmetric distances, and
+/// would otherwise only give wrong results. In release builds, this is the same as
+/// [`ArrayAdapter::get`].
+///
+/// ## Panics
+///
+/// * panics in debug builds when the matrix is not symmetric at i,j
+///
+/// ## Example
+/// ```
+/// let data = ndarray::arr2(&[[0, 1], [1, 0]]);
+/// assert_eq!(kmedoids::arrayadapter::get_symmetric(&data, 0, 1), 1);
+/// ```
+#[inline]
+pub fn get_symmetric<M: ArrayAdapter<N>, N: Copy + PartialOrd + std::fmt::Debug>(mat: &M, i: usize, j: usize) -> N {
+	let d = mat.get(i, j);
+	debug_assert_eq!(d, mat.get(j, i), "dissimilarity matrix is not symmetric at {},{}", i, j);
+	d
 }
 
 /// Adapter trait for using `ndarray::Array2` and similar
@@ -32,18 +210,41 @@ where
 	fn get(&self, x: usize, y: usize) -> N {
 		self[[x, y]]
 	}
+	#[inline]
+	fn prefetch(&self, x: usize, y: usize) {
+		if let Some(v) = ndarray::ArrayBase::get(self, [x, y]) {
+			prefetch_read(v);
+		}
+	}
+}
+
+/// Prefetch hint for reading the value, where supported
+#[inline(always)]
+fn prefetch_read<N>(v: &N) {
+	#[cfg(target_arch = "x86_64")]
+	// SAFETY: prefetching has no effect on the program semantics, and SSE is always available on x86_64
+	unsafe {
+		core::arch::x86_64::_mm_prefetch::<{ core::arch::x86_64::_MM_HINT_T0 }>(v as *const N as *const i8);
+	}
+	#[cfg(not(target_arch = "x86_64"))]
+	let _ = v;
 }
 
 /// Lower triangular matrix in serial form (without diagonal)
 ///
+/// The matrix is `Send` and `Sync` (if `N` is), so it can be shared across threads,
+/// e.g., with the parallel algorithms.
+///
 /// ## Example
 /// ```
 /// let data = kmedoids::arrayadapter::LowerTriangle { n: 4, data: vec![1, 2, 3, 4, 5, 6] };
 /// let mut meds = vec![0, 1];
-/// let (loss, numswap, numiter, assignment): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 10);
+/// let (loss, numswap, numiter, assignment): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 10);
 /// println!("Loss is {}", loss);
 /// ```
-#[derive(Debug, Clone)]
+///
+/// Matrices can be compared with `==`, and the default is the empty matrix.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub struct LowerTriangle<N> {
 	/// Matrix size
 	pub n: usize,
@@ -58,7 +259,299 @@ impl<N: Copy + num_traits::Zero> ArrayAdapter<N> for LowerTriangle<N> {
 	}
 	#[inline]
 	fn is_square(&self) -> bool {
-		self.data.len() == (self.n * (self.n - 1)) >> 1
+		self.data.len() == (self.n * self.n.saturating_sub(1)) >> 1
+	}
+	#[inline]
+	fn get(&self, x: usize, y: usize) -> N {
+		match x.cmp(&y) {
+			std::cmp::Ordering::Less => self.data[((y * (y - 1)) >> 1) + x],
+			std::cmp::Ordering::Greater => self.data[((x * (x - 1)) >> 1) + y],
+			std::cmp::Ordering::Equal => N::zero(),
+		}
+	}
+	#[inline]
+	fn prefetch(&self, x: usize, y: usize) {
+		let (x, y) = if x < y { (y, x) } else { (x, y) };
+		if x != y {
+			if let Some(v) = self.data.get(((x * (x - 1)) >> 1) + y) {
+				prefetch_read(v);
+			}
+		}
+	}
+	fn get_batch(&self, queries: &[(usize, usize)], out: &mut Vec<N>) {
+		out.clear();
+		out.extend(queries.iter().map(|&(x, y)| {
+			let (x, y) = if x < y { (y, x) } else { (x, y) };
+			if x == y { N::zero() } else { self.data[((x * (x - 1)) >> 1) + y] }
+		}));
+	}
+}
+
+impl<N: Copy> LowerTriangle<N> {
+	/// Build the matrix from a list of `(i, j, distance)` triples, e.g., from a database query
+	///
+	/// Every pair of distinct points must be given exactly once, in any order. The indexes
+	/// are usually given with `i > j`, but `(j, i, distance)` is accepted for the same pair.
+	///
+	/// ## Panics
+	///
+	/// * panics when a pair has `i == j`, or an index not less than `n`
+	/// * panics when a pair is given more than once
+	/// * panics when not all n x (n-1) / 2 pairs are given
+	///
+	/// ## Example
+	/// ```
+	/// let pairs = [(1, 0, 1), (2, 0, 2), (2, 1, 4)];
+	/// let data = kmedoids::arrayadapter::LowerTriangle::from_labeled_pairs(3, &pairs);
+	/// assert_eq!(data.data, vec![1, 2, 4]);
+	/// ```
+	pub fn from_labeled_pairs(n: usize, pairs: &[(usize, usize, N)]) -> Self {
+		let size = triangle_size(n);
+		assert_eq!(pairs.len(), size, "exactly n x (n-1) / 2 pairs are required");
+		let mut data = vec![None; size];
+		for &(i, j, d) in pairs.iter() {
+			assert!(i != j, "pairs must have i != j");
+			let (i, j) = if i < j { (j, i) } else { (i, j) };
+			assert!(i < n, "invalid index, must be less than n");
+			let cell = &mut data[((i * (i - 1)) >> 1) + j];
+			assert!(cell.is_none(), "pair ({}, {}) given more than once", i, j);
+			*cell = Some(d);
+		}
+		// with n x (n-1) / 2 distinct pairs, all cells are set
+		LowerTriangle { n, data: data.into_iter().map(Option::unwrap).collect() }
+	}
+}
+
+impl<N: Copy + num_traits::ToPrimitive> LowerTriangle<N> {
+	/// Scale all distances to [0, 1], by dividing them by the largest distance
+	///
+	/// This changes the loss values, but not the clustering found. If all distances are
+	/// zero, they remain zero.
+	///
+	/// ## Panics
+	///
+	/// * panics when a distance cannot be converted to `f64`
+	///
+	/// ## Example
+	/// ```
+	/// let data = kmedoids::arrayadapter::LowerTriangle { n: 3, data: vec![1, 2, 4] };
+	/// assert_eq!(data.normalize().data, vec![0.25, 0.5, 1.]);
+	/// ```
+	pub fn normalize(&self) -> LowerTriangle<f64> {
+		let data = self.to_f64();
+		let max = data.iter().fold(0., |m: f64, &d| m.max(d));
+		let scale = if max > 0. { 1. / max } else { 0. };
+		LowerTriangle { n: self.n, data: data.into_iter().map(|d| d * scale).collect() }
+	}
+
+	/// Standardize all distances, subtracting their mean and dividing by their standard deviation
+	///
+	/// Distances below the mean would become negative, so these are set to zero. Unlike
+	/// [`LowerTriangle::normalize`], this can change the clustering found, as all points
+	/// closer than the mean are then at distance zero. If all distances are equal, they
+	/// become zero.
+	///
+	/// ## Panics
+	///
+	/// * panics when a distance cannot be converted to `f64`
+	///
+	/// ## Example
+	/// ```
+	/// let data = kmedoids::arrayadapter::LowerTriangle { n: 3, data: vec![1, 2, 3] };
+	/// let z = data.standardize().data;
+	/// assert_eq!(z[0], 0.);
+	/// assert_eq!(z[1], 0.);
+	/// assert!((z[2] - 1.5f64.sqrt()).abs() < 1e-12);
+	/// ```
+	pub fn standardize(&self) -> LowerTriangle<f64> {
+		let data = self.to_f64();
+		let len = data.len().max(1) as f64;
+		let mean = data.iter().sum::<f64>() / len;
+		let std = (data.iter().map(|d| (d - mean) * (d - mean)).sum::<f64>() / len).sqrt();
+		let scale = if std > 0. { 1. / std } else { 0. };
+		LowerTriangle { n: self.n, data: data.into_iter().map(|d| ((d - mean) * scale).max(0.)).collect() }
+	}
+
+	/// The distances converted to `f64`
+	fn to_f64(&self) -> Vec<f64> {
+		self.data.iter().map(|d| d.to_f64().expect("distance not representable as f64")).collect()
+	}
+}
+
+/// Lower triangular matrix in serial form (without diagonal), with aligned storage
+///
+/// The data is allocated with an alignment of `ALIGN` bytes (a power of two, such as
+/// 32 or 64), e.g., for processing with SIMD instructions, or to align with cache lines.
+///
+/// ## Example
+/// ```
+/// use kmedoids::arrayadapter::{AlignedLowerTriangle, LowerTriangle};
+/// let data = LowerTriangle { n: 4, data: vec![1, 2, 3, 4, 5, 6] };
+/// let data = AlignedLowerTriangle::<_, 64>::from_lower_triangle(&data);
+/// assert_eq!(data.data().as_ptr() as usize % 64, 0);
+/// let mut meds = vec![0, 1];
+/// let (loss, numswap, numiter, assignment): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 10);
+/// println!("Loss is {}", loss);
+/// ```
+pub struct AlignedLowerTriangle<N: Copy, const ALIGN: usize> {
+	n: usize,
+	ptr: std::ptr::NonNull<N>,
+	size: usize,
+}
+impl<N: Copy, const ALIGN: usize> AlignedLowerTriangle<N, ALIGN> {
+	/// Copy the values of a lower triangular matrix into aligned storage
+	///
+	/// ## Panics
+	///
+	/// * panics when `ALIGN` is not a power of two
+	pub fn from_lower_triangle(mat: &LowerTriangle<N>) -> Self {
+		Self::from_slice(mat.n, &mat.data)
+	}
+	/// Copy n x (n-1) / 2 values into aligned storage
+	fn from_slice(n: usize, data: &[N]) -> Self {
+		let len = data.len();
+		let layout = Self::layout(len);
+		let ptr = if layout.size() == 0 {
+			// no allocation needed, but use a pointer with the requested alignment
+			std::ptr::NonNull::new(layout.align() as *mut N).unwrap()
+		} else {
+			// SAFETY: the layout has a non-zero size
+			let raw = unsafe { std::alloc::alloc(layout) } as *mut N;
+			let ptr = std::ptr::NonNull::new(raw).unwrap_or_else(|| std::alloc::handle_alloc_error(layout));
+			// SAFETY: the new allocation is valid for len values, and cannot overlap with data
+			unsafe { std::ptr::copy_nonoverlapping(data.as_ptr(), ptr.as_ptr(), len) };
+			ptr
+		};
+		AlignedLowerTriangle { n, ptr, size: len }
+	}
+	/// Memory layout of len values
+	fn layout(len: usize) -> std::alloc::Layout {
+		assert!(ALIGN.is_power_of_two(), "alignment must be a power of two");
+		let size = std::mem::size_of::<N>().checked_mul(len).expect("matrix too large");
+		std::alloc::Layout::from_size_align(size, ALIGN.max(std::mem::align_of::<N>())).expect("matrix too large")
+	}
+	/// Matrix data, lower triangular form without diagonal
+	pub fn data(&self) -> &[N] {
+		// SAFETY: ptr is valid for size initialized values (or dangling, but aligned, for size = 0)
+		unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.size) }
+	}
+}
+impl<N: Copy, const ALIGN: usize> Drop for AlignedLowerTriangle<N, ALIGN> {
+	fn drop(&mut self) {
+		let layout = Self::layout(self.size);
+		if layout.size() > 0 {
+			// SAFETY: the pointer was allocated with the same layout, and N: Copy needs no drop
+			unsafe { std::alloc::dealloc(self.ptr.as_ptr() as *mut u8, layout) };
+		}
+	}
+}
+impl<N: Copy, const ALIGN: usize> Clone for AlignedLowerTriangle<N, ALIGN> {
+	fn clone(&self) -> Self {
+		Self::from_slice(self.n, self.data())
+	}
+}
+impl<N: Copy + std::fmt::Debug, const ALIGN: usize> std::fmt::Debug for AlignedLowerTriangle<N, ALIGN> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("AlignedLowerTriangle").field("n", &self.n).field("data", &self.data()).finish()
+	}
+}
+// SAFETY: the storage is owned, as in a Vec<N>
+unsafe impl<N: Copy + Send, const ALIGN: usize> Send for AlignedLowerTriangle<N, ALIGN> {}
+// SAFETY: the storage is only modified by drop, as in a Vec<N>
+unsafe impl<N: Copy + Sync, const ALIGN: usize> Sync for AlignedLowerTriangle<N, ALIGN> {}
+/// Adapter implementation for AlignedLowerTriangle
+impl<N: Copy + num_traits::Zero, const ALIGN: usize> ArrayAdapter<N> for AlignedLowerTriangle<N, ALIGN> {
+	#[inline]
+	fn len(&self) -> usize {
+		self.n
+	}
+	#[inline]
+	fn is_square(&self) -> bool {
+		self.size == (self.n * self.n.saturating_sub(1)) >> 1
+	}
+	#[inline]
+	fn get(&self, x: usize, y: usize) -> N {
+		let data = self.data();
+		match x.cmp(&y) {
+			std::cmp::Ordering::Less => data[((y * (y - 1)) >> 1) + x],
+			std::cmp::Ordering::Greater => data[((x * (x - 1)) >> 1) + y],
+			std::cmp::Ordering::Equal => N::zero(),
+		}
+	}
+	#[inline]
+	fn prefetch(&self, x: usize, y: usize) {
+		let (x, y) = if x < y { (y, x) } else { (x, y) };
+		if x != y {
+			if let Some(v) = self.data().get(((x * (x - 1)) >> 1) + y) {
+				prefetch_read(v);
+			}
+		}
+	}
+}
+
+/// Storage size n x (n-1) / 2 of a lower triangular matrix of n points, see [`StaticLowerTriangle`]
+pub const fn triangle_size(n: usize) -> usize {
+	(n * n.saturating_sub(1)) >> 1
+}
+
+/// Number of points of a lower triangular matrix with storage size k, if k is a triangular number
+const fn triangle_points(k: usize) -> Option<usize> {
+	let mut n = 1;
+	while triangle_size(n) < k {
+		n += 1;
+	}
+	if triangle_size(n) == k {
+		Some(n)
+	} else {
+		None
+	}
+}
+
+/// Lower triangular matrix (without diagonal) in a fixed-size array
+///
+/// The storage size `K` = n x (n-1) / 2 is a const parameter, and the number of points is
+/// computed from it at compile time. This is meant for small matrices embedded in code,
+/// where the compiler checks that the data has `K` values, and that `K` is a valid size:
+/// ```compile_fail
+/// // 9 is not n x (n-1) / 2 for any n
+/// let data = kmedoids::arrayadapter::StaticLowerTriangle::new([1, 2, 3, 4, 5, 6, 7, 8, 9]);
+/// ```
+///
+/// ## Example
+/// ```
+/// use kmedoids::arrayadapter::{ArrayAdapter, StaticLowerTriangle};
+/// let data = StaticLowerTriangle::new([1, 2, 3, 4, 5, 6]);
+/// assert_eq!(data.len(), 4);
+/// let mut meds = vec![0, 1];
+/// let (loss, numswap, numiter, assignment): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 10);
+/// println!("Loss is {}", loss);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StaticLowerTriangle<N, const K: usize> {
+	/// Matrix data, lower triangular form without diagonal
+	pub data: [N; K],
+}
+impl<N, const K: usize> StaticLowerTriangle<N, K> {
+	/// Number of points, fails to compile if `K` is not n x (n-1) / 2
+	pub const N_POINTS: usize = match triangle_points(K) {
+		Some(n) => n,
+		None => panic!("storage size must be n x (n-1) / 2"),
+	};
+	/// Wrap the values of a lower triangular matrix
+	pub const fn new(data: [N; K]) -> Self {
+		let _ = Self::N_POINTS; // check the size at compile time
+		StaticLowerTriangle { data }
+	}
+}
+/// Adapter implementation for StaticLowerTriangle
+impl<N: Copy + num_traits::Zero, const K: usize> ArrayAdapter<N> for StaticLowerTriangle<N, K> {
+	#[inline]
+	fn len(&self) -> usize {
+		Self::N_POINTS
+	}
+	#[inline]
+	fn is_square(&self) -> bool {
+		triangle_size(Self::N_POINTS) == K
 	}
 	#[inline]
 	fn get(&self, x: usize, y: usize) -> N {
@@ -68,4 +561,700 @@ impl<N: Copy + num_traits::Zero> ArrayAdapter<N> for LowerTriangle<N> {
 			std::cmp::Ordering::Equal => N::zero(),
 		}
 	}
+	#[inline]
+	fn prefetch(&self, x: usize, y: usize) {
+		let (x, y) = if x < y { (y, x) } else { (x, y) };
+		if x != y {
+			if let Some(v) = self.data.get(((x * (x - 1)) >> 1) + y) {
+				prefetch_read(v);
+			}
+		}
+	}
+}
+
+/// Dense square matrix in serial form (row major)
+///
+/// ## Example
+/// ```
+/// let data = kmedoids::arrayadapter::SquareMatrix::new(4, vec![0, 1, 2, 3, 1, 0, 4, 5, 2, 4, 0, 6, 3, 5, 6, 0]);
+/// let mut meds = vec![0, 1];
+/// let (loss, numswap, numiter, assignment): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 10);
+/// println!("Loss is {}", loss);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SquareMatrix<N> {
+	/// Matrix size
+	pub n: usize,
+	/// Matrix data, row by row
+	pub data: Vec<N>,
+}
+impl<N> SquareMatrix<N> {
+	/// Wrap a vector of n x n values, row by row
+	///
+	/// ## Panics
+	///
+	/// * panics when the data does not have length n x n
+	pub fn new(n: usize, data: Vec<N>) -> Self {
+		assert_eq!(data.len(), n * n, "data must have length n x n");
+		SquareMatrix { n, data }
+	}
+}
+/// Adapter implementation for SquareMatrix
+impl<N: Copy> ArrayAdapter<N> for SquareMatrix<N> {
+	#[inline]
+	fn len(&self) -> usize {
+		self.n
+	}
+	#[inline]
+	fn is_square(&self) -> bool {
+		self.data.len() == self.n * self.n
+	}
+	#[inline]
+	fn get(&self, x: usize, y: usize) -> N {
+		self.data[x * self.n + y]
+	}
+	#[inline]
+	fn prefetch(&self, x: usize, y: usize) {
+		if y < self.n {
+			if let Some(v) = self.data.get(x * self.n + y) {
+				prefetch_read(v);
+			}
+		}
+	}
+}
+
+/// Square matrix stored as nested vectors, one vector per row
+///
+/// ## Example
+/// ```
+/// let data = kmedoids::arrayadapter::JaggedMatrix::new(vec![
+///     vec![0, 1, 2, 3], vec![1, 0, 4, 5], vec![2, 4, 0, 6], vec![3, 5, 6, 0]
+/// ]);
+/// let mut meds = vec![0, 1];
+/// let (loss, numswap, numiter, assignment): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 10);
+/// println!("Loss is {}", loss);
+/// ```
+#[derive(Debug, Clone)]
+pub struct JaggedMatrix<N> {
+	/// Matrix data, one vector per row
+	pub data: Vec<Vec<N>>,
+}
+impl<N> JaggedMatrix<N> {
+	/// Wrap a nested vector
+	///
+	/// ## Panics
+	///
+	/// * panics when not all rows have length n, the number of rows
+	pub fn new(data: Vec<Vec<N>>) -> Self {
+		let n = data.len();
+		assert!(data.iter().all(|row| row.len() == n), "all rows must have length n");
+		JaggedMatrix { data }
+	}
+}
+/// Adapter implementation for JaggedMatrix
+impl<N: Copy> ArrayAdapter<N> for JaggedMatrix<N> {
+	#[inline]
+	fn len(&self) -> usize {
+		self.data.len()
+	}
+	#[inline]
+	fn is_square(&self) -> bool {
+		let n = self.data.len();
+		self.data.iter().all(|row| row.len() == n)
+	}
+	#[inline]
+	fn get(&self, x: usize, y: usize) -> N {
+		self.data[x][y]
+	}
+}
+
+/// Type-erased array adapter, for runtime polymorphism
+///
+/// This allows storing different types of distance matrices, e.g., in a
+/// `Vec<DynArrayAdapter<N>>` for batch processing. As the wrapped matrix must be
+/// `Send` and `Sync`, this can also be used with the parallel algorithms.
+///
+/// ## Example
+/// ```
+/// use kmedoids::arrayadapter::{DynArrayAdapter, LowerTriangle};
+/// let mats: Vec<DynArrayAdapter<i32>> = vec![
+///     DynArrayAdapter::new(ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]])),
+///     DynArrayAdapter::new(LowerTriangle { n: 4, data: vec![1, 2, 3, 4, 5, 6] }),
+/// ];
+/// for data in mats.iter() {
+///     let mut meds = vec![0, 1];
+///     let (loss, assi, n_iter, n_swap): (i64, _, _, _) = kmedoids::fasterpam(data, &mut meds, 0, 10);
+///     println!("Loss is {}", loss);
+/// }
+/// ```
+pub struct DynArrayAdapter<N> {
+	inner: Box<dyn ArrayAdapter<N> + Send + Sync>,
+}
+impl<N> DynArrayAdapter<N> {
+	/// Wrap an array adapter
+	pub fn new(mat: impl ArrayAdapter<N> + Send + Sync + 'static) -> Self {
+		DynArrayAdapter { inner: Box::new(mat) }
+	}
+}
+impl<N> From<Box<dyn ArrayAdapter<N> + Send + Sync>> for DynArrayAdapter<N> {
+	fn from(inner: Box<dyn ArrayAdapter<N> + Send + Sync>) -> Self {
+		DynArrayAdapter { inner }
+	}
+}
+/// Adapter implementation for DynArrayAdapter, delegating to the wrapped array
+impl<N> ArrayAdapter<N> for DynArrayAdapter<N> {
+	#[inline]
+	fn len(&self) -> usize {
+		self.inner.len()
+	}
+	#[inline]
+	fn is_square(&self) -> bool {
+		self.inner.is_square()
+	}
+	#[inline]
+	fn get(&self, x: usize, y: usize) -> N {
+		self.inner.get(x, y)
+	}
+	#[inline]
+	fn prefetch(&self, x: usize, y: usize) {
+		self.inner.prefetch(x, y)
+	}
+}
+
+/// Distance matrix computed on demand by a closure, without storing any distances
+///
+/// Every access calls the distance function, so this is best for cheap distance
+/// functions, or when each distance is needed only few times and O(N²) memory is
+/// not available. See [`CachingClosureMatrix`] for a memoizing version.
+///
+/// ## Example
+/// ```
+/// let points = [0f64, 1., 2., 10.];
+/// let data = kmedoids::arrayadapter::ClosureMatrix::new(points.len(), |i, j| (points[i] - points[j]).abs());
+/// let mut meds = vec![0, 1];
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 10);
+/// println!("Loss is {}", loss);
+/// ```
+pub struct ClosureMatrix<F> {
+	n: usize,
+	f: F,
+}
+impl<F> ClosureMatrix<F> {
+	/// Wrap a distance function for `n` objects
+	pub fn new<N>(n: usize, f: F) -> Self
+	where
+		F: Fn(usize, usize) -> N,
+	{
+		ClosureMatrix { n, f }
+	}
+}
+/// Adapter implementation for ClosureMatrix
+impl<F, N> ArrayAdapter<N> for ClosureMatrix<F>
+where
+	F: Fn(usize, usize) -> N,
+{
+	#[inline]
+	fn len(&self) -> usize {
+		self.n
+	}
+	#[inline]
+	fn is_square(&self) -> bool {
+		true
+	}
+	#[inline]
+	fn get(&self, x: usize, y: usize) -> N {
+		(self.f)(x, y)
+	}
+}
+
+/// Distance matrix computed on demand by a closure, with memoization
+///
+/// This is useful for expensive distance functions, such as the edit distance of
+/// strings, where each distance should be computed only once. The distance function
+/// is assumed to be symmetric, so each unordered pair is computed at most once
+/// (when used from multiple threads at the same time, a pair may occasionally be
+/// computed twice, as the cache is not locked during the computation).
+/// All computed distances are kept, so the memory use can grow to O(N²).
+///
+/// ## Example
+/// ```
+/// let words = ["apple", "apply", "ample", "maple"];
+/// let data = kmedoids::arrayadapter::CachingClosureMatrix::new(words.len(), |i, j| {
+///     words[i].chars().zip(words[j].chars()).filter(|(a, b)| a != b).count() as u32
+/// });
+/// let mut meds = vec![0, 1];
+/// let (loss, assi, n_iter, n_swap): (i64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 10);
+/// println!("Loss is {}, computed {} distances", loss, data.cache_size());
+/// ```
+pub struct CachingClosureMatrix<F, N> {
+	n: usize,
+	f: F,
+	cache: std::sync::Mutex<std::collections::HashMap<(usize, usize), N>>,
+}
+impl<F, N> CachingClosureMatrix<F, N>
+where
+	F: Fn(usize, usize) -> N,
+{
+	/// Wrap a distance function for `n` objects
+	pub fn new(n: usize, f: F) -> Self {
+		CachingClosureMatrix {
+			n,
+			f,
+			cache: std::sync::Mutex::new(std::collections::HashMap::new()),
+		}
+	}
+	/// Number of distances computed so far
+	pub fn cache_size(&self) -> usize {
+		self.cache.lock().unwrap().len()
+	}
+}
+/// Adapter implementation for CachingClosureMatrix
+impl<F, N> ArrayAdapter<N> for CachingClosureMatrix<F, N>
+where
+	F: Fn(usize, usize) -> N,
+	N: Copy,
+{
+	#[inline]
+	fn len(&self) -> usize {
+		self.n
+	}
+	#[inline]
+	fn is_square(&self) -> bool {
+		true
+	}
+	#[inline]
+	fn get(&self, x: usize, y: usize) -> N {
+		let key = if x < y { (x, y) } else { (y, x) };
+		// The lock is not held while computing, as the function may be slow
+		if let Some(&d) = self.cache.lock().unwrap().get(&key) {
+			return d;
+		}
+		let d = (self.f)(key.0, key.1);
+		*self.cache.lock().unwrap().entry(key).or_insert(d)
+	}
+}
+
+/// Distance matrix computed on demand by a closure, with a bounded least-recently-used cache
+///
+/// Unlike [`CachingClosureMatrix`], at most `capacity` distances are kept, and the least
+/// recently used distance is evicted when the cache is full. This is useful for expensive
+/// distance functions (e.g., sequence alignment) when the working set of the algorithm fits
+/// into memory, but the full matrix does not. The distance function is assumed to be
+/// symmetric, so both orders of a pair share one cache entry.
+///
+/// Requires the `lru` feature.
+///
+/// ## Example
+/// ```
+/// let words = ["apple", "apply", "ample", "maple"];
+/// let data = kmedoids::arrayadapter::LruCachedMatrix::new(words.len(), 4, |i, j| {
+///     words[i].chars().zip(words[j].chars()).filter(|(a, b)| a != b).count() as u32
+/// });
+/// let mut meds = vec![0, 1];
+/// let (loss, assi, n_iter, n_swap): (i64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 10);
+/// println!("Loss is {}, {} distances cached", loss, data.cache_size());
+/// ```
+#[cfg(feature = "lru")]
+pub struct LruCachedMatrix<F, N> {
+	n: usize,
+	f: F,
+	cache: std::sync::Mutex<lru::LruCache<(usize, usize), N>>,
+}
+#[cfg(feature = "lru")]
+impl<F, N> LruCachedMatrix<F, N>
+where
+	F: Fn(usize, usize) -> N,
+{
+	/// Wrap a distance function for `n` objects, caching up to `capacity` distances
+	///
+	/// ## Panics
+	///
+	/// * panics when the capacity is 0
+	pub fn new(n: usize, capacity: usize, f: F) -> Self {
+		let capacity = std::num::NonZeroUsize::new(capacity).expect("capacity must be positive");
+		LruCachedMatrix {
+			n,
+			f,
+			cache: std::sync::Mutex::new(lru::LruCache::new(capacity)),
+		}
+	}
+	/// Number of distances currently cached
+	pub fn cache_size(&self) -> usize {
+		self.cache.lock().unwrap().len()
+	}
+}
+/// Adapter implementation for LruCachedMatrix
+#[cfg(feature = "lru")]
+impl<F, N> ArrayAdapter<N> for LruCachedMatrix<F, N>
+where
+	F: Fn(usize, usize) -> N,
+	N: Copy,
+{
+	#[inline]
+	fn len(&self) -> usize {
+		self.n
+	}
+	#[inline]
+	fn is_square(&self) -> bool {
+		true
+	}
+	#[inline]
+	fn get(&self, x: usize, y: usize) -> N {
+		let key = if x < y { (x, y) } else { (y, x) };
+		// The lock is not held while computing, as the function may be slow
+		if let Some(&d) = self.cache.lock().unwrap().get(&key) {
+			return d;
+		}
+		let d = (self.f)(key.0, key.1);
+		self.cache.lock().unwrap().put(key, d);
+		d
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::{get_symmetric, AlignedLowerTriangle, ArrayAdapter, CachingClosureMatrix, DynArrayAdapter, JaggedMatrix, LowerTriangle, SquareMatrix, StaticLowerTriangle, triangle_size}, pam, util::assert_array};
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	#[test]
+	fn test_aligned_lower_triangle() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let aligned = AlignedLowerTriangle::<_, 64>::from_lower_triangle(&data);
+		assert_eq!(aligned.data().as_ptr() as usize % 64, 0, "not aligned");
+		assert_eq!(aligned.data(), &data.data[..], "data not as expected");
+		assert!(aligned.is_square(), "not square");
+		for i in 0..5 {
+			for j in 0..5 {
+				assert_eq!(aligned.get(i, j), data.get(i, j), "value not as expected");
+			}
+		}
+		let cloned = aligned.clone();
+		assert_eq!(cloned.data().as_ptr() as usize % 64, 0, "clone not aligned");
+		let (loss, assi, meds, _, _): (i64, _, _, _, _) = pam(&cloned, 2, 10);
+		let (eloss, eassi, emeds, _, _): (i64, _, _, _, _) = pam(&data, 2, 10);
+		assert_eq!((loss, assi, meds), (eloss, eassi, emeds), "result not as expected");
+		let empty = AlignedLowerTriangle::<u32, 32>::from_lower_triangle(&LowerTriangle { n: 0, data: vec![] });
+		assert!(empty.data().is_empty() && empty.is_square(), "empty matrix not as expected");
+	}
+
+	#[test]
+	fn test_get_batch() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let queries = (0..40).map(|q| (q * 7 % 5, q * 3 % 5)).collect::<Vec<_>>();
+		let expect = queries.iter().map(|&(x, y)| data.get(x, y)).collect::<Vec<_>>();
+		let mut out = vec![42; 3];
+		data.get_batch(&queries, &mut out);
+		assert_eq!(out, expect, "batch not as expected");
+		// the default implementation
+		SquareMatrix::new(5, (0..25).map(|c| data.get(c / 5, c % 5)).collect()).get_batch(&queries, &mut out);
+		assert_eq!(out, expect, "default batch not as expected");
+		data.get_batch(&[], &mut out);
+		assert!(out.is_empty(), "empty batch not as expected");
+	}
+
+	#[test]
+	fn test_iter_triangle() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let mut iter = data.iter_triangle();
+		assert_eq!(iter.len(), 10, "size not as expected");
+		iter.next();
+		assert_eq!(iter.len(), 9, "remaining size not as expected");
+		let cells = data.iter_triangle().collect::<Vec<_>>();
+		assert_eq!(cells.len(), 5 * 4 / 2, "number of cells not as expected");
+		for &(i, j, d) in cells.iter() {
+			assert!(i > j, "not below the diagonal");
+			assert_eq!(d, data.get(i, j), "value not as expected");
+		}
+		// row-major, as stored in LowerTriangle
+		assert_eq!(cells.iter().map(|c| c.2).collect::<Vec<_>>(), data.data, "order not as expected");
+		assert_eq!(cells[..4], [(1, 0, 1), (2, 0, 2), (2, 1, 3), (3, 0, 4)], "cells not as expected");
+		for n in 0..3 {
+			let small = LowerTriangle { n, data: vec![1; triangle_size(n)] };
+			assert_eq!((small.iter_triangle().len(), small.iter_triangle().count()), (triangle_size(n), triangle_size(n)), "small matrix not as expected");
+		}
+	}
+
+	#[test]
+	fn test_static_lower_triangle() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1., 2., 3., 4., 5., 6., 7., 8., 9., 1.],
+		};
+		let fixed = StaticLowerTriangle::<f64, 10>::new([1., 2., 3., 4., 5., 6., 7., 8., 9., 1.]);
+		assert_eq!((fixed.len(), StaticLowerTriangle::<f64, 10>::N_POINTS), (5, 5), "size not as expected");
+		assert!(fixed.is_square(), "not square");
+		for i in 0..5 {
+			for j in 0..5 {
+				assert_eq!(fixed.get(i, j), data.get(i, j), "value not as expected");
+			}
+		}
+		let (loss, assi, meds, _, _): (f64, _, _, _, _) = pam(&fixed, 2, 10);
+		let (eloss, eassi, emeds, _, _): (f64, _, _, _, _) = pam(&data, 2, 10);
+		assert_eq!((loss, assi, meds), (eloss, eassi, emeds), "result not as expected");
+		assert_eq!(StaticLowerTriangle::<u32, 0>::new([]).len(), 1, "empty matrix not as expected");
+		assert_eq!([0, 1, 2, 3, 4].map(triangle_size), [0, 0, 1, 3, 6], "sizes not as expected");
+	}
+
+	#[test]
+	fn test_lower_triangle_from_labeled_pairs() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let mut pairs = data.iter_triangle().collect::<Vec<_>>();
+		pairs.reverse();
+		pairs[2] = (pairs[2].1, pairs[2].0, pairs[2].2); // either order is allowed
+		let built = LowerTriangle::from_labeled_pairs(5, &pairs);
+		assert_eq!(built, data, "matrix not as expected");
+		let (loss, assi, meds, _, _): (i64, _, _, _, _) = pam(&built, 2, 10);
+		let (eloss, eassi, emeds, _, _): (i64, _, _, _, _) = pam(&data, 2, 10);
+		assert_eq!((loss, assi, meds), (eloss, eassi, emeds), "result not as expected");
+		assert_eq!(LowerTriangle::<u32>::from_labeled_pairs(1, &[]).n, 1, "single point not as expected");
+	}
+
+	#[test]
+	#[should_panic(expected = "more than once")]
+	fn test_lower_triangle_from_labeled_pairs_duplicate() {
+		LowerTriangle::from_labeled_pairs(3, &[(1, 0, 1), (2, 0, 2), (0, 1, 4)]);
+	}
+
+	#[test]
+	#[should_panic(expected = "i != j")]
+	fn test_lower_triangle_from_labeled_pairs_diagonal() {
+		LowerTriangle::from_labeled_pairs(3, &[(1, 0, 1), (2, 0, 2), (2, 2, 4)]);
+	}
+
+	#[test]
+	#[should_panic(expected = "n x (n-1) / 2 pairs")]
+	fn test_lower_triangle_from_labeled_pairs_missing() {
+		LowerTriangle::from_labeled_pairs(3, &[(1, 0, 1), (2, 0, 2)]);
+	}
+
+	#[test]
+	fn test_get_symmetric() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for (i, j, d) in data.iter_triangle() {
+			assert_eq!((get_symmetric(&data, i, j), get_symmetric(&data, j, i)), (d, d), "distance not as expected");
+		}
+	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic(expected = "not symmetric at 2,0")]
+	fn test_get_symmetric_asymmetric() {
+		let data = SquareMatrix::new(3, vec![0, 1, 2, 1, 0, 3, 4, 3, 0]);
+		get_symmetric(&data, 2, 0);
+	}
+
+	#[test]
+	fn test_lower_triangle_normalize() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let norm = data.normalize();
+		assert_eq!(norm.data.iter().fold(0., |m: f64, &d| m.max(d)), 1., "maximum not as expected");
+		assert_eq!(norm.data[0], 1. / 9., "distance not as expected");
+		let (_, assi, meds, _, _): (f64, _, _, _, _) = pam(&norm, 2, 10);
+		let (_, eassi, emeds, _, _): (i64, _, _, _, _) = pam(&data, 2, 10);
+		assert_eq!((assi, meds), (eassi, emeds), "result not as expected");
+		// z scores, floored at zero
+		let z = data.standardize();
+		let (mean, std) = (4.6, 2.727636339397171);
+		for (&d, &s) in data.data.iter().zip(z.data.iter()) {
+			let expect = (d as f64 - mean) / std;
+			assert!((s - expect.max(0.)).abs() < 1e-12, "standardized distance not as expected");
+		}
+		let constant = LowerTriangle { n: 3, data: vec![2u32; 3] };
+		assert_eq!((constant.normalize().data, constant.standardize().data), (vec![1.; 3], vec![0.; 3]), "constant distances not as expected");
+		assert_eq!(LowerTriangle { n: 2, data: vec![0u32] }.normalize().data, vec![0.], "zero distances not as expected");
+	}
+
+	#[test]
+	fn test_lower_triangle_traits() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let cloned = data.clone();
+		assert_eq!(cloned, data, "clone not equal");
+		let (loss, assi, meds, _, _): (i64, _, _, _, _) = pam(&cloned, 2, 10);
+		let (eloss, eassi, emeds, _, _): (i64, _, _, _, _) = pam(&data, 2, 10);
+		assert_eq!((loss, assi, meds), (eloss, eassi, emeds), "result not as expected");
+		let mut other = data.clone();
+		other.data[3] = 0;
+		assert_ne!(other, data, "different matrices equal");
+		assert_ne!(LowerTriangle { n: 1, data: vec![] }, LowerTriangle::<u32>::default(), "different sizes equal");
+		let set = [data.clone(), cloned, other].into_iter().collect::<std::collections::HashSet<_>>();
+		assert_eq!(set.len(), 2, "hash not as expected");
+		let empty = LowerTriangle::<f64>::default();
+		assert_eq!((empty.n, empty.data.len()), (0, 0), "default not empty");
+		assert!(empty.is_square(), "empty matrix not square");
+	}
+
+	#[test]
+	fn test_prefetch() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let square = SquareMatrix::new(5, (0..25).map(|i| data.get(i / 5, i % 5)).collect());
+		let array = ndarray::Array2::from_shape_fn((5, 5), |(i, j)| data.get(i, j));
+		let aligned = AlignedLowerTriangle::<_, 64>::from_lower_triangle(&data);
+		let dynamic = DynArrayAdapter::new(data.clone());
+		macro_rules! check {
+			($mat:expr) => {
+				let mat = $mat;
+				for i in 0..5 {
+					for j in 0..5 {
+						ArrayAdapter::prefetch(&mat, i, j + 1);
+						ArrayAdapter::prefetch(&mat, i + 1, j);
+						assert_eq!(ArrayAdapter::get(&mat, i, j), data.get(i, j), "value not as expected");
+					}
+				}
+				ArrayAdapter::prefetch(&mat, 7, 9); // outside of the matrix, ignored
+			};
+		}
+		check!(data.clone());
+		check!(square);
+		check!(array.view());
+		check!(aligned);
+		check!(dynamic);
+	}
+
+	#[test]
+	fn test_rows() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		assert_eq!(data.rows().len(), 5, "number of rows not as expected");
+		for (i, row) in data.rows().enumerate() {
+			assert_eq!(row.row(), i, "row not as expected");
+			assert_eq!(row.len(), 5, "length not as expected");
+			for j in 0..5 {
+				assert_eq!(row.get(j), data.get(i, j), "value not as expected");
+				assert_eq!(row[j], data.get(i, j), "value not as expected");
+			}
+			assert_array(row.iter().collect(), (0..5).map(|j| data.get(i, j)).collect(), "values not as expected");
+		}
+	}
+
+	#[test]
+	#[should_panic(expected = "column 5 out of range for a row of length 5")]
+	fn test_rows_index_out_of_range() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let row = data.rows().next().unwrap();
+		let _ = row[5];
+	}
+
+	#[test]
+	fn test_dyn_array_adapter() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let (loss, assi, meds, n_iter, n_swap): (i64, _, _, _, _) = pam(&data, 2, 10);
+		let data = DynArrayAdapter::new(data);
+		let (dloss, dassi, dmeds, dn_iter, dn_swap): (i64, _, _, _, _) = pam(&data, 2, 10);
+		assert_eq!(dloss, loss, "loss not as expected");
+		assert_eq!(dn_swap, n_swap, "swaps not as expected");
+		assert_eq!(dn_iter, n_iter, "iterations not as expected");
+		assert_array(dassi, assi, "assignment not as expected");
+		assert_array(dmeds, meds, "medoids not as expected");
+	}
+
+	#[test]
+	fn test_caching_closure_matrix() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let calls = AtomicUsize::new(0);
+		let slow = |i, j| {
+			calls.fetch_add(1, Ordering::Relaxed);
+			std::thread::sleep(std::time::Duration::from_millis(1));
+			data.get(i, j)
+		};
+		let cached = CachingClosureMatrix::new(5, slow);
+		let (loss, assi, meds, n_iter, n_swap): (i64, _, _, _, _) = pam(&data, 2, 10);
+		let (closs, cassi, cmeds, cn_iter, cn_swap): (i64, _, _, _, _) = pam(&cached, 2, 10);
+		assert_eq!(closs, loss, "loss not as expected");
+		assert_eq!(cn_swap, n_swap, "swaps not as expected");
+		assert_eq!(cn_iter, n_iter, "iterations not as expected");
+		assert_array(cassi, assi, "assignment not as expected");
+		assert_array(cmeds, meds, "medoids not as expected");
+		// at most once per unordered pair, including the diagonal
+		assert!(calls.load(Ordering::Relaxed) <= 5 * 6 / 2, "distances computed repeatedly");
+		assert_eq!(calls.load(Ordering::Relaxed), cached.cache_size(), "cache size not as expected");
+	}
+
+	#[cfg(feature = "lru")]
+	#[test]
+	fn test_lru_cached_matrix() {
+		use crate::{arrayadapter::{ClosureMatrix, LruCachedMatrix}, pammedsil_swap};
+		let points = (0..40).map(|i| ((i * 37) % 101) as f64).collect::<Vec<f64>>();
+		let calls = AtomicUsize::new(0);
+		let dist = |i: usize, j: usize| {
+			calls.fetch_add(1, Ordering::Relaxed);
+			(points[i] - points[j]).abs()
+		};
+		let (loss, assi, _, n_swap): (f64, _, _, _) = pammedsil_swap(&ClosureMatrix::new(40, dist), &mut vec![0, 1, 2], 10);
+		let uncached = calls.swap(0, Ordering::Relaxed);
+		// room for a fifth of the 820 pairs
+		let cached = LruCachedMatrix::new(40, 164, dist);
+		let mut meds = vec![0, 1, 2];
+		let (closs, cassi, _, cn_swap): (f64, _, _, _) = pammedsil_swap(&cached, &mut meds, 10);
+		assert_eq!((closs, cn_swap), (loss, n_swap), "result not as expected");
+		assert_array(cassi, assi, "assignment not as expected");
+		assert_eq!(cached.cache_size(), 164, "cache size not as expected");
+		let n_calls = calls.swap(0, Ordering::Relaxed);
+		assert!(n_calls * 2 <= uncached, "cache saved too few calls: {} of {}", n_calls, uncached);
+		// both orders of a pair share the entry, the oldest entry is evicted
+		let small = LruCachedMatrix::new(40, 2, dist);
+		assert_eq!(small.get(3, 5), small.get(5, 3), "not symmetric");
+		assert_eq!((small.cache_size(), calls.load(Ordering::Relaxed)), (1, 1), "pair cached twice");
+		small.get(1, 2);
+		small.get(3, 4);
+		small.get(3, 5);
+		assert_eq!(calls.load(Ordering::Relaxed), 4, "evicted pair not recomputed");
+	}
+
+	#[test]
+	fn test_jagged_matrix() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1., 2., 3., 4., 5., 6., 7., 8., 9., 1.],
+		};
+		let jagged = JaggedMatrix::new((0..5).map(|i| (0..5).map(|j| data.get(i, j)).collect()).collect());
+		assert!(jagged.is_square(), "not square");
+		let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = pam(&data, 2, 10);
+		let (jloss, jassi, jmeds, jn_iter, jn_swap): (f64, _, _, _, _) = pam(&jagged, 2, 10);
+		assert_eq!(jloss, loss, "loss not as expected");
+		assert_eq!(jn_swap, n_swap, "swaps not as expected");
+		assert_eq!(jn_iter, n_iter, "iterations not as expected");
+		assert_array(jassi, assi, "assignment not as expected");
+		assert_array(jmeds, meds, "medoids not as expected");
+	}
+
+	#[test]
+	#[should_panic]
+	fn test_jagged_matrix_invalid() {
+		JaggedMatrix::new(vec![vec![0., 1.], vec![1.]]);
+	}
 }
@@ -0,0 +1,84 @@
+use crate::arrayadapter::LowerTriangle;
+use arrow2::array::{Array, FixedSizeListArray, PrimitiveArray};
+use arrow2::types::NativeType;
+
+/// Convert a square distance matrix in an Arrow array into a lower triangular matrix.
+///
+/// Each element of the fixed-size list array is one row of the full n x n matrix, e.g.,
+/// a column of a Polars DataFrame with an array type. The values below the diagonal are
+/// copied into a [`LowerTriangle`], i.e., the upper triangle and the diagonal are ignored,
+/// and the input only needs to be read, not copied as a whole.
+///
+/// Requires the `arrow2` feature.
+///
+/// * type `N` - number data type such as `u32` or `f64`, the type of the list values
+/// * `array` - the rows of the distance matrix
+///
+/// returns the lower triangular matrix
+///
+/// ## Panics
+///
+/// * panics when the list values are not of type `N`
+/// * panics when the number of rows is not the list size
+/// * panics when the array contains null values
+///
+/// ## Example
+/// ```
+/// use arrow2::array::{FixedSizeListArray, PrimitiveArray};
+/// use arrow2::datatypes::DataType;
+/// let values = PrimitiveArray::from_vec(vec![0., 1., 2., 1., 0., 3., 2., 3., 0.]).boxed();
+/// let array = FixedSizeListArray::new(FixedSizeListArray::default_datatype(DataType::Float64, 3), values, None);
+/// let data = kmedoids::arrow_to_lower_triangle::<f64>(&array);
+/// assert_eq!(data.data, vec![1., 2., 3.]);
+/// ```
+pub fn arrow_to_lower_triangle<N: NativeType>(array: &FixedSizeListArray) -> LowerTriangle<N> {
+	let n = array.size();
+	assert_eq!(array.len(), n, "Dissimilarity matrix is not square");
+	let values = array
+		.values()
+		.as_any()
+		.downcast_ref::<PrimitiveArray<N>>()
+		.expect("list values are not of the requested type");
+	assert!(array.null_count() == 0 && values.null_count() == 0, "null values are not supported");
+	let values = values.values();
+	let mut data = Vec::with_capacity((n * n.saturating_sub(1)) >> 1);
+	for i in 1..n {
+		data.extend_from_slice(&values[i * n..i * n + i]);
+	}
+	LowerTriangle { n, data }
+}
+
+#[cfg(test)]
+mod tests {
+	use super::arrow_to_lower_triangle;
+	use crate::arrayadapter::{ArrayAdapter, LowerTriangle};
+	use arrow2::array::{FixedSizeListArray, PrimitiveArray};
+	use arrow2::datatypes::DataType;
+
+	/// The rows of a full matrix as an Arrow array
+	fn to_arrow<N: arrow2::types::NativeType>(rows: Vec<N>, n: usize, data_type: DataType) -> FixedSizeListArray {
+		let data_type = FixedSizeListArray::default_datatype(data_type, n);
+		FixedSizeListArray::new(data_type, PrimitiveArray::from_vec(rows).boxed(), None)
+	}
+
+	#[test]
+	fn test_arrow_to_lower_triangle() {
+		let expect = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let rows = (0..25).map(|x| expect.get(x / 5, x % 5)).collect::<Vec<u32>>();
+		let array = to_arrow(rows, 5, DataType::UInt32);
+		assert_eq!(arrow_to_lower_triangle::<u32>(&array), expect, "matrix not as expected");
+		// only the lower triangle is used, and sliced values are supported
+		let values = PrimitiveArray::from_vec(vec![7., 7., 0., 9., 9., 1., 0., 9., 2., 3., 0.]).sliced(2, 9).boxed();
+		let array = FixedSizeListArray::new(FixedSizeListArray::default_datatype(DataType::Float64, 3), values, None);
+		assert_eq!(arrow_to_lower_triangle::<f64>(&array).data, vec![1., 2., 3.], "sliced matrix not as expected");
+	}
+
+	#[test]
+	#[should_panic]
+	fn test_arrow_to_lower_triangle_not_square() {
+		arrow_to_lower_triangle::<f64>(&to_arrow(vec![0.; 6], 3, DataType::Float64));
+	}
+}
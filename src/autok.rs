@@ -0,0 +1,265 @@
+use crate::arrayadapter::ArrayAdapter;
+use crate::result::KMedoidsResult;
+use crate::{fastermsc, fasterpam, pammedsil_with_tie_breaking, random_initialization, silhouette, BuildTieBreaking};
+use core::ops::{AddAssign, RangeInclusive};
+use num_traits::{Float, FromPrimitive, Signed};
+use std::convert::From;
+
+/// Clustering algorithm used by [`autok_silhouette`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoKAlgorithm {
+	/// FasterMSC, optimizing the medoid silhouette
+	FasterMSC,
+	/// FasterPAM, optimizing the total deviation
+	FasterPAM,
+}
+
+/// Result of the automatic choice of k.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AutoKResult<L> {
+	/// the number of clusters with the largest silhouette
+	pub best_k: usize,
+	/// the silhouette of every k
+	pub curve: Vec<(usize, L)>,
+	/// the clustering results of every k, in the same order
+	pub results: Vec<KMedoidsResult<L>>,
+}
+
+impl<L> AutoKResult<L> {
+	/// Get the clustering result of the best k.
+	pub fn best(&self) -> &KMedoidsResult<L> {
+		&self.results[self.curve.iter().position(|&(k, _)| k == self.best_k).unwrap()]
+	}
+}
+
+/// Choose the number of clusters by the silhouette.
+///
+/// For every k in the given range, a clustering is computed with FasterMSC or FasterPAM,
+/// and evaluated with the full [`silhouette`]. The k with the largest silhouette is
+/// chosen; if multiple k have the same silhouette, the smallest k is chosen.
+///
+/// Only the smallest k is initialized randomly. The clustering for k+1 is warm-started
+/// with the medoids for k, plus the point farthest from its medoid. The computation of
+/// the silhouette needs O(N²) time for every k.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `ks` - the range of k to try
+/// * `maxiter` - the maximum number of iterations allowed for every k
+/// * `algorithm` - the clustering algorithm to use
+/// * `rng` - random number generator for the initial medoids
+///
+/// returns the chosen k, the silhouette of every k, and all clustering results
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when the range of k is empty, starts below 2, or ends above N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let result: kmedoids::AutoKResult<f64> =
+///     kmedoids::autok_silhouette(&data, 2..=3, 100, kmedoids::AutoKAlgorithm::FasterMSC, &mut rand::thread_rng());
+/// println!("Best k is: {}", result.best_k);
+/// ```
+pub fn autok_silhouette<M, N, L>(
+	mat: &M,
+	ks: RangeInclusive<usize>,
+	maxiter: usize,
+	algorithm: AutoKAlgorithm,
+	rng: &mut impl rand::Rng,
+) -> AutoKResult<L>
+where
+	N: num_traits::Zero + PartialOrd + Copy,
+	L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let results = autok_runs(mat, ks, maxiter, algorithm, rng);
+	let curve = results
+		.iter()
+		.map(|r| (r.medoids.len(), silhouette(mat, &r.assignment, false).0))
+		.collect::<Vec<(usize, L)>>();
+	AutoKResult { best_k: select_best_k(&curve), curve, results }
+}
+
+/// Choose the number of clusters for PAMMEDSIL by the medoid silhouette.
+///
+/// For every k in `k_min..=k_max`, [`crate::pammedsil`] is run, and the k with the largest
+/// medoid silhouette (the loss of PAMMEDSIL) is chosen; if multiple k have the same medoid
+/// silhouette, the smallest k is chosen. Unlike [`autok_silhouette`], which needs O(N²) time
+/// to compute the full silhouette of every k, this uses the native objective of PAMMEDSIL.
+/// The random number generator breaks ties in BUILD, see [`BuildTieBreaking::Random`].
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * type `R` - random number generator type
+/// * `mat` - a pairwise distance matrix
+/// * `k_min` - the smallest k to try
+/// * `k_max` - the largest k to try
+/// * `maxiter` - the maximum number of iterations allowed for every k
+/// * `rng` - random number generator for breaking ties
+///
+/// returns a tuple containing:
+/// * the chosen k, as requested
+/// * the clustering result for this k, which has fewer than k medoids if there are fewer
+///   than k distinct points (see [`crate::pammedsil`])
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k_min is below 2, or larger than k_max, or k_max is larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let (k, result): (_, kmedoids::KMedoidsResult<f64>) = kmedoids::pammedsil_auto_k(&data, 2, 3, 100, &mut rand::thread_rng());
+/// println!("Best k is {} with medoid silhouette {}", k, result.loss);
+/// ```
+pub fn pammedsil_auto_k<M, N, L, R>(mat: &M, k_min: usize, k_max: usize, maxiter: usize, rng: &mut R) -> (usize, KMedoidsResult<L>)
+where
+	N: num_traits::Zero + PartialOrd + Copy,
+	L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+	R: rand::Rng,
+{
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(k_min >= 2 && k_min <= k_max && k_max <= mat.len(), "invalid range of k, must be within 2..=N");
+	let results = (k_min..=k_max)
+		.map(|k| {
+			let (loss, assi, meds, n_iter, n_swap) = pammedsil_with_tie_breaking(mat, k, maxiter, BuildTieBreaking::Random(rng));
+			KMedoidsResult::new(loss, assi, meds, n_iter, n_swap)
+		})
+		.collect::<Vec<KMedoidsResult<L>>>();
+	// by the requested k, as BUILD may return fewer medoids for duplicate points
+	let curve = (k_min..=k_max).zip(results.iter()).map(|(k, r)| (k, r.loss)).collect::<Vec<_>>();
+	let best_k = select_best_k(&curve);
+	(best_k, results.into_iter().nth(best_k - k_min).unwrap())
+}
+
+/// Run the clustering for every k, with warm starts, not exposed (use autok_silhouette)
+pub(crate) fn autok_runs<M, N, L>(
+	mat: &M,
+	ks: RangeInclusive<usize>,
+	maxiter: usize,
+	algorithm: AutoKAlgorithm,
+	rng: &mut impl rand::Rng,
+) -> Vec<KMedoidsResult<L>>
+where
+	N: num_traits::Zero + PartialOrd + Copy,
+	L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(!ks.is_empty() && *ks.start() >= 2 && *ks.end() <= n, "invalid range of k, must be within 2..=N");
+	let mut meds = random_initialization(n, *ks.start(), rng);
+	let mut results = Vec::with_capacity(ks.end() - ks.start() + 1);
+	for k in ks {
+		if meds.len() < k {
+			// warm start: add the point that is farthest from its medoid
+			let prev: &KMedoidsResult<L> = results.last().unwrap();
+			let far = (0..n)
+				.filter(|i| !meds.contains(i))
+				.map(|i| (i, mat.get(i, meds[prev.assignment[i]])))
+				.reduce(|a, b| if b.1 > a.1 { b } else { a })
+				.unwrap()
+				.0;
+			meds.push(far);
+		}
+		let (loss, assi, n_iter, n_swap) = match algorithm {
+			AutoKAlgorithm::FasterMSC => fastermsc(mat, &mut meds, 0, maxiter),
+			AutoKAlgorithm::FasterPAM => fasterpam(mat, &mut meds, 0, maxiter),
+		};
+		results.push(KMedoidsResult::new(loss, assi, meds.clone(), n_iter, n_swap));
+	}
+	results
+}
+
+/// Choose the k with the largest silhouette, preferring the smaller k on ties
+pub(crate) fn select_best_k<L: PartialOrd + Copy>(curve: &[(usize, L)]) -> usize {
+	let mut best = curve[0];
+	for &(k, s) in curve.iter().skip(1) {
+		if s > best.1 {
+			best = (k, s);
+		}
+	}
+	best.0
+}
+
+#[cfg(test)]
+mod tests {
+	use super::select_best_k;
+	use crate::{arrayadapter::LowerTriangle, autok_silhouette, medoid_silhouette, pammedsil_auto_k, silhouette, AutoKAlgorithm, AutoKResult, KMedoidsResult};
+	use rand::{rngs::StdRng, Rng, SeedableRng};
+
+	/// Three well-separated blobs
+	fn three_blobs(rng: &mut StdRng) -> LowerTriangle<f64> {
+		let points = (0..60)
+			.map(|i| (i % 3 * 100 + rng.gen_range(0..10), (i % 3 == 1) as i32 * 100 + rng.gen_range(0..10)))
+			.collect::<Vec<(i32, i32)>>();
+		let n = points.len();
+		let mut data = Vec::with_capacity(n * (n - 1) / 2);
+		for i in 1..n {
+			for j in 0..i {
+				data.push((((points[i].0 - points[j].0).pow(2) + (points[i].1 - points[j].1).pow(2)) as f64).sqrt());
+			}
+		}
+		LowerTriangle { n, data }
+	}
+
+	#[test]
+	fn test_autok_silhouette() {
+		let mut rng = StdRng::seed_from_u64(1);
+		let data = three_blobs(&mut rng);
+		for algorithm in [AutoKAlgorithm::FasterMSC, AutoKAlgorithm::FasterPAM] {
+			let result: AutoKResult<f64> = autok_silhouette(&data, 2..=6, 100, algorithm, &mut rng);
+			assert_eq!(result.best_k, 3, "k not as expected");
+			assert_eq!(result.curve.iter().map(|x| x.0).collect::<Vec<_>>(), vec![2, 3, 4, 5, 6], "curve not as expected");
+			assert_eq!(result.results.len(), 5, "results not as expected");
+			for (r, &(k, s)) in result.results.iter().zip(result.curve.iter()) {
+				assert_eq!(r.medoids.len(), k, "number of medoids not as expected");
+				assert_eq!(silhouette::<_, _, f64>(&data, &r.assignment, false).0, s, "silhouette not as expected");
+			}
+			assert_eq!(result.best().medoids.len(), 3, "best result not as expected");
+		}
+	}
+
+	#[test]
+	fn test_pammedsil_auto_k() {
+		let mut rng = StdRng::seed_from_u64(1);
+		let data = three_blobs(&mut rng);
+		let (k, result): (_, KMedoidsResult<f64>) = pammedsil_auto_k(&data, 2, 6, 100, &mut rng);
+		assert_eq!(k, 3, "k not as expected");
+		assert_eq!(result.medoids.len(), 3, "number of medoids not as expected");
+		assert_eq!(medoid_silhouette::<_, _, f64>(&data, &result.medoids, false).0, result.loss, "medoid silhouette not as expected");
+		let (k, result): (_, KMedoidsResult<f64>) = pammedsil_auto_k(&data, 4, 4, 100, &mut rng);
+		assert_eq!((k, result.medoids.len()), (4, 4), "single k not as expected");
+	}
+
+	#[test]
+	fn test_pammedsil_auto_k_duplicates() {
+		// only two distinct points, so BUILD returns two medoids for every k
+		let pos = [0u32, 5, 0, 5, 0, 5, 0];
+		let data = LowerTriangle { n: 7, data: (1..7).flat_map(|i| (0..i).map(move |j| pos[i].abs_diff(pos[j]))).collect() };
+		let (k, result): (_, KMedoidsResult<f64>) = pammedsil_auto_k(&data, 3, 5, 100, &mut StdRng::seed_from_u64(1));
+		assert_eq!(k, 3, "k not as expected");
+		assert_eq!(result.medoids.len(), 2, "number of medoids not as expected");
+		assert_eq!(result.loss, 1., "medoid silhouette not as expected");
+		let (_, eassi): (f64, _) = crate::assign_to_medoids(&data, &result.medoids);
+		assert_eq!(result.assignment, eassi, "assignment not as expected");
+	}
+
+	#[test]
+	fn test_select_best_k() {
+		assert_eq!(select_best_k(&[(2, 0.5), (3, 0.7), (4, 0.7), (5, 0.6)]), 3, "smaller k not preferred");
+		assert_eq!(select_best_k(&[(2, 0.5), (3, 0.7), (4, 0.8)]), 4, "k not as expected");
+		assert_eq!(select_best_k(&[(2, 0.5)]), 2, "k not as expected");
+	}
+}
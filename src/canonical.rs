@@ -0,0 +1,89 @@
+use std::cmp::Reverse;
+
+/// Bring a clustering into canonical form, with the medoids sorted ascending.
+///
+/// Cluster labels and the order of the medoids are arbitrary, and may change with
+/// the initialization or the order of the data. This sorts the medoids, and updates
+/// the cluster assignment accordingly. This is the recommended way to compare the
+/// results of different runs, e.g., in tests.
+///
+/// Labels that do not refer to a medoid (i.e., that are at least k, such as noise
+/// labels like `usize::MAX`) are kept unchanged.
+///
+/// * `meds` - the list of medoids, will be sorted
+/// * `assi` - the cluster assignment, will be relabeled
+///
+/// ## Example
+/// ```
+/// let (mut meds, mut assi) = (vec![3, 0], vec![1, 1, 1, 0, 0]);
+/// kmedoids::canonicalize(&mut meds, &mut assi);
+/// assert_eq!(meds, vec![0, 3]);
+/// assert_eq!(assi, vec![0, 0, 0, 1, 1]);
+/// ```
+pub fn canonicalize(meds: &mut [usize], assi: &mut [usize]) {
+	let mut order = (0..meds.len()).collect::<Vec<_>>();
+	order.sort_by_key(|&c| meds[c]);
+	reorder(meds, assi, &order);
+}
+
+/// Bring a clustering into canonical form, with the clusters sorted by size.
+///
+/// The largest cluster comes first, clusters of the same size are ordered by their
+/// medoid index. Labels that do not refer to a medoid (such as noise labels) are
+/// kept unchanged, and not counted.
+///
+/// * `meds` - the list of medoids, will be reordered
+/// * `assi` - the cluster assignment, will be relabeled
+///
+/// ## Example
+/// ```
+/// let (mut meds, mut assi) = (vec![0, 3], vec![0, 1, 1, 1, 0]);
+/// kmedoids::canonicalize_by_size(&mut meds, &mut assi);
+/// assert_eq!(meds, vec![3, 0]);
+/// assert_eq!(assi, vec![1, 0, 0, 0, 1]);
+/// ```
+pub fn canonicalize_by_size(meds: &mut [usize], assi: &mut [usize]) {
+	let k = meds.len();
+	let mut sizes = vec![0; k];
+	for &a in assi.iter().filter(|&&a| a < k) {
+		sizes[a] += 1;
+	}
+	let mut order = (0..k).collect::<Vec<_>>();
+	order.sort_by_key(|&c| (Reverse(sizes[c]), meds[c]));
+	reorder(meds, assi, &order);
+}
+
+/// Permute the medoids into the given order, and relabel the assignment
+fn reorder(meds: &mut [usize], assi: &mut [usize], order: &[usize]) {
+	let k = meds.len();
+	let mut relabel = vec![0; k];
+	for (new, &old) in order.iter().enumerate() {
+		relabel[old] = new;
+	}
+	let sorted = order.iter().map(|&c| meds[c]).collect::<Vec<_>>();
+	meds.copy_from_slice(&sorted);
+	for a in assi.iter_mut().filter(|a| **a < k) {
+		*a = relabel[*a];
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{canonicalize, canonicalize_by_size, util::assert_array};
+
+	#[test]
+	fn test_canonicalize() {
+		let (mut meds, mut assi) = (vec![4, 0, 2], vec![1, 1, 2, usize::MAX, 0, 7]);
+		canonicalize(&mut meds, &mut assi);
+		assert_array(meds, vec![0, 2, 4], "medoids not as expected");
+		assert_array(assi, vec![0, 0, 1, usize::MAX, 2, 7], "assignment not as expected");
+	}
+
+	#[test]
+	fn test_canonicalize_by_size() {
+		let (mut meds, mut assi) = (vec![4, 0, 2], vec![1, 2, 2, usize::MAX, 0, 2, 0]);
+		canonicalize_by_size(&mut meds, &mut assi);
+		assert_array(meds, vec![2, 4, 0], "medoids not as expected");
+		assert_array(assi, vec![2, 0, 0, usize::MAX, 1, 0, 1], "assignment not as expected");
+	}
+}
@@ -0,0 +1,356 @@
+use crate::arrayadapter::ArrayAdapter;
+use crate::fasterpam::{do_swap, initial_assignment};
+use crate::pam::{pam_build_initialize, BuildShortfall};
+use crate::result::KMedoidsResult;
+use crate::util::*;
+use core::ops::AddAssign;
+use num_traits::{FromPrimitive, Signed, Zero};
+use std::convert::From;
+
+/// Run PAM (BUILD and SWAP) with a minimum cluster size.
+///
+/// After BUILD, every cluster with fewer than `min_size` points is merged into the
+/// cluster with the nearest medoid, and the medoid of the merged cluster is chosen
+/// anew, until all clusters are large enough. Hence fewer than k medoids are returned
+/// if BUILD produces clusters that are too small, e.g., for outliers. The SWAP then
+/// rejects every swap that would create a cluster with fewer than `min_size` points.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `min_size` - the minimum number of points in each cluster
+/// * `maxiter` - the maximum number of iterations allowed
+///
+/// returns the clustering result
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+/// * panics when min_size is larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let result: kmedoids::KMedoidsResult<i64> = kmedoids::pam_min_cluster_size(&data, 2, 2, 100);
+/// println!("Loss is: {}", result.loss);
+/// ```
+pub fn pam_min_cluster_size<M, N, L>(mat: &M, k: usize, min_size: usize, maxiter: usize) -> KMedoidsResult<L>
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
+	assert!(k <= n, "k must be at most N");
+	assert!(min_size <= n, "min_size must be at most N");
+	let mut meds = Vec::<usize>::with_capacity(k);
+	let mut data = Vec::<Rec<N>>::with_capacity(n);
+	let _: L = pam_build_initialize(mat, &mut meds, &mut data, k, None, BuildShortfall::Truncate, None);
+	let (mut loss, mut data) = merge_small_clusters(mat, &mut meds, min_size);
+	let k = meds.len();
+	if k == 1 {
+		let assi = vec![0; n];
+		if maxiter == 0 {
+			return KMedoidsResult::new(loss, assi, meds, 0, 0);
+		}
+		let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, &mut meds, 0);
+		return KMedoidsResult::new(loss, assi, meds, 1, if swapped { 1 } else { 0 });
+	}
+	let (mut n_swaps, mut iter) = (0, 0);
+	while iter < maxiter {
+		iter += 1;
+		let mut best = (L::zero(), k, usize::MAX);
+		let mut sizes = vec![0; k];
+		for j in 0..n {
+			if j == meds[data[j].near.i as usize] {
+				continue; // This already is a medoid
+			}
+			for m in 0..k {
+				let change: L = swap_change_and_sizes(mat, &data, m, j, &mut sizes);
+				if change < best.0 && sizes.iter().all(|&s| s >= min_size) {
+					best = (change, m, j);
+				}
+			}
+		}
+		if best.0 < L::zero() {
+			n_swaps += 1;
+			// perform the swap
+			let newloss = do_swap(mat, &mut meds, &mut data, best.1, best.2);
+			if newloss >= loss {
+				break; // Probably numerically unstable now.
+			}
+			loss = newloss;
+		} else {
+			break; // No improvement, or NaN.
+		}
+	}
+	let assi = data.iter().map(|x| x.near.i as usize).collect();
+	KMedoidsResult::new(loss, assi, meds, iter, n_swaps)
+}
+
+/// Merge the clusters below the minimum size, not exposed (use pam_min_cluster_size)
+fn merge_small_clusters<M, N, L>(mat: &M, meds: &mut Vec<usize>, min_size: usize) -> (L, Vec<Rec<N>>)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	loop {
+		let (loss, data) = initial_assignment(mat, meds);
+		let k = meds.len();
+		let mut sizes = vec![0; k];
+		data.iter().for_each(|r| sizes[r.near.i as usize] += 1);
+		// the smallest cluster that is too small, the first on ties
+		let small = (0..k).filter(|&c| sizes[c] < min_size).min_by_key(|&c| sizes[c]);
+		let c = match small {
+			Some(c) if k > 1 => c,
+			_ => return (loss, data),
+		};
+		let t = (0..k)
+			.filter(|&t| t != c)
+			.reduce(|a, b| if mat.get(meds[c], meds[b]) < mat.get(meds[c], meds[a]) { b } else { a })
+			.unwrap();
+		let assi = data.iter().map(|r| if r.near.i as usize == c { t } else { r.near.i as usize }).collect::<Vec<_>>();
+		let _: (bool, L) = choose_medoid_within_partition(mat, &assi, meds, t);
+		meds.remove(c);
+	}
+}
+
+/// Run PAM (BUILD and SWAP) with a maximum cluster size.
+///
+/// The points are not assigned to their nearest medoid, but greedily with a capacity:
+/// all pairs of a point and a medoid are processed by increasing distance, and the point
+/// is assigned to the medoid unless the point is already assigned or the cluster is full.
+/// Hence each point is assigned to the nearest medoid below capacity, and every medoid
+/// is in its own cluster. The SWAP evaluates every swap with this assignment, which
+/// needs O(k² N² log(kN)) time per iteration, much slower than PAM.
+///
+/// If there are fewer than k distinct points, BUILD is filled up with the farthest points
+/// (see [`BuildShortfall::FillFarthest`]), so that k medoids are always returned.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `max_size` - the maximum number of points in each cluster
+/// * `maxiter` - the maximum number of iterations allowed
+///
+/// returns the clustering result
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+/// * panics when k times max_size is less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let result: kmedoids::KMedoidsResult<i64> = kmedoids::pam_max_cluster_size(&data, 2, 2, 100);
+/// assert!(result.assignment.iter().filter(|&&a| a == 0).count() <= 2);
+/// ```
+pub fn pam_max_cluster_size<M, N, L>(mat: &M, k: usize, max_size: usize, maxiter: usize) -> KMedoidsResult<L>
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
+	assert!(k <= n, "k must be at most N");
+	assert!(k.saturating_mul(max_size) >= n, "k times max_size must be at least N");
+	let mut meds = Vec::<usize>::with_capacity(k);
+	let mut data = Vec::<Rec<N>>::with_capacity(n);
+	let _: L = pam_build_initialize(mat, &mut meds, &mut data, k, None, BuildShortfall::FillFarthest, None);
+	let (mut loss, mut assi) = capacitated_assignment::<M, N, L>(mat, &meds, max_size);
+	let (mut n_swaps, mut iter) = (0, 0);
+	while iter < maxiter {
+		iter += 1;
+		let mut best: Option<(L, Vec<usize>, usize, usize)> = None;
+		for j in 0..n {
+			if meds.contains(&j) {
+				continue; // This already is a medoid
+			}
+			for m in 0..k {
+				let first = meds[m];
+				meds[m] = j;
+				let (lossj, assij) = capacitated_assignment::<M, N, L>(mat, &meds, max_size);
+				meds[m] = first;
+				if lossj < best.as_ref().map_or(loss, |b| b.0) {
+					best = Some((lossj, assij, m, j));
+				}
+			}
+		}
+		match best {
+			Some((lossj, assij, m, j)) => {
+				n_swaps += 1;
+				meds[m] = j;
+				(loss, assi) = (lossj, assij);
+			}
+			None => break, // No improvement, or NaN.
+		}
+	}
+	KMedoidsResult::new(loss, assi, meds, iter, n_swaps)
+}
+
+/// Assign each point to the nearest medoid below capacity, not exposed (use pam_max_cluster_size)
+fn capacitated_assignment<M, N, L>(mat: &M, meds: &[usize], max_size: usize) -> (L, Vec<usize>)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Zero + From<N>,
+	M: ArrayAdapter<N>,
+{
+	let (n, k) = (mat.len(), meds.len());
+	let (mut assi, mut sizes, mut loss) = (vec![usize::MAX; n], vec![0; k], L::zero());
+	// every medoid is in its own cluster
+	for (m, &me) in meds.iter().enumerate() {
+		assi[me] = m;
+		sizes[m] += 1;
+	}
+	let mut pairs = (0..n)
+		.filter(|&i| assi[i] == usize::MAX)
+		.flat_map(|i| (0..k).map(move |m| (i, m)))
+		.map(|(i, m)| (mat.get(i, meds[m]), i, m))
+		.collect::<Vec<_>>();
+	// stable sort, so ties are broken by point and then medoid
+	pairs.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(core::cmp::Ordering::Equal));
+	for (d, i, m) in pairs {
+		if assi[i] == usize::MAX && sizes[m] < max_size {
+			assi[i] = m;
+			sizes[m] += 1;
+			loss += L::from(d);
+		}
+	}
+	(loss, assi)
+}
+
+/// Loss change when replacing medoid m with j, not exposed
+///
+/// The points are reassigned with the same rules as in `do_swap`, and the
+/// resulting cluster sizes are written into `sizes`, which has length k.
+fn swap_change_and_sizes<M, N, L>(mat: &M, data: &[Rec<N>], m: usize, j: usize, sizes: &mut [usize]) -> L
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	sizes.fill(0);
+	sizes[m] += 1; // j becomes medoid
+	let mut acc: L = -L::from(data[j].near.d);
+	for (o, reco) in data.iter().enumerate() {
+		if o == j {
+			continue;
+		}
+		let doj = mat.get(o, j);
+		if reco.near.i as usize == m {
+			// Current medoid is being replaced
+			if doj < reco.seco.d {
+				acc += L::from(doj) - L::from(reco.near.d);
+				sizes[m] += 1;
+			} else {
+				acc += L::from(reco.seco.d) - L::from(reco.near.d);
+				sizes[reco.seco.i as usize] += 1;
+			}
+		} else if doj < reco.near.d {
+			acc += L::from(doj) - L::from(reco.near.d);
+			sizes[m] += 1;
+		} else {
+			sizes[reco.near.i as usize] += 1;
+		}
+	}
+	acc
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::{ArrayAdapter, LowerTriangle}, assign_to_medoids, pam, pam_max_cluster_size, pam_min_cluster_size};
+	use rand::{rngs::StdRng, Rng, SeedableRng};
+
+	/// Distances of points on a line
+	fn line(points: &[i32]) -> LowerTriangle<u32> {
+		let n = points.len();
+		let mut data = Vec::with_capacity(n * (n - 1) / 2);
+		for i in 1..n {
+			for j in 0..i {
+				data.push((points[i] - points[j]).unsigned_abs());
+			}
+		}
+		LowerTriangle { n, data }
+	}
+
+	fn sizes(assi: &[usize], k: usize) -> Vec<usize> {
+		let mut sizes = vec![0; k];
+		assi.iter().for_each(|&a| sizes[a] += 1);
+		sizes
+	}
+
+	#[test]
+	fn test_pam_min_cluster_size() {
+		// three groups of six points, and two outliers
+		let mut points = (0..18).map(|i| i / 6 * 100 + i % 6).collect::<Vec<i32>>();
+		points.extend([1000, 1001]);
+		let data = line(&points);
+		let (_, assi, _, _, _): (i64, _, _, _, _) = pam(&data, 4, 10);
+		assert!(sizes(&assi, 4).contains(&2), "outliers not a cluster in PAM");
+		let result = pam_min_cluster_size::<_, _, i64>(&data, 4, 3, 10);
+		assert_eq!(result.medoids.len(), 3, "number of medoids not as expected");
+		assert!(sizes(&result.assignment, 3).iter().all(|&s| s >= 3), "cluster too small");
+		let (loss, assi): (i64, _) = assign_to_medoids(&data, &result.medoids);
+		assert_eq!((loss, assi), (result.loss, result.assignment), "assignment not as expected");
+
+		let mut rng = StdRng::seed_from_u64(1);
+		let points = (0..20).map(|_| rng.gen_range(0..100)).collect::<Vec<i32>>();
+		let data = line(&points);
+		let result = pam_min_cluster_size::<_, _, i64>(&data, 4, 3, 10);
+		let k = result.medoids.len();
+		assert!(k > 0 && k <= 4, "number of medoids not as expected");
+		assert!(sizes(&result.assignment, k).iter().all(|&s| s >= 3), "cluster too small");
+		let result = pam_min_cluster_size::<_, _, i64>(&data, 4, 0, 10);
+		let (loss, assi, meds, n_iter, n_swap): (i64, _, _, _, _) = pam(&data, 4, 10);
+		assert_eq!(result, crate::KMedoidsResult::new(loss, assi, meds, n_iter, n_swap), "not the same as pam");
+	}
+
+	#[test]
+	fn test_pam_max_cluster_size() {
+		// a group of fifteen points, and a group of five points
+		let points = (0..20).map(|i| if i < 15 { i } else { 100 + i }).collect::<Vec<i32>>();
+		let data = line(&points);
+		let (_, assi, _, _, _): (i64, _, _, _, _) = pam(&data, 2, 10);
+		assert!(sizes(&assi, 2).contains(&15), "groups not clusters in PAM");
+		let result = pam_max_cluster_size::<_, _, i64>(&data, 2, 12, 10);
+		assert_eq!(result.medoids.len(), 2, "number of medoids not as expected");
+		assert!(sizes(&result.assignment, 2).iter().all(|&s| s <= 12), "cluster too large");
+		for (m, &me) in result.medoids.iter().enumerate() {
+			assert_eq!(result.assignment[me], m, "medoid not in its cluster");
+		}
+		let loss = result.assignment.iter().enumerate().map(|(i, &a)| data.get(i, result.medoids[a]) as i64).sum::<i64>();
+		assert_eq!(loss, result.loss, "loss not as expected");
+
+		let mut rng = StdRng::seed_from_u64(1);
+		let points = (0..20).map(|_| rng.gen_range(0..100)).collect::<Vec<i32>>();
+		let data = line(&points);
+		let result = pam_max_cluster_size::<_, _, i64>(&data, 2, 12, 10);
+		assert!(sizes(&result.assignment, 2).iter().all(|&s| s <= 12), "cluster too large");
+		let result = pam_max_cluster_size::<_, _, i64>(&data, 2, 20, 10);
+		let (loss, _, _, _, _): (i64, _, _, _, _) = pam(&data, 2, 10);
+		assert_eq!(result.loss, loss, "not the same loss as pam");
+	}
+
+	#[test]
+	#[should_panic]
+	fn test_pam_max_cluster_size_infeasible() {
+		let data = line(&[1, 2, 3, 4, 5]);
+		pam_max_cluster_size::<_, _, i64>(&data, 2, 2, 10);
+	}
+}
@@ -1,19 +1,12 @@
 use crate::arrayadapter::ArrayAdapter;
+use crate::profiling::{self, timed, Phase, Stopwatch};
 use crate::util::*;
 use core::ops::AddAssign;
 use num_traits::{Signed, Zero, Float, FromPrimitive};
 use std::convert::From;
+use std::sync::atomic::AtomicBool;
 use crate::fastermsc::{initial_assignment,update_removal_loss,find_best_swap,do_swap,fastermsc_k2};
 
-#[inline]
-fn _loss<N, L>(a: N, b: N) -> L
-	where
-		N: Zero + Copy,
-		L: Float + From<N>,
-{
-	if N::is_zero(&a) || N::is_zero(&b) { L::zero() } else { <L as From<N>>::from(a) / <L as From<N>>::from(b) } 
-}
-
 /// Run the DynMSC algorithm.
 ///
 /// We begin with a maximum number of clusters, optimize the Average Medoid Silhouette,
@@ -44,7 +37,7 @@ fn _loss<N, L>(a: N, b: N) -> L
 /// ```
 /// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
 /// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
-/// let (loss, assi, n_iter, n_swap, meds, losses): (f64, _, _, _, _, _) = kmedoids::dynmsc(&data, &meds, 100);
+/// let (loss, assi, n_iter, n_swap, meds, losses): (f64, _, _, _, _, _) = kmedoids::dynmsc(&data, &meds, 0, 100);
 /// println!("Loss is: {}", loss);
 /// println!("Best k: {}", meds.len());
 /// ```
@@ -59,7 +52,80 @@ pub fn dynmsc<M, N, L>(
 		L: Float + Signed + AddAssign + From<N> + From<u32> + std::fmt::Debug + FromPrimitive + std::fmt::Display,
 		M: ArrayAdapter<N>,
 {
-	let mut med = med.clone();
+	let (loss, assi, n_iter, n_swap, meds, losses, _) = dynmsc_optimize(mat, med, n_fixed_meds, maxiter, None);
+	(loss, assi, n_iter, n_swap, meds, losses)
+}
+
+/// Run the DynMSC algorithm, with cancellation.
+///
+/// The flag is checked (with relaxed ordering) before each candidate is evaluated.
+/// When it is set, the current number of clusters is still evaluated, then the best
+/// solution found so far is returned. The losses of the numbers of clusters not yet
+/// visited remain zero.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `n_fixed_meds` - the number of leading medoids that must not be swapped
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `cancel` - cancellation flag, may be set from another thread
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+/// * the best medoids
+/// * the losses for each number of clusters
+/// * whether the run was cancelled
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let meds = kmedoids::random_initialization(4, 3, &mut rand::thread_rng());
+/// let cancel = std::sync::atomic::AtomicBool::new(false);
+/// let (loss, assi, n_iter, n_swap, meds, losses, cancelled): (f64, _, _, _, _, _, _) = kmedoids::dynmsc_cancellable(&data, &meds, 0, 100, &cancel);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn dynmsc_cancellable<M, N, L>(
+	mat: &M,
+	med: &[usize],
+	n_fixed_meds: usize,
+	maxiter: usize,
+	cancel: &AtomicBool,
+) -> (L, Vec<usize>, usize, usize, Vec<usize>, Vec<L>, bool)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + From<u32> + std::fmt::Debug + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	dynmsc_optimize(mat, med, n_fixed_meds, maxiter, Some(cancel))
+}
+
+/// Main optimization function of DynMSC, not exposed (use dynmsc)
+#[allow(clippy::type_complexity)]
+fn dynmsc_optimize<M, N, L>(
+	mat: &M,
+	med: &[usize],
+	n_fixed_meds: usize,
+	maxiter: usize,
+	cancel: Option<&AtomicBool>,
+) -> (L, Vec<usize>, usize, usize, Vec<usize>, Vec<L>, bool)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + From<u32> + std::fmt::Debug + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	profiling::reset();
+	let mut med = med.to_vec();
 	let (n, mut k) = (mat.len(), med.len());
 	if k == 1 {
 		let mut return_loss = vec![L::zero(); 1 as usize];
@@ -67,9 +133,9 @@ pub fn dynmsc<M, N, L>(
 		let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, &mut med, 0);
 		return_loss[0] = loss;
 		let return_meds = med.clone();
-		return (loss, assi, 1, if swapped { 1 } else { 0 }, return_meds, return_loss);
+		return (loss, assi, 1, if swapped { 1 } else { 0 }, return_meds, return_loss, false);
 	}
-	let (mut loss, mut data): (L, _) = initial_assignment(mat, &med);
+	let (mut loss, mut data): (L, _) = timed(Phase::InitialAssignment, || initial_assignment(mat, &med));
 	debug_assert_assignment_th(mat, &med, &data);
 
 	let mut return_loss = vec![L::zero(); k - 1];
@@ -80,29 +146,36 @@ pub fn dynmsc<M, N, L>(
 	let (mut lastswap, mut n_swaps, mut iter);
 	let mut removal_loss = vec![L::zero(); k];
 	let mut return_meds = med.clone();
+	let mut cancelled = false;
 	while k >= 3 {
 		update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
 		lastswap = n;
 		n_swaps = 0;
 		iter = 0;
-		while iter < maxiter {
+		while iter < maxiter && !cancelled {
 			iter += 1;
+			let _iteration = Stopwatch::start(Phase::Iteration);
 			let (swaps_before, lastloss) = (n_swaps, loss);
 			for j in 0..n {
 				if j == lastswap {
 					break;
 				}
+				if is_cancelled(cancel) {
+					cancelled = true;
+					break;
+				}
 				if j == med[data[j].near.i as usize] {
 					continue; // This already is a medoid
 				}
-				let (change, b) = find_best_swap(mat, &removal_loss, &data, j);
+				let (change, b) = timed(Phase::FindBestSwap, || find_best_swap(mat, &removal_loss, &data, j));
 				if change <= L::zero() {
 					continue; // No improvement
 				}
 				n_swaps += 1;
 				lastswap = j;
 				// perform the swap
-				loss = do_swap(mat, &mut med, &mut data, b, j);
+				loss = timed(Phase::DoSwap, || do_swap(mat, &mut med, &mut data, b, j));
+				debug_assert_assignment_th(mat, &med, &data);
 				update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
 			}
 			if n_swaps == swaps_before || loss >= lastloss {
@@ -125,11 +198,14 @@ pub fn dynmsc<M, N, L>(
 		}
 		return_swaps = return_swaps + n_swaps;
 		return_iter = return_iter + iter;
+		if cancelled {
+			return (best_loss, return_assi, return_iter, return_swaps, return_meds, return_loss, true);
+		}
 		loss = remove_med(mat, &mut med, &mut data, r.1);
 		removal_loss.remove(r.1);
 		k = med.len();
 	}
-	let (loss2, assi2, iter2, n_swaps2): (L, _, _, _) = fastermsc_k2(mat, &mut med, maxiter);
+	let (loss2, assi2, iter2, n_swaps2, cancelled, _): (L, _, _, _, _, _) = fastermsc_k2(mat, &mut med, maxiter, cancel, ScanOrder::Sequential);
 	return_loss[0] = loss2;
 	if loss2 > best_loss {
 		return_meds = med.clone();
@@ -137,7 +213,7 @@ pub fn dynmsc<M, N, L>(
 	}
 	return_swaps = return_swaps + n_swaps2;
 	return_iter = return_iter + iter2;
-	(best_loss, return_assi, return_iter, return_swaps, return_meds, return_loss)
+	(best_loss, return_assi, return_iter, return_swaps, return_meds, return_loss, cancelled)
 }
 
 /// Update the third nearest medoid information
@@ -229,7 +305,7 @@ pub(crate) fn remove_med<M, N, L>(
 					reco.third.i = b as u32;
 				}
 			}
-			_loss::<N, L>(reco.near.d, reco.seco.d)
+			safe_ratio::<N, L>(reco.near.d, reco.seco.d)
 		})
 		.reduce(L::add)
 		.unwrap()
@@ -237,13 +313,14 @@ pub(crate) fn remove_med<M, N, L>(
 #[cfg(test)]
 mod tests {
 	// TODO: use a larger, much more interesting example.
-	use crate::{dynmsc, silhouette, medoid_silhouette, initialization::random_initialization};
+	use crate::{dynmsc, dynmsc_cancellable, silhouette, medoid_silhouette, initialization::random_initialization};
+	use std::sync::atomic::AtomicBool;
 
 	#[test]
 	fn testdynmsc_simple() {
 		let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
 		let mut meds = random_initialization(4, 3, &mut rand::thread_rng());
-		let (loss, assi, n_iter, n_swap, best_meds, losses): (f64, _, _, _, _, _) = dynmsc(&data, &mut meds, 100);
+		let (loss, assi, n_iter, n_swap, best_meds, losses): (f64, _, _, _, _, _) = dynmsc(&data, &mut meds, 0, 100);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		let (msil, _): (f64, _) = medoid_silhouette(&data, &best_meds, false);
 		print!("DynMSC: {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?}", loss, n_iter, n_swap, msil, sil, assi, best_meds, losses);
@@ -251,4 +328,19 @@ mod tests {
 		assert_eq!(msil, 0.9375, "Medoid Silhouette not as expected");
 		assert_eq!(best_meds.len(), 3, "Best k not as expected");
 	}
+
+	#[test]
+	fn testdynmsc_cancelled() {
+		let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+		let meds = vec![0, 1, 2];
+		let cancel = AtomicBool::new(true);
+		let (loss, assi, _, n_swap, best_meds, losses, cancelled): (f64, _, _, _, _, _, _) = dynmsc_cancellable(&data, &meds, 0, 100, &cancel);
+		let (msil, _): (f64, _) = medoid_silhouette(&data, &best_meds, false);
+		assert!(cancelled, "run not cancelled");
+		assert_eq!(n_swap, 0, "swaps not as expected");
+		assert_eq!(loss, msil, "loss not as expected");
+		assert_eq!(best_meds, meds, "medoids not as expected");
+		assert_eq!(assi.len(), 4, "assignment not as expected");
+		assert_eq!(losses[0], 0., "unvisited k not as expected");
+	}
 }
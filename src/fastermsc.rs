@@ -1,17 +1,12 @@
 use crate::arrayadapter::ArrayAdapter;
+use crate::fasterpam::StepOutcome;
+use crate::pammedsil::BuildTieBreaking;
+use crate::profiling::{self, timed, Phase, Stopwatch};
 use crate::util::*;
 use core::ops::AddAssign;
 use num_traits::{Signed, Zero, Float, FromPrimitive};
 use std::convert::From;
-
-#[inline]
-fn _loss<N, L>(a: N, b: N) -> L
-	where
-		N: Zero + Copy,
-		L: Float + From<N>,
-{
-	if N::is_zero(&a) || N::is_zero(&b) { L::zero() } else { <L as From<N>>::from(a) / <L as From<N>>::from(b) } 
-}
+use std::sync::atomic::AtomicBool;
 
 /// Run the FasterMSC algorithm.
 ///
@@ -24,7 +19,7 @@ fn _loss<N, L>(a: N, b: N) -> L
 /// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
 /// * `mat` - a pairwise distance matrix
 /// * `med` - the list of medoids
-/// * `maxiter` - the maximum number of iterations allowed
+/// * `maxiter` - the maximum number of iterations allowed; with 0, the given medoids are only evaluated
 ///
 /// returns a tuple containing:
 /// * the final loss
@@ -42,7 +37,7 @@ fn _loss<N, L>(a: N, b: N) -> L
 /// ```
 /// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
 /// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
-/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fastermsc(&data, &mut meds, 100);
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fastermsc(&data, &mut meds, 0, 100);
 /// println!("Loss is: {}", loss);
 /// ```
 pub fn fastermsc<M, N, L>(
@@ -56,39 +51,276 @@ pub fn fastermsc<M, N, L>(
 		L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
 		M: ArrayAdapter<N>,
 {
+	let (loss, assi, n_iter, n_swap, _, _) = fastermsc_optimize(mat, med, n_fixed_meds, maxiter, None, ScanOrder::Sequential);
+	(loss, assi, n_iter, n_swap)
+}
+
+/// Run the FasterMSC algorithm, and also report the total deviation.
+///
+/// The total deviation (the loss of k-medoids) of the final solution is computed from
+/// the cached nearest medoid distances in O(n), and is identical to calling
+/// [`assign_to_medoids`](crate::assign_to_medoids) afterwards.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `n_fixed_meds` - the number of leading medoids that must not be swapped
+/// * `maxiter` - the maximum number of iterations allowed
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+/// * the total deviation of the final solution
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter, n_swap, td): (f64, _, _, _, _) = kmedoids::fastermsc_dual(&data, &mut meds, 0, 100);
+/// println!("Loss is: {}, total deviation is: {}", loss, td);
+/// ```
+pub fn fastermsc_dual<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	n_fixed_meds: usize,
+	maxiter: usize,
+) -> (L, Vec<usize>, usize, usize, L)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, assi, n_iter, n_swap, _, td) = fastermsc_optimize(mat, med, n_fixed_meds, maxiter, None, ScanOrder::Sequential);
+	(loss, assi, n_iter, n_swap, td)
+}
+
+/// Run the FasterMSC algorithm with additional randomization.
+///
+/// The candidates are scanned in a new random order in every iteration. Because the
+/// swaps are performed eagerly, this removes the bias towards low indexes, and
+/// increases the chance of finding a better solution when used multiple times.
+/// Convergence is only detected after a full iteration without swaps.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `n_fixed_meds` - the number of leading medoids that must not be swapped
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `rng` - random number generator for shuffling the candidates
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::rand_fastermsc(&data, &mut meds, 0, 100, &mut rand::thread_rng());
+/// println!("Loss is: {}", loss);
+/// ```
+#[cfg(feature = "rand")]
+pub fn rand_fastermsc<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	n_fixed_meds: usize,
+	maxiter: usize,
+	rng: &mut impl rand::Rng,
+) -> (L, Vec<usize>, usize, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, assi, n_iter, n_swap, _, _) = fastermsc_optimize(mat, med, n_fixed_meds, maxiter, None, ScanOrder::Shuffled(rng));
+	(loss, assi, n_iter, n_swap)
+}
+
+/// Run the FasterMSC algorithm, scanning the candidates in the given order.
+///
+/// The same order is used in every iteration. This allows reproducing a run of
+/// [`rand_fastermsc`] with a fixed permutation, or to study the effect of the order.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `n_fixed_meds` - the number of leading medoids that must not be swapped
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `order` - a permutation of 0..n, the order of the candidates
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+/// * panics when the order is not a permutation of 0..n
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = vec![0, 1];
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fastermsc_with_order(&data, &mut meds, 0, 100, &[3, 2, 1, 0]);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn fastermsc_with_order<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	n_fixed_meds: usize,
+	maxiter: usize,
+	order: &[usize],
+) -> (L, Vec<usize>, usize, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, assi, n_iter, n_swap, _, _) = fastermsc_optimize(mat, med, n_fixed_meds, maxiter, None, ScanOrder::Fixed(order));
+	(loss, assi, n_iter, n_swap)
+}
+
+/// Run the FasterMSC algorithm, with cancellation.
+///
+/// The flag is checked (with relaxed ordering) before each candidate is evaluated.
+/// Because FasterMSC performs swaps eagerly, the current solution is always
+/// consistent, and is returned when the flag is set.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `n_fixed_meds` - the number of leading medoids that must not be swapped
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `cancel` - cancellation flag, may be set from another thread
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+/// * whether the run was cancelled
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let cancel = std::sync::atomic::AtomicBool::new(false);
+/// let (loss, assi, n_iter, n_swap, cancelled): (f64, _, _, _, _) = kmedoids::fastermsc_cancellable(&data, &mut meds, 0, 100, &cancel);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn fastermsc_cancellable<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	n_fixed_meds: usize,
+	maxiter: usize,
+	cancel: &AtomicBool,
+) -> (L, Vec<usize>, usize, usize, bool)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, assi, n_iter, n_swap, cancelled, _) = fastermsc_optimize(mat, med, n_fixed_meds, maxiter, Some(cancel), ScanOrder::Sequential);
+	(loss, assi, n_iter, n_swap, cancelled)
+}
+
+/// Main optimization function of FasterMSC, not exposed (use fastermsc)
+///
+/// additionally returns whether the run was cancelled, and the total deviation
+fn fastermsc_optimize<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	n_fixed_meds: usize,
+	maxiter: usize,
+	cancel: Option<&AtomicBool>,
+	mut order: ScanOrder,
+) -> (L, Vec<usize>, usize, usize, bool, L)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	profiling::reset();
 	let (n, k) = (mat.len(), med.len());
 	if k == 1 {
 		let assi = vec![0; n];
+		if maxiter == 0 {
+			return (L::one(), assi, 0, 0, false, single_medoid_loss(mat, med[0])); // only evaluate the given medoid
+		}
 		let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, med, 0);
-		return (loss, assi, 1, if swapped { 1 } else { 0 });
+		return (L::one(), assi, 1, if swapped { 1 } else { 0 }, false, loss); // as medoid_silhouette
 	}
 	if k == 2 { // special hadling, as there is no third
-		return fastermsc_k2(mat, med, maxiter);
+		return fastermsc_k2(mat, med, maxiter, cancel, order);
 	}
-	let (mut loss, mut data):(L,_) = initial_assignment(mat, med);
+	let (mut loss, mut data):(L,_) = timed(Phase::InitialAssignment, || initial_assignment(mat, med));
 	debug_assert_assignment_th(mat, med, &data);
 
 	let mut removal_loss = vec![L::zero(); k];
 	update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
-	let (mut lastswap, mut n_swaps, mut iter) = (n, 0, 0);
-	while iter < maxiter {
+	let mut seq = order.initial(n);
+	let (mut lastswap, mut n_swaps, mut iter, mut cancelled) = (n, 0, 0, false);
+	while iter < maxiter && !cancelled {
 		iter += 1;
+		let _iteration = Stopwatch::start(Phase::Iteration);
+		if order.next_iteration(&mut seq) {
+			lastswap = n; // scan all candidates in the new order
+		}
 		let (swaps_before, lastloss) = (n_swaps, loss);
-		for j in 0..n {
+		for &j in seq.iter() {
 			if j == lastswap {
 				break;
 			}
+			if is_cancelled(cancel) {
+				cancelled = true;
+				break;
+			}
 			if j == med[data[j].near.i as usize] {
 				continue; // This already is a medoid
 			}
-			let (change, b) = find_best_swap(mat, &removal_loss, &data, j);
+			let (change, b) = timed(Phase::FindBestSwap, || find_best_swap(mat, &removal_loss, &data, j));
 			if change <= L::zero() {
 				continue; // No improvement
 			}
 			n_swaps += 1;
 			lastswap = j;
 			// perform the swap
-			loss = do_swap(mat, med, &mut data, b, j);
+			loss = timed(Phase::DoSwap, || do_swap(mat, med, &mut data, b, j));
+			debug_assert_assignment_th(mat, med, &data);
 			update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
 		}
 		if n_swaps == swaps_before || loss >= lastloss {
@@ -96,8 +328,197 @@ pub fn fastermsc<M, N, L>(
 		}
 	}
 	let assi = data.iter().map(|x| x.near.i as usize).collect();
+	let td = data.iter().fold(L::zero(), |acc, x| acc + <L as From<N>>::from(x.near.d));
 	loss = L::one() - loss / <L as From<u32>>::from(n as u32);
-	(loss, assi, iter, n_swaps)
+	(loss, assi, iter, n_swaps, cancelled, td)
+}
+
+/// Cached nearest medoid information of FasterMSC; k=2 is special.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum MSCCache<N, L> {
+	General { data: Vec<Reco<N>>, removal_loss: Vec<L> },
+	K2 { assi: Vec<usize>, data: Vec<(N, N)> },
+}
+
+/// Resumable state of the FasterMSC algorithm.
+///
+/// This runs FasterMSC one iteration at a time, for example to checkpoint long
+/// optimizations. With the `serde` feature, the state can be serialized, and the
+/// optimization be continued later. Calling [`FasterMSCState::step`] until
+/// convergence gives the same result as [`fastermsc`].
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let mut state = kmedoids::FasterMSCState::<_, f64>::new(&data, meds, 0);
+/// while state.n_iter() < 100 && state.step(&data) == kmedoids::StepOutcome::Improved {}
+/// println!("Loss is: {}", state.loss());
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FasterMSCState<N, L> {
+	med: Vec<usize>,
+	cache: MSCCache<N, L>,
+	loss: L,
+	n_fixed_meds: usize,
+	lastswap: usize,
+	n_iter: usize,
+	n_swap: usize,
+	converged: bool,
+}
+
+impl<N, L> FasterMSCState<N, L>
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
+{
+	/// Initialize the state by assigning all points to the initial medoids.
+	///
+	/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+	/// * `mat` - a pairwise distance matrix
+	/// * `med` - the list of initial medoids
+	/// * `n_fixed_meds` - the number of leading medoids that must not be swapped
+	///
+	/// ## Panics
+	///
+	/// * panics when the dissimilarity matrix is not square
+	/// * panics when k is 0 or larger than N
+	pub fn new<M: ArrayAdapter<N>>(mat: &M, med: Vec<usize>, n_fixed_meds: usize) -> Self {
+		let (loss, cache) = if med.len() == 2 {
+			let (loss, assi, data) = initial_assignment_k2(mat, &med);
+			(loss, MSCCache::K2 { assi, data })
+		} else {
+			let (loss, data) = initial_assignment(mat, &med);
+			debug_assert_assignment_th(mat, &med, &data);
+			let mut removal_loss = vec![L::zero(); med.len()];
+			update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
+			(loss, MSCCache::General { data, removal_loss })
+		};
+		FasterMSCState {
+			med,
+			cache,
+			loss,
+			n_fixed_meds,
+			lastswap: mat.len(),
+			n_iter: 0,
+			n_swap: 0,
+			converged: false,
+		}
+	}
+
+	/// Perform one iteration of FasterMSC.
+	///
+	/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+	/// * `mat` - the same pairwise distance matrix that this state was initialized with
+	///
+	/// returns whether the solution was improved, or the optimization has converged
+	pub fn step<M: ArrayAdapter<N>>(&mut self, mat: &M) -> StepOutcome {
+		if self.converged {
+			return StepOutcome::Converged;
+		}
+		let n = mat.len();
+		self.n_iter += 1;
+		if self.med.len() == 1 {
+			let assi = vec![0; n];
+			let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, &mut self.med, 0);
+			self.loss = loss;
+			self.n_swap += if swapped { 1 } else { 0 };
+			self.converged = true;
+			return StepOutcome::Converged;
+		}
+		let (swaps_before, lastloss) = (self.n_swap, self.loss);
+		match &mut self.cache {
+			MSCCache::General { data, removal_loss } => {
+				assert_eq!(n, data.len(), "matrix does not match the state");
+				for j in 0..n {
+					if j == self.lastswap {
+						break;
+					}
+					if j == self.med[data[j].near.i as usize] {
+						continue; // This already is a medoid
+					}
+					let (change, b) = find_best_swap(mat, removal_loss, data, j);
+					if change <= L::zero() {
+						continue; // No improvement
+					}
+					self.n_swap += 1;
+					self.lastswap = j;
+					// perform the swap
+					self.loss = do_swap(mat, &mut self.med, data, b, j);
+					debug_assert_assignment_th(mat, &self.med, data);
+					update_removal_loss(data, removal_loss, self.n_fixed_meds);
+				}
+			}
+			MSCCache::K2 { assi, data } => {
+				assert_eq!(n, data.len(), "matrix does not match the state");
+				for j in 0..n {
+					if j == self.lastswap {
+						break;
+					}
+					if j == self.med[assi[j]] {
+						continue; // This already is a medoid
+					}
+					let (newloss, b): (L, _) = find_best_swap_k2(mat, data, j);
+					if newloss >= self.loss {
+						continue; // No improvement
+					}
+					self.n_swap += 1;
+					self.lastswap = j;
+					// perform the swap
+					self.loss = do_swap_k2(mat, &mut self.med, assi, data, b, j);
+				}
+			}
+		}
+		if self.n_swap == swaps_before || self.loss >= lastloss {
+			self.converged = true; // converged
+			return StepOutcome::Converged;
+		}
+		StepOutcome::Improved
+	}
+
+	/// The current loss (medoid silhouette)
+	pub fn loss(&self) -> L {
+		if self.med.len() == 1 {
+			return L::one(); // as in fastermsc and medoid_silhouette
+		}
+		let n = match &self.cache {
+			MSCCache::General { data, .. } => data.len(),
+			MSCCache::K2 { data, .. } => data.len(),
+		};
+		L::one() - self.loss / <L as From<u32>>::from(n as u32)
+	}
+
+	/// The current medoids
+	pub fn medoids(&self) -> &[usize] {
+		&self.med
+	}
+
+	/// The current cluster assignment
+	pub fn assignment(&self) -> Vec<usize> {
+		match &self.cache {
+			MSCCache::General { data, .. } if self.med.len() == 1 => vec![0; data.len()],
+			MSCCache::General { data, .. } => data.iter().map(|x| x.near.i as usize).collect(),
+			MSCCache::K2 { assi, .. } => assi.clone(),
+		}
+	}
+
+	/// The number of iterations performed
+	pub fn n_iter(&self) -> usize {
+		self.n_iter
+	}
+
+	/// The number of swaps performed
+	pub fn n_swap(&self) -> usize {
+		self.n_swap
+	}
+
+	/// Whether the optimization has converged
+	pub fn is_converged(&self) -> bool {
+		self.converged
+	}
 }
 
 /// Perform the initial assignment to medoids
@@ -107,6 +528,17 @@ pub(crate) fn initial_assignment<M, N, L>(mat: &M, med: &[usize]) -> (L, Vec<Rec
 		N: Zero + PartialOrd + Copy,
 		L: Float + AddAssign + From<N> + std::fmt::Display,
 		M: ArrayAdapter<N>,
+{
+	initial_assignment_with_tie_breaking(mat, med, &mut BuildTieBreaking::FirstIndex)
+}
+
+/// Perform the initial assignment to medoids, with a choice among equally near medoids
+#[inline]
+pub(crate) fn initial_assignment_with_tie_breaking<M, N, L>(mat: &M, med: &[usize], ties: &mut BuildTieBreaking) -> (L, Vec<Reco<N>>)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + AddAssign + From<N> + std::fmt::Display,
+		M: ArrayAdapter<N>,
 {
 	let (n, k) = (mat.len(), med.len());
 	assert!(mat.is_square(), "Dissimilarity matrix is not square");
@@ -119,21 +551,23 @@ pub(crate) fn initial_assignment<M, N, L>(mat: &M, med: &[usize]) -> (L, Vec<Rec
 		.iter_mut()
 		.enumerate()
 		.map(|(i, cur)| {
-			*cur = Reco::new(0, mat.get(i, firstcenter), u32::MAX, N::zero(), u32::MAX, N::zero());
+			*cur = Reco { near: DistancePair::new(0, mat.get(i, firstcenter)), seco: DistancePair::sentinel(), third: DistancePair::sentinel() };
+			let mut count = 1; // number of medoids tied for nearest
 			for (m, &me) in med.iter().enumerate().skip(1) {
 				let d = mat.get(i, me);
-				if d < cur.near.d || i == me {
+				// a medoid is always nearest to itself
+				if i == me || (i != med[cur.near.i as usize] && ties.replaces(d, cur.near.d, &mut count)) {
 					cur.third = cur.seco;
 					cur.seco = cur.near;
 					cur.near = DistancePair { i: m as u32, d };
-				} else if cur.seco.i == u32::MAX || d < cur.seco.d {
+				} else if cur.seco.is_sentinel() || d < cur.seco.d {
 					cur.third = cur.seco;
 					cur.seco = DistancePair { i: m as u32, d };
-				} else if cur.third.i == u32::MAX || d < cur.third.d {
+				} else if cur.third.is_sentinel() || d < cur.third.d {
 					cur.third = DistancePair { i: m as u32, d };
 				}
 			}
-			_loss::<N, L>(cur.near.d, cur.seco.d)
+			safe_ratio::<N, L>(cur.near.d, cur.seco.d)
 		})
 		.reduce(L::add)
 		.unwrap();
@@ -159,21 +593,21 @@ pub(crate) fn find_best_swap<M, N, L>(
 	for (o, reco) in data.iter().enumerate() {
 		let doj = mat.get(o, j);
 		if doj < reco.near.d {
-			acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(doj, reco.near.d);
+			acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(doj, reco.near.d);
 			// loss already includes (dt - ds) - (ds - dn), remove
-			ploss[reco.near.i as usize] += _loss::<N, L>(doj, reco.near.d) + _loss::<N, L>(reco.seco.d, reco.third.d) - _loss::<N, L>(reco.near.d + doj, reco.seco.d);
-			ploss[reco.seco.i as usize] += _loss::<N, L>(reco.near.d, reco.third.d) - _loss::<N, L>(reco.near.d, reco.seco.d);
+			ploss[reco.near.i as usize] += safe_ratio::<N, L>(doj, reco.near.d) + safe_ratio::<N, L>(reco.seco.d, reco.third.d) - safe_ratio::<N, L>(reco.near.d + doj, reco.seco.d);
+			ploss[reco.seco.i as usize] += safe_ratio::<N, L>(reco.near.d, reco.third.d) - safe_ratio::<N, L>(reco.near.d, reco.seco.d);
 		} else if doj < reco.seco.d {
-			acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(reco.near.d, doj);
-			ploss[reco.near.i as usize] += _loss::<N, L>(reco.near.d, doj) + _loss::<N, L>(reco.seco.d, reco.third.d) - _loss::<N, L>(reco.near.d + doj, reco.seco.d);
+			acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(reco.near.d, doj);
+			ploss[reco.near.i as usize] += safe_ratio::<N, L>(reco.near.d, doj) + safe_ratio::<N, L>(reco.seco.d, reco.third.d) - safe_ratio::<N, L>(reco.near.d + doj, reco.seco.d);
 			// loss already includes (dt - ds) - (ds - dn), adjust to 2*d(xo) - ds - dt
 			// loss already includes (dt - ds), adjust to 2*d(xo) - ds - dt
-			ploss[reco.seco.i as usize] += _loss::<N, L>(reco.near.d, reco.third.d) - _loss::<N, L>(reco.near.d, reco.seco.d);
+			ploss[reco.seco.i as usize] += safe_ratio::<N, L>(reco.near.d, reco.third.d) - safe_ratio::<N, L>(reco.near.d, reco.seco.d);
 		} else if doj < reco.third.d {
 			// loss already includes (dt - ds) - (ds - dn), adjust to d(xo)- dt
-			ploss[reco.near.i as usize] += _loss::<N, L>(reco.seco.d, reco.third.d) - _loss::<N, L>(reco.seco.d, doj);
+			ploss[reco.near.i as usize] += safe_ratio::<N, L>(reco.seco.d, reco.third.d) - safe_ratio::<N, L>(reco.seco.d, doj);
 			// loss already includes (dt - ds), adjust to d(xo)- dt
-			ploss[reco.seco.i as usize] += _loss::<N, L>(reco.near.d, reco.third.d) - _loss::<N, L>(reco.near.d, doj);
+			ploss[reco.seco.i as usize] += safe_ratio::<N, L>(reco.near.d, reco.third.d) - safe_ratio::<N, L>(reco.near.d, doj);
 		}
 	}
 	let (b, bloss) = find_max(&mut ploss.iter());
@@ -188,8 +622,8 @@ pub(crate) fn update_removal_loss<N, L>(data: &[Reco<N>], loss: &mut Vec<L>, n_f
 {
 	loss.fill(L::zero()); // stable since 1.50
 	for rec in data.iter() {
-		loss[rec.near.i as usize] += _loss::<N, L>(rec.near.d, rec.seco.d) - _loss::<N, L>(rec.seco.d, rec.third.d);
-		loss[rec.seco.i as usize] += _loss::<N, L>(rec.near.d, rec.seco.d) - _loss::<N, L>(rec.near.d, rec.third.d);
+		loss[rec.near.i as usize] += safe_ratio::<N, L>(rec.near.d, rec.seco.d) - safe_ratio::<N, L>(rec.seco.d, rec.third.d);
+		loss[rec.seco.i as usize] += safe_ratio::<N, L>(rec.near.d, rec.seco.d) - safe_ratio::<N, L>(rec.near.d, rec.third.d);
 		// as N might be unsigned
 	}
 
@@ -215,7 +649,7 @@ pub(crate) fn update_third_nearest<M, N>(
 	doj: N,
 ) -> DistancePair<N>
 	where
-		N: PartialOrd + Copy,
+		N: Zero + PartialOrd + Copy,
 		M: ArrayAdapter<N>,
 {
 	let mut dist = DistancePair::new(b as u32, doj);
@@ -267,7 +701,7 @@ pub(crate) fn do_swap<M, N, L>(
 			if reco.near.i == b as u32 {
 				if doj < reco.seco.d {
 					reco.near = DistancePair::new(b as u32, doj);
-				} else if reco.third.i == u32::MAX || doj < reco.third.d {
+				} else if reco.third.is_sentinel() || doj < reco.third.d {
 					reco.near = reco.seco;
 					reco.seco = DistancePair::new(b as u32, doj);
 				} else {
@@ -280,7 +714,7 @@ pub(crate) fn do_swap<M, N, L>(
 				if doj < reco.near.d {
 					reco.seco = reco.near;
 					reco.near = DistancePair::new(b as u32, doj);
-				} else if reco.third.i == u32::MAX || doj < reco.third.d {
+				} else if reco.third.is_sentinel() || doj < reco.third.d {
 					reco.seco = DistancePair::new(b as u32, doj);
 				} else {
 					reco.seco = reco.third;
@@ -295,25 +729,106 @@ pub(crate) fn do_swap<M, N, L>(
 				} else if doj < reco.seco.d {
 					reco.third = reco.seco;
 					reco.seco = DistancePair::new(b as u32, doj);
-				} else if reco.third.i == u32::MAX || doj < reco.third.d {
+				} else if reco.third.is_sentinel() || doj < reco.third.d {
 					reco.third = DistancePair::new(b as u32, doj);
 				} else if reco.third.i == b as u32 {
 					reco.third = update_third_nearest(mat, med, reco.near.i as usize, reco.seco.i as usize, b, o, doj);
 				}
 			}
-			_loss::<N, L>(reco.near.d, reco.seco.d)
+			safe_ratio::<N, L>(reco.near.d, reco.seco.d)
 		})
 		.reduce(L::add)
 		.unwrap()
 }
 
+/// Assign all points to their three nearest medoids, for use with [`apply_swap`].
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+///
+/// returns a tuple containing:
+/// * the initial loss, the sum of the distance ratios; the Medoid Silhouette is 1 - loss / N
+/// * the nearest, second and third nearest medoid of every point
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is less than 2 or larger than N
+///
+/// ## Example
+/// See [`apply_swap`].
+pub fn initial_reco<M, N, L>(mat: &M, med: &[usize]) -> (L, Vec<Reco<N>>)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + AddAssign + From<N> + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(med.len() >= 2 && med.len() <= mat.len(), "invalid number of medoids");
+	initial_assignment(mat, med)
+}
+
+/// Replace one medoid, and update the nearest medoids of all points.
+///
+/// This is the update step of [`fastermsc`] and PAMMEDSIL, for implementing other
+/// optimization loops, e.g., simulated annealing, on top of it. Only the points whose
+/// nearest medoids change are updated, most in O(1) time; a point that loses its third
+/// nearest medoid is recomputed in O(k) time.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids, updated in place
+/// * `data` - the nearest medoids of every point, from [`initial_reco`] or a previous swap
+/// * `medoid_idx` - the position of the medoid to replace in `med`
+/// * `new_point` - the point to become a medoid; must not be a medoid already
+///
+/// returns the new loss, the sum of the distance ratios; the Medoid Silhouette is 1 - loss / N
+///
+/// ## Panics
+///
+/// * panics when `medoid_idx` is not less than k, or `new_point` not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 5 x 5, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3,4],[1,0,2,3,4],[2,2,0,1,4],[3,3,1,0,4],[4,4,4,4,0]]);
+/// let mut meds = vec![0, 1, 2];
+/// let (loss, mut reco): (f64, _) = kmedoids::initial_reco(&data, &meds);
+/// let newloss: f64 = kmedoids::apply_swap(&data, &mut meds, &mut reco, 1, 4);
+/// assert_eq!(meds, vec![0, 4, 2]);
+/// assert!(newloss < loss);
+/// ```
+pub fn apply_swap<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	data: &mut Vec<Reco<N>>,
+	medoid_idx: usize,
+	new_point: usize,
+) -> L
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	do_swap(mat, med, data, medoid_idx, new_point)
+}
+
 /// Special case k=2 of the FasterMSC algorithm.
+///
+/// additionally returns whether the run was cancelled, and the total deviation
 #[inline]
 pub(crate) fn fastermsc_k2<M, N, L>(
 	mat: &M,
 	med: &mut Vec<usize>,
 	maxiter: usize,
-) -> (L, Vec<usize>, usize, usize)
+	cancel: Option<&AtomicBool>,
+	mut order: ScanOrder,
+) -> (L, Vec<usize>, usize, usize, bool, L)
 	where
 		N: Zero + PartialOrd + Copy,
 		L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
@@ -321,33 +836,44 @@ pub(crate) fn fastermsc_k2<M, N, L>(
 {
 	let (n, k) = (mat.len(), med.len());
 	assert!(k == 2, "Only valid for k=2");
-	let (mut loss, mut assi, mut data): (L,_,_) = initial_assignment_k2(mat, med);
-	let (mut lastswap, mut n_swaps, mut iter) = (n, 0, 0);
-	while iter < maxiter {
+	let (mut loss, mut assi, mut data): (L,_,_) = timed(Phase::InitialAssignment, || initial_assignment_k2(mat, med));
+	let mut seq = order.initial(n);
+	let (mut lastswap, mut n_swaps, mut iter, mut cancelled) = (n, 0, 0, false);
+	while iter < maxiter && !cancelled {
 		iter += 1;
+		let _iteration = Stopwatch::start(Phase::Iteration);
+		if order.next_iteration(&mut seq) {
+			lastswap = n; // scan all candidates in the new order
+		}
 		let (swaps_before, lastloss) = (n_swaps, loss);
-		for j in 0..n {
+		for &j in seq.iter() {
 			if j == lastswap {
 				break;
 			}
+			if is_cancelled(cancel) {
+				cancelled = true;
+				break;
+			}
 			if j == med[assi[j] as usize] {
 				continue; // This already is a medoid
 			}
-			let (newloss, b): (L, _) = find_best_swap_k2(mat, &data, j); // assi not used, see below
+			let (newloss, b): (L, _) = timed(Phase::FindBestSwap, || find_best_swap_k2(mat, &data, j)); // assi not used, see below
 			if newloss >= loss {
 				continue; // No improvement
 			}
 			n_swaps += 1;
 			lastswap = j;
 			// perform the swap
-			loss = do_swap_k2(mat, med, &mut assi, &mut data, b, j);
+			loss = timed(Phase::DoSwap, || do_swap_k2(mat, med, &mut assi, &mut data, b, j));
 		}
 		if n_swaps == swaps_before || loss >= lastloss {
 			break; // converged
 		}
 	}
+	let td = assi.iter().zip(data.iter())
+		.fold(L::zero(), |acc, (&a, d)| acc + <L as From<N>>::from(if a == 0 { d.0 } else { d.1 }));
 	loss = L::one() - loss / <L as From<u32>>::from(n as u32);
-	(loss, assi, iter, n_swaps)
+	(loss, assi, iter, n_swaps, cancelled, td)
 }
 /// Perform the initial assignment to medoids, for k=2 only
 #[inline]
@@ -369,10 +895,10 @@ pub(crate) fn initial_assignment_k2<M, N, L>(mat: &M, med: &[usize]) -> (L, Vec<
 			*d = (mat.get(i, med[0]), mat.get(i, med[1]));
 			if d.0 < d.1 {
 				*a = 0;
-				return _loss::<N, L>(d.0, d.1);
+				return safe_ratio::<N, L>(d.0, d.1);
 			} else {
 				*a = 1;
-				return _loss::<N, L>(d.1, d.0);
+				return safe_ratio::<N, L>(d.1, d.0);
 			}
 		})
 		.reduce(L::add)
@@ -396,8 +922,8 @@ pub(crate) fn find_best_swap_k2<M, N, L>(
 	for (o, d) in data.iter().enumerate() {
 		let doj = mat.get(o, j);
 		// We do not use the assignment here, because we stored d0/d1 by medoid position, not closeness
-		ploss[0] += if doj < d.1 { _loss(doj, d.1) } else { _loss(d.1, doj) };
-		ploss[1] += if doj < d.0 { _loss(doj, d.0) } else { _loss(d.0, doj) };
+		ploss[0] += if doj < d.1 { safe_ratio(doj, d.1) } else { safe_ratio(d.1, doj) };
+		ploss[1] += if doj < d.0 { safe_ratio(doj, d.0) } else { safe_ratio(d.0, doj) };
 	}
 	let (b, bloss) = find_min(&mut ploss.iter());
 	(bloss, b)
@@ -436,10 +962,10 @@ pub(crate) fn do_swap_k2<M, N, L>(
 				d.0 = doj;
 				if doj < d.1 || (doj == d.1 && *a == 0) {
 					*a = 0;
-					return _loss::<N, L>(doj, d.1);
+					return safe_ratio::<N, L>(doj, d.1);
 				} else {
 					*a = 1;
-					return _loss::<N, L>(d.1, doj);
+					return safe_ratio::<N, L>(d.1, doj);
 				}
 			})
 			.reduce(L::add)
@@ -457,10 +983,10 @@ pub(crate) fn do_swap_k2<M, N, L>(
 				d.1 = doj;
 				if doj < d.0 || (doj == d.0 && *a == 1) {
 					*a = 1;
-					return _loss::<N, L>(doj, d.0);
+					return safe_ratio::<N, L>(doj, d.0);
 				} else {
 					*a = 0;
-					return _loss::<N, L>(d.0, doj);
+					return safe_ratio::<N, L>(d.0, doj);
 				}
 			})
 			.reduce(L::add)
@@ -471,7 +997,8 @@ pub(crate) fn do_swap_k2<M, N, L>(
 #[cfg(test)]
 mod tests {
 	// TODO: use a larger, much more interesting example.
-	use crate::{arrayadapter::LowerTriangle, fastermsc, silhouette, medoid_silhouette, util::assert_array};
+	use crate::{canonicalize, arrayadapter::LowerTriangle, assign_to_medoids, fastermsc, fastermsc_cancellable, fastermsc_dual, fastermsc_with_order, silhouette, medoid_silhouette, util::assert_array};
+	use std::sync::atomic::AtomicBool;
 
 	#[test]
 	fn testfastermsc_simple() {
@@ -480,7 +1007,8 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![0, 1, 2];
-		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = fastermsc(&data, &mut meds, 10);
+		let (loss, mut assi, n_iter, n_swap): (f64, _, _, _) = fastermsc(&data, &mut meds, 0, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
 		print!("FasterMSC: {:?} {:?} {:?} {:?} {:?} {:?}", loss, n_iter, n_swap, sil, assi, meds);
@@ -488,8 +1016,8 @@ mod tests {
 		assert_eq!(msil, 0.9047619047619048, "Medoid Silhouette not as expected");
 		assert_eq!(n_swap, 1, "swaps not as expected");
 		assert_eq!(n_iter, 2, "iterations not as expected");
-		assert_array(assi, vec![0, 0, 2, 1, 1], "assignment not as expected");
-		assert_array(meds, vec![0, 3, 2], "medoids not as expected");
+		assert_array(assi, vec![0, 0, 1, 2, 2], "assignment not as expected");
+		assert_array(meds, vec![0, 2, 3], "medoids not as expected");
 		assert_eq!(sil, 0.5622222222222222, "Silhouette not as expected");
 	}
 
@@ -500,7 +1028,8 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![0, 1];
-		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = fastermsc(&data, &mut meds, 10);
+		let (loss, mut assi, n_iter, n_swap): (f64, _, _, _) = fastermsc(&data, &mut meds, 0, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
 		print!("FasterMSC: {:?} {:?} {:?} {:?} {:?} {:?}", loss, n_iter, n_swap, sil, assi, meds);
@@ -512,4 +1041,156 @@ mod tests {
 		assert_array(meds, vec![0, 4], "medoids not as expected");
 		assert_eq!(sil, 0.7522494172494172, "Silhouette not as expected");
 	}
+
+	#[test]
+	fn testfastermsc_dual() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for k in 2..4 {
+			let mut meds = (0..k).collect::<Vec<_>>();
+			let (loss, assi, _, _, td): (f64, _, _, _, _) = fastermsc_dual(&data, &mut meds, 0, 10);
+			let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
+			let (expected_td, expected_assi): (f64, _) = assign_to_medoids(&data, &meds);
+			assert_eq!(loss, msil, "loss not as expected");
+			assert_eq!(td, expected_td, "total deviation not as expected");
+			assert_array(assi, expected_assi, "assignment not as expected");
+		}
+	}
+
+	#[test]
+	fn testfastermsc_cancelled() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let cancel = AtomicBool::new(true);
+		for k in 2..=3 {
+			// Already cancelled, so we must get the initial solution back
+			let mut meds = (0..k).collect::<Vec<_>>();
+			let (loss, assi, _, n_swap, cancelled): (f64, _, _, _, _) = fastermsc_cancellable(&data, &mut meds, 0, 10, &cancel);
+			let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
+			assert!(cancelled, "run not cancelled");
+			assert_eq!(n_swap, 0, "swaps not as expected");
+			assert_eq!(loss, msil, "loss not as expected");
+			assert_array(meds, (0..k).collect(), "medoids not as expected");
+			assert_eq!(assi.len(), 5, "assignment not as expected");
+		}
+	}
+
+	#[cfg(feature = "rand")]
+	#[test]
+	fn testfastermsc_state() {
+		use crate::{FasterMSCState, StepOutcome};
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let n = 200;
+		let mut rng = StdRng::seed_from_u64(1);
+		let data = LowerTriangle {
+			n,
+			data: (0..n * (n - 1) / 2).map(|_| rng.gen_range(1..1000)).collect::<Vec<u32>>(),
+		};
+		for k in [2, 5] {
+			let init = crate::random_initialization(n, k, &mut rng);
+			let mut meds = init.clone();
+			let (loss, assi, n_iter, n_swap): (f64, _, _, _) = fastermsc(&data, &mut meds, 0, 100);
+			let mut state = FasterMSCState::<_, f64>::new(&data, init.clone(), 0);
+			while state.step(&data) == StepOutcome::Improved {}
+			assert_eq!(state.loss(), loss, "loss not as expected");
+			assert_eq!(state.n_swap(), n_swap, "swaps not as expected");
+			assert_eq!(state.n_iter(), n_iter, "iterations not as expected");
+			assert_array(state.assignment(), assi.clone(), "assignment not as expected");
+			assert_array(state.medoids().to_vec(), meds.clone(), "medoids not as expected");
+
+			#[cfg(feature = "serde")]
+			{
+				// Suspend after one iteration, and resume from the serialized state
+				let mut state = FasterMSCState::<_, f64>::new(&data, init, 0);
+				assert_eq!(state.step(&data), StepOutcome::Improved, "first iteration did not improve");
+				let json = serde_json::to_string(&state).unwrap();
+				let mut state: FasterMSCState<u32, f64> = serde_json::from_str(&json).unwrap();
+				while state.step(&data) == StepOutcome::Improved {}
+				assert_eq!(state.loss(), loss, "loss not as expected");
+				assert_eq!(state.n_iter(), n_iter, "iterations not as expected");
+				assert_array(state.assignment(), assi, "assignment not as expected");
+				assert_array(state.medoids().to_vec(), meds, "medoids not as expected");
+			}
+		}
+	}
+
+	#[test]
+	fn testfastermsc_maxiter0() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for init in [vec![1], vec![1, 2], vec![1, 2, 4]] {
+			let mut meds = init.clone();
+			let (loss, assi, n_iter, n_swap): (f64, _, _, _) = fastermsc(&data, &mut meds, 0, 0);
+			let (msil, _): (f64, _) = medoid_silhouette(&data, &init, false);
+			let (_, expected_assi): (f64, _) = assign_to_medoids(&data, &init);
+			assert_eq!(loss, msil, "loss not as expected");
+			assert_eq!(n_swap, 0, "swaps not as expected");
+			assert_eq!(n_iter, 0, "iterations not as expected");
+			assert_array(assi, expected_assi, "assignment not as expected");
+			assert_array(meds, init, "medoids not as expected");
+		}
+		// with k = 1, the loss is the medoid silhouette, with and without SWAP
+		let (loss0, _, _, _): (f64, _, _, _) = fastermsc(&data, &mut vec![1], 0, 0);
+		let (loss1, _, _, _): (f64, _, _, _) = fastermsc(&data, &mut vec![1], 0, 1);
+		assert_eq!((loss0, loss1), (1., 1.), "loss not as expected");
+	}
+
+	#[cfg(feature = "rand")]
+	#[test]
+	fn testrand_fastermsc() {
+		use crate::{arrayadapter::ArrayAdapter, pammedsil_swap, rand_fastermsc};
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		// Blob data, with some ties in the integer distances
+		let mut rng = StdRng::seed_from_u64(1);
+		let points = (0..150)
+			.map(|i| (i % 5 * 30 + rng.gen_range(0..20), i % 3 * 30 + rng.gen_range(0..20)))
+			.collect::<Vec<(i32, i32)>>();
+		let n = points.len();
+		let mut dist = Vec::with_capacity(n * (n - 1) / 2);
+		for i in 1..n {
+			for j in 0..i {
+				dist.push(((points[i].0 - points[j].0).abs() + (points[i].1 - points[j].1).abs()) as u32);
+			}
+		}
+		let data = LowerTriangle { n, data: dist };
+		let (mut sum_fixed, mut sum_rand) = (0., 0.);
+		for seed in 0..10 {
+			let init = crate::random_initialization(n, 15, &mut rng);
+			let mut meds = init.clone();
+			let (loss, _, _, _): (f64, _, _, _) = fastermsc(&data, &mut meds, 0, 100);
+			sum_fixed += loss;
+			let mut meds = init.clone();
+			let (loss, assi, n_iter, _): (f64, _, _, _) = rand_fastermsc(&data, &mut meds, 0, 100, &mut StdRng::seed_from_u64(seed));
+			sum_rand += loss;
+			assert!(n_iter < 100, "not converged");
+			assert!(assi.iter().enumerate().all(|(i, &a)| meds.iter().all(|&m| data.get(i, meds[a]) <= data.get(i, m))), "not assigned to nearest");
+			// converged to a local optimum
+			let mut meds2 = meds.clone();
+			let (_, _, _, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds2, 100);
+			assert_eq!(n_swap, 0, "not a local optimum");
+			// deterministic given the seed
+			let mut meds2 = init.clone();
+			let (loss2, assi2, n_iter2, _): (f64, _, _, _) = rand_fastermsc(&data, &mut meds2, 0, 100, &mut StdRng::seed_from_u64(seed));
+			assert_eq!((loss, n_iter), (loss2, n_iter2), "not deterministic");
+			assert_array(assi, assi2, "assignment not deterministic");
+			assert_array(meds, meds2, "medoids not deterministic");
+		}
+		assert!(sum_rand >= sum_fixed - 0.1, "randomized worse than sequential: {} < {}", sum_rand / 10., sum_fixed / 10.);
+
+		// the sequential order is the default
+		let init = crate::random_initialization(n, 5, &mut rng);
+		let (mut meds, mut meds2) = (init.clone(), init);
+		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = fastermsc(&data, &mut meds, 0, 100);
+		let order = (0..n).collect::<Vec<_>>();
+		let (loss2, assi2, n_iter2, n_swap2): (f64, _, _, _) = fastermsc_with_order(&data, &mut meds2, 0, 100, &order);
+		assert_eq!((loss, n_iter, n_swap), (loss2, n_iter2, n_swap2), "result not as expected");
+		assert_array(assi, assi2, "assignment not as expected");
+		assert_array(meds, meds2, "medoids not as expected");
+	}
 }
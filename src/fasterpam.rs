@@ -1,8 +1,10 @@
 use crate::arrayadapter::ArrayAdapter;
+use crate::profiling::{self, timed, Phase, Stopwatch};
 use crate::util::*;
 use core::ops::AddAssign;
-use num_traits::{Signed, Zero, FromPrimitive};
+use num_traits::{Float, Signed, Zero, FromPrimitive};
 use std::convert::From;
+use std::sync::atomic::AtomicBool;
 
 
 /// Run the FasterPAM algorithm.
@@ -16,7 +18,7 @@ use std::convert::From;
 /// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
 /// * `mat` - a pairwise distance matrix
 /// * `med` - the list of medoids
-/// * `maxiter` - the maximum number of iterations allowed
+/// * `maxiter` - the maximum number of iterations allowed; with 0, the given medoids are only evaluated
 ///
 /// returns a tuple containing:
 /// * the final loss
@@ -34,7 +36,7 @@ use std::convert::From;
 /// ```
 /// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
 /// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
-/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 100);
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 100);
 /// println!("Loss is: {}", loss);
 /// ```
 pub fn fasterpam<M, N, L>(
@@ -48,28 +50,171 @@ where
 	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
 	M: ArrayAdapter<N>,
 {
+	let (loss, data, iter, n_swaps, _) = fasterpam_optimize(mat, med, n_fixed_meds, maxiter, None);
+	let assi = if data.is_empty() { vec![0; mat.len()] } else { data.iter().map(|x| x.near.i as usize).collect() };
+	(loss, assi, iter, n_swaps)
+}
+
+/// Run the FasterPAM algorithm, with cancellation.
+///
+/// The flag is checked (with relaxed ordering) before each candidate is evaluated.
+/// Because FasterPAM performs swaps eagerly, the current solution is always
+/// consistent, and is returned when the flag is set.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `n_fixed_meds` - the number of leading medoids that must not be swapped
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `cancel` - cancellation flag, may be set from another thread
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+/// * whether the run was cancelled
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let cancel = std::sync::atomic::AtomicBool::new(false);
+/// let (loss, assi, n_iter, n_swap, cancelled): (f64, _, _, _, _) = kmedoids::fasterpam_cancellable(&data, &mut meds, 0, 100, &cancel);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn fasterpam_cancellable<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	n_fixed_meds: usize,
+	maxiter: usize,
+	cancel: &AtomicBool,
+) -> (L, Vec<usize>, usize, usize, bool)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let (loss, data, iter, n_swaps, cancelled) = fasterpam_optimize(mat, med, n_fixed_meds, maxiter, Some(cancel));
+	let assi = if data.is_empty() { vec![0; mat.len()] } else { data.iter().map(|x| x.near.i as usize).collect() };
+	(loss, assi, iter, n_swaps, cancelled)
+}
+
+/// Run the FasterPAM algorithm, and also report the medoid silhouette.
+///
+/// The medoid silhouette of the final solution is computed from the cached nearest
+/// and second nearest medoid distances in O(n), and is identical to calling
+/// [`medoid_silhouette`](crate::medoid_silhouette) afterwards.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * type `S` - number data type such as `f64` for the medoid silhouette (use a float type)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `n_fixed_meds` - the number of leading medoids that must not be swapped
+/// * `maxiter` - the maximum number of iterations allowed
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+/// * the medoid silhouette of the final solution
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter, n_swap, msil): (i64, _, _, _, f64) = kmedoids::fasterpam_dual(&data, &mut meds, 0, 100);
+/// println!("Loss is: {}, medoid silhouette is: {}", loss, msil);
+/// ```
+pub fn fasterpam_dual<M, N, L, S>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	n_fixed_meds: usize,
+	maxiter: usize,
+) -> (L, Vec<usize>, usize, usize, S)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	S: Float + AddAssign + From<N> + From<u32>,
+	M: ArrayAdapter<N>,
+{
+	let (loss, data, iter, n_swaps, _) = fasterpam_optimize(mat, med, n_fixed_meds, maxiter, None);
+	if data.is_empty() {
+		return (loss, vec![0; mat.len()], iter, n_swaps, S::one()); // not really well-defined
+	}
+	let mut sum = S::zero();
+	for rec in data.iter().filter(|rec| !N::is_zero(&rec.near.d)) {
+		sum += <S as From<N>>::from(rec.near.d) / <S as From<N>>::from(rec.seco.d);
+	}
+	let msil = S::one() - sum / <S as From<u32>>::from(data.len() as u32);
+	let assi = data.iter().map(|x| x.near.i as usize).collect();
+	(loss, assi, iter, n_swaps, msil)
+}
+
+/// Main optimization function of FasterPAM, not exposed (use fasterpam)
+///
+/// returns the loss, the nearest medoid cache (empty for k = 1), the number of
+/// iterations and the number of swaps, and whether the run was cancelled
+fn fasterpam_optimize<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	n_fixed_meds: usize,
+	maxiter: usize,
+	cancel: Option<&AtomicBool>,
+) -> (L, Vec<Rec<N>>, usize, usize, bool)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	profiling::reset();
 	let (n, k) = (mat.len(), med.len());
 	if k == 1 {
 		let assi = vec![0; n];
+		if maxiter == 0 {
+			return (single_medoid_loss(mat, med[0]), vec![], 0, 0, false); // only evaluate the given medoid
+		}
 		let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, med, 0);
-		return (loss, assi, 1, if swapped { 1 } else { 0 });
+		return (loss, vec![], 1, if swapped { 1 } else { 0 }, false);
 	}
-	let (mut loss, mut data) = initial_assignment(mat, med);
+	let (mut loss, mut data) = timed(Phase::InitialAssignment, || initial_assignment(mat, med));
 	debug_assert_assignment(mat, med, &data);
 	let mut removal_loss = vec![L::zero(); k];
 	update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
-	let (mut lastswap, mut n_swaps, mut iter) = (n, 0, 0);
-	while iter < maxiter {
+	let (mut lastswap, mut n_swaps, mut iter, mut cancelled) = (n, 0, 0, false);
+	while iter < maxiter && !cancelled {
 		iter += 1;
+		let _iteration = Stopwatch::start(Phase::Iteration);
 		let (swaps_before, lastloss) = (n_swaps, loss);
 		for j in n_fixed_meds..n {
 			if j == lastswap {
 				break;
 			}
+			if is_cancelled(cancel) {
+				cancelled = true;
+				break;
+			}
 			if j == med[data[j].near.i as usize] {
 				continue; // This already is a medoid
 			}
-			let (change, b) = find_best_swap(mat, &removal_loss, &data, j);
+			let (change, b) = timed(Phase::FindBestSwap, || find_best_swap(mat, &removal_loss, &data, j));
 			assert!(b >= n_fixed_meds, "Selected medoid is < n_fixed_meds");
 			if change >= L::zero() {
 				continue; // No improvement
@@ -77,15 +222,17 @@ where
 			n_swaps += 1;
 			lastswap = j;
 			// perform the swap
-			loss = do_swap(mat, med, &mut data, b, j);
+			loss = timed(Phase::DoSwap, || do_swap(mat, med, &mut data, b, j));
 			update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
 		}
+		if cancelled {
+			break; // keep the current, consistent, solution
+		}
 		if n_swaps == swaps_before || loss >= lastloss {
 			break; // converged
 		}
 	}
-	let assi = data.iter().map(|x| x.near.i as usize).collect();
-	(loss, assi, iter, n_swaps)
+	(loss, data, iter, n_swaps, cancelled)
 }
 
 /// Run the FasterPAM algorithm with additional randomization.
@@ -117,7 +264,7 @@ where
 /// ```
 /// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
 /// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
-/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::rand_fasterpam(&data, &mut meds, 100, &mut rand::thread_rng());
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::rand_fasterpam(&data, &mut meds, 0, 100, &mut rand::thread_rng());
 /// println!("Loss is: {}", loss);
 /// ```
 #[cfg(feature = "rand")]
@@ -133,45 +280,283 @@ where
 	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
 	M: ArrayAdapter<N>,
 {
+	let (loss, assi, iter, n_swaps, _) = rand_fasterpam_optimize(mat, med, n_fixed_meds, maxiter, rng, None);
+	(loss, assi, iter, n_swaps)
+}
+
+/// Run the FasterPAM algorithm with additional randomization, with cancellation.
+///
+/// The flag is checked (with relaxed ordering) before each candidate is evaluated,
+/// and the current solution is returned when it is set, as in [`fasterpam_cancellable`].
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `n_fixed_meds` - the number of leading medoids that must not be swapped
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `rng` - random number generator for shuffling the input data
+/// * `cancel` - cancellation flag, may be set from another thread
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+/// * whether the run was cancelled
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let cancel = std::sync::atomic::AtomicBool::new(false);
+/// let (loss, assi, n_iter, n_swap, cancelled): (f64, _, _, _, _) = kmedoids::rand_fasterpam_cancellable(&data, &mut meds, 0, 100, &mut rand::thread_rng(), &cancel);
+/// println!("Loss is: {}", loss);
+/// ```
+#[cfg(feature = "rand")]
+pub fn rand_fasterpam_cancellable<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	n_fixed_meds: usize,
+	maxiter: usize,
+	rng: &mut impl rand::Rng,
+	cancel: &AtomicBool,
+) -> (L, Vec<usize>, usize, usize, bool)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	rand_fasterpam_optimize(mat, med, n_fixed_meds, maxiter, rng, Some(cancel))
+}
+
+/// Main optimization function of the randomized FasterPAM, not exposed (use rand_fasterpam)
+#[cfg(feature = "rand")]
+fn rand_fasterpam_optimize<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	n_fixed_meds: usize,
+	maxiter: usize,
+	rng: &mut impl rand::Rng,
+	cancel: Option<&AtomicBool>,
+) -> (L, Vec<usize>, usize, usize, bool)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	profiling::reset();
 	let (n, k) = (mat.len(), med.len());
 	if k == 1 {
 		let assi = vec![0; n];
+		if maxiter == 0 {
+			return (single_medoid_loss(mat, med[0]), assi, 0, 0, false); // only evaluate the given medoid
+		}
 		let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, med, 0);
-		return (loss, assi, 1, if swapped { 1 } else { 0 });
+		return (loss, assi, 1, if swapped { 1 } else { 0 }, false);
 	}
-	let (mut loss, mut data) = initial_assignment(mat, med);
+	let (mut loss, mut data) = timed(Phase::InitialAssignment, || initial_assignment(mat, med));
 	debug_assert_assignment(mat, med, &data);
 
 	let mut removal_loss = vec![L::zero(); k];
 	update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
-	let (mut lastswap, mut n_swaps, mut iter) = (n, 0, 0);
+	let (mut lastswap, mut n_swaps, mut iter, mut cancelled) = (n, 0, 0, false);
 	let seq = rand::seq::index::sample(rng, n, n); // random shuffling
-	while iter < maxiter {
+	while iter < maxiter && !cancelled {
 		iter += 1;
+		let _iteration = Stopwatch::start(Phase::Iteration);
 		let (swaps_before, lastloss) = (n_swaps, loss);
 		for j in seq.iter() {
 			if j == lastswap {
 				break;
 			}
+			if is_cancelled(cancel) {
+				cancelled = true;
+				break;
+			}
 			if j == med[data[j].near.i as usize] {
 				continue; // This already is a medoid
 			}
-			let (change, b) = find_best_swap(mat, &removal_loss, &data, j);
+			let (change, b) = timed(Phase::FindBestSwap, || find_best_swap(mat, &removal_loss, &data, j));
 			if change >= L::zero() {
 				continue; // No improvement
 			}
 			n_swaps += 1;
 			lastswap = j;
 			// perform the swap
-			loss = do_swap(mat, med, &mut data, b, j);
+			loss = timed(Phase::DoSwap, || do_swap(mat, med, &mut data, b, j));
 			update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
 		}
+		if cancelled {
+			break; // keep the current, consistent, solution
+		}
 		if n_swaps == swaps_before || loss >= lastloss {
 			break; // converged
 		}
 	}
 	let assi = data.iter().map(|x| x.near.i as usize).collect();
-	(loss, assi, iter, n_swaps)
+	(loss, assi, iter, n_swaps, cancelled)
+}
+
+/// Outcome of a single optimization step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+	/// The iteration improved the loss, further steps may improve it more.
+	Improved,
+	/// The optimization has converged, further steps do not change anything.
+	Converged,
+}
+
+/// Resumable state of the FasterPAM algorithm.
+///
+/// This runs FasterPAM one iteration at a time, for example to checkpoint long
+/// optimizations. With the `serde` feature, the state can be serialized, and the
+/// optimization be continued later. Calling [`FasterPAMState::step`] until
+/// convergence gives the same result as [`fasterpam`].
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let mut state = kmedoids::FasterPAMState::<_, f64>::new(&data, meds, 0);
+/// while state.n_iter() < 100 && state.step(&data) == kmedoids::StepOutcome::Improved {}
+/// println!("Loss is: {}", state.loss());
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FasterPAMState<N, L> {
+	med: Vec<usize>,
+	data: Vec<Rec<N>>,
+	removal_loss: Vec<L>,
+	loss: L,
+	n_fixed_meds: usize,
+	lastswap: usize,
+	n_iter: usize,
+	n_swap: usize,
+	converged: bool,
+}
+
+impl<N, L> FasterPAMState<N, L>
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+{
+	/// Initialize the state by assigning all points to the initial medoids.
+	///
+	/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+	/// * `mat` - a pairwise distance matrix
+	/// * `med` - the list of initial medoids
+	/// * `n_fixed_meds` - the number of leading medoids that must not be swapped
+	///
+	/// ## Panics
+	///
+	/// * panics when the dissimilarity matrix is not square
+	/// * panics when k is 0 or larger than N
+	pub fn new<M: ArrayAdapter<N>>(mat: &M, med: Vec<usize>, n_fixed_meds: usize) -> Self {
+		let (loss, data) = initial_assignment(mat, &med);
+		debug_assert_assignment(mat, &med, &data);
+		let mut removal_loss = vec![L::zero(); med.len()];
+		update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
+		FasterPAMState {
+			med,
+			data,
+			removal_loss,
+			loss,
+			n_fixed_meds,
+			lastswap: mat.len(),
+			n_iter: 0,
+			n_swap: 0,
+			converged: false,
+		}
+	}
+
+	/// Perform one iteration of FasterPAM.
+	///
+	/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+	/// * `mat` - the same pairwise distance matrix that this state was initialized with
+	///
+	/// returns whether the solution was improved, or the optimization has converged
+	pub fn step<M: ArrayAdapter<N>>(&mut self, mat: &M) -> StepOutcome {
+		if self.converged {
+			return StepOutcome::Converged;
+		}
+		let n = mat.len();
+		assert_eq!(n, self.data.len(), "matrix does not match the state");
+		self.n_iter += 1;
+		if self.med.len() == 1 {
+			let assi = vec![0; n];
+			let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, &mut self.med, 0);
+			self.loss = loss;
+			self.n_swap += if swapped { 1 } else { 0 };
+			self.converged = true;
+			return StepOutcome::Converged;
+		}
+		let (swaps_before, lastloss) = (self.n_swap, self.loss);
+		for j in self.n_fixed_meds..n {
+			if j == self.lastswap {
+				break;
+			}
+			if j == self.med[self.data[j].near.i as usize] {
+				continue; // This already is a medoid
+			}
+			let (change, b) = find_best_swap(mat, &self.removal_loss, &self.data, j);
+			assert!(b >= self.n_fixed_meds, "Selected medoid is < n_fixed_meds");
+			if change >= L::zero() {
+				continue; // No improvement
+			}
+			self.n_swap += 1;
+			self.lastswap = j;
+			// perform the swap
+			self.loss = do_swap(mat, &mut self.med, &mut self.data, b, j);
+			update_removal_loss(&self.data, &mut self.removal_loss, self.n_fixed_meds);
+		}
+		if self.n_swap == swaps_before || self.loss >= lastloss {
+			self.converged = true; // converged
+			return StepOutcome::Converged;
+		}
+		StepOutcome::Improved
+	}
+
+	/// The current loss
+	pub fn loss(&self) -> L {
+		self.loss
+	}
+
+	/// The current medoids
+	pub fn medoids(&self) -> &[usize] {
+		&self.med
+	}
+
+	/// The current cluster assignment
+	pub fn assignment(&self) -> Vec<usize> {
+		if self.med.len() == 1 {
+			return vec![0; self.data.len()];
+		}
+		self.data.iter().map(|x| x.near.i as usize).collect()
+	}
+
+	/// The number of iterations performed
+	pub fn n_iter(&self) -> usize {
+		self.n_iter
+	}
+
+	/// The number of swaps performed
+	pub fn n_swap(&self) -> usize {
+		self.n_swap
+	}
+
+	/// Whether the optimization has converged
+	pub fn is_converged(&self) -> bool {
+		self.converged
+	}
 }
 
 /// Perform the initial assignment to medoids
@@ -194,13 +579,13 @@ where
 		.iter_mut()
 		.enumerate()
 		.map(|(i, cur)| {
-			*cur = Rec::new(0, mat.get(i, firstcenter), u32::MAX, N::zero());
+			*cur = Rec { near: DistancePair::new(0, mat.get(i, firstcenter)), seco: DistancePair::sentinel() };
 			for (m, &me) in med.iter().enumerate().skip(1) {
 				let d = mat.get(i, me);
 				if d < cur.near.d || i == me {
 					cur.seco = cur.near;
 					cur.near = DistancePair { i: m as u32, d };
-				} else if cur.seco.i == u32::MAX || d < cur.seco.d {
+				} else if cur.seco.is_sentinel() || d < cur.seco.d {
 					cur.seco = DistancePair { i: m as u32, d };
 				}
 			}
@@ -282,7 +667,7 @@ pub(crate) fn update_second_nearest<M, N>(
 	doj: N,
 ) -> DistancePair<N>
 where
-	N: PartialOrd + Copy,
+	N: Zero + PartialOrd + Copy,
 	M: ArrayAdapter<N>,
 {
 	let mut s = DistancePair::new(b as u32, doj);
@@ -356,7 +741,29 @@ where
 #[cfg(test)]
 mod tests {
 	// TODO: use a larger, much more interesting example.
-	use crate::{arrayadapter::LowerTriangle, fasterpam, silhouette, util::assert_array};
+	use crate::{canonicalize, arrayadapter::LowerTriangle, assign_to_medoids, fasterpam, fasterpam_dual, medoid_silhouette, pam_swap, silhouette, util::assert_array};
+
+	#[test]
+	fn testfasterpam_vs_pam_swap() {
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let mut rng = StdRng::seed_from_u64(5);
+		let n = 80;
+		let data = LowerTriangle { n, data: (0..n * (n - 1) / 2).map(|_| rng.gen_range(1..1000)).collect::<Vec<u32>>() };
+		for k in [2, 5, 10] {
+			let mut meds = (0..k).collect::<Vec<usize>>();
+			let (loss, assi, _, _): (i64, _, _, _) = fasterpam(&data, &mut meds, 0, 100);
+			let (eloss, eassi): (i64, _) = assign_to_medoids(&data, &meds);
+			assert_eq!(loss, eloss, "loss not as expected");
+			assert_array(assi, eassi, "assignment not as expected");
+			// FasterPAM stops in a local optimum, where PAM SWAP finds no improving swap
+			let mut pmeds = meds.clone();
+			let (ploss, _, _, n_swap): (i64, _, _, _) = pam_swap(&data, &mut pmeds, 0, 100);
+			assert_eq!((ploss, n_swap), (loss, 0), "not a local optimum of PAM SWAP");
+			// both find local optima of similar quality from the same start
+			let (ploss, _, _, _): (i64, _, _, _) = pam_swap(&data, &mut (0..k).collect(), 0, 100);
+			assert!(loss * 10 <= ploss * 11, "loss much worse than PAM SWAP: {} vs. {}", loss, ploss);
+		}
+	}
 
 	#[test]
 	fn testfasterpam_simple() {
@@ -365,7 +772,8 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![0, 1];
-		let (loss, assi, n_iter, n_swap): (i64, _, _, _) = fasterpam(&data, &mut meds, 10);
+		let (loss, mut assi, n_iter, n_swap): (i64, _, _, _) = fasterpam(&data, &mut meds, 0, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		assert_eq!(loss, 4, "loss not as expected");
 		assert_eq!(n_swap, 2, "swaps not as expected");
@@ -382,7 +790,7 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![1]; // So we need one swap
-		let (loss, assi, n_iter, n_swap): (i64, _, _, _) = fasterpam(&data, &mut meds, 10);
+		let (loss, assi, n_iter, n_swap): (i64, _, _, _) = fasterpam(&data, &mut meds, 0, 10);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		assert_eq!(loss, 14, "loss not as expected");
 		assert_eq!(n_swap, 1, "swaps not as expected");
@@ -392,8 +800,25 @@ mod tests {
 		assert_eq!(sil, 0., "Silhouette not as expected");
 	}
 
+	#[test]
+	fn testfasterpam_dual() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for k in 1..4 {
+			let mut meds = (0..k).collect::<Vec<_>>();
+			let (loss, assi, _, _, msil): (i64, _, _, _, f64) = fasterpam_dual(&data, &mut meds, 0, 10);
+			let (expected_msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
+			let (expected_loss, expected_assi): (i64, _) = assign_to_medoids(&data, &meds);
+			assert_eq!(loss, expected_loss, "loss not as expected");
+			assert_eq!(msil, expected_msil, "Medoid Silhouette not as expected");
+			assert_array(assi, expected_assi, "assignment not as expected");
+		}
+	}
+
 	#[cfg(feature = "rand")]
-	use crate::rand_fasterpam;
+	use crate::{fasterpam_cancellable, rand_fasterpam, rand_fasterpam_cancellable};
 	#[cfg(feature = "rand")]
 	use rand::{rngs::StdRng, SeedableRng};
 	#[cfg(feature = "rand")]
@@ -405,8 +830,9 @@ mod tests {
 		};
 		let mut meds = vec![0, 1];
 		let mut rng = StdRng::seed_from_u64(1);
-		let (loss, assi, n_iter, n_swap): (i64, _, _, _) =
-			rand_fasterpam(&data, &mut meds, 10, &mut rng);
+		let (loss, mut assi, n_iter, n_swap): (i64, _, _, _) =
+			rand_fasterpam(&data, &mut meds, 0, 10, &mut rng);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		assert_eq!(loss, 4, "loss not as expected");
 		assert_eq!(n_swap, 1, "swaps not as expected");
@@ -415,4 +841,106 @@ mod tests {
 		assert_array(meds, vec![0, 4], "medoids not as expected");
 		assert_eq!(sil, 0.7522494172494172, "Silhouette not as expected");
 	}
+
+	#[cfg(feature = "rand")]
+	#[test]
+	fn testfasterpam_cancelled_in_progress() {
+		use crate::arrayadapter::ClosureMatrix;
+		use rand::Rng;
+		use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+		let mut rng = StdRng::seed_from_u64(2);
+		let points = (0..300).map(|_| (rng.gen::<f64>(), rng.gen::<f64>())).collect::<Vec<_>>();
+		let (calls, limit, cancel) = (AtomicUsize::new(0), AtomicUsize::new(usize::MAX), AtomicBool::new(false));
+		// the flag is set from within the distance function, i.e., while the optimizer is running
+		let data = ClosureMatrix::new(300, |i: usize, j: usize| {
+			if calls.fetch_add(1, Ordering::Relaxed) == limit.load(Ordering::Relaxed) {
+				cancel.store(true, Ordering::Relaxed);
+			}
+			((points[i].0 - points[j].0).powi(2) + (points[i].1 - points[j].1).powi(2)).sqrt()
+		});
+		let init = crate::random_initialization(300, 10, &mut rng);
+		let (iloss, _): (f64, _) = assign_to_medoids(&data, &init);
+		for randomized in [false, true] {
+			let run = |meds: &mut Vec<usize>| -> (f64, Vec<usize>, usize, usize, bool) {
+				if randomized {
+					rand_fasterpam_cancellable(&data, meds, 0, 100, &mut StdRng::seed_from_u64(3), &cancel)
+				} else {
+					fasterpam_cancellable(&data, meds, 0, 100, &cancel)
+				}
+			};
+			cancel.store(false, Ordering::Relaxed);
+			calls.store(0, Ordering::Relaxed);
+			let (loss, _, n_iter, n_swap, cancelled) = run(&mut init.clone());
+			assert!(!cancelled && n_iter > 1, "full run not as expected");
+			// cancel halfway
+			limit.store(calls.swap(0, Ordering::Relaxed) / 2, Ordering::Relaxed);
+			let mut meds = init.clone();
+			let (closs, cassi, cn_iter, cn_swap, cancelled) = run(&mut meds);
+			limit.store(usize::MAX, Ordering::Relaxed);
+			assert!(cancelled, "run not cancelled");
+			assert!(cn_iter <= n_iter && cn_swap > 0 && cn_swap < n_swap, "not cancelled in progress: {} of {} swaps", cn_swap, n_swap);
+			let (eloss, eassi): (f64, _) = assign_to_medoids(&data, &meds);
+			assert!((closs - eloss).abs() < 1e-9, "loss not consistent");
+			assert_array(cassi, eassi, "assignment not as expected");
+			assert!(loss <= closs && closs < iloss, "loss not between the initial and final loss: {} {} {}", iloss, closs, loss);
+		}
+	}
+
+	#[cfg(feature = "rand")]
+	use crate::{FasterPAMState, StepOutcome};
+	#[cfg(feature = "rand")]
+	#[test]
+	fn testfasterpam_state() {
+		use rand::Rng;
+		let n = 200;
+		let mut rng = StdRng::seed_from_u64(1);
+		let data = LowerTriangle {
+			n,
+			data: (0..n * (n - 1) / 2).map(|_| rng.gen_range(1..1000)).collect::<Vec<u32>>(),
+		};
+		let init = crate::random_initialization(n, 5, &mut rng);
+		let mut meds = init.clone();
+		let (loss, assi, n_iter, n_swap): (i64, _, _, _) = fasterpam(&data, &mut meds, 0, 100);
+		let mut state = FasterPAMState::<_, i64>::new(&data, init.clone(), 0);
+		while state.step(&data) == StepOutcome::Improved {}
+		assert_eq!(state.loss(), loss, "loss not as expected");
+		assert_eq!(state.n_swap(), n_swap, "swaps not as expected");
+		assert_eq!(state.n_iter(), n_iter, "iterations not as expected");
+		assert_array(state.assignment(), assi.clone(), "assignment not as expected");
+		assert_array(state.medoids().to_vec(), meds.clone(), "medoids not as expected");
+		assert_eq!(state.step(&data), StepOutcome::Converged, "not converged");
+
+		#[cfg(feature = "serde")]
+		{
+			// Suspend after one iteration, and resume from the serialized state
+			let mut state = FasterPAMState::<_, i64>::new(&data, init, 0);
+			assert_eq!(state.step(&data), StepOutcome::Improved, "first iteration did not improve");
+			let json = serde_json::to_string(&state).unwrap();
+			let mut state: FasterPAMState<u32, i64> = serde_json::from_str(&json).unwrap();
+			while state.step(&data) == StepOutcome::Improved {}
+			assert_eq!(state.loss(), loss, "loss not as expected");
+			assert_eq!(state.n_swap(), n_swap, "swaps not as expected");
+			assert_eq!(state.n_iter(), n_iter, "iterations not as expected");
+			assert_array(state.assignment(), assi, "assignment not as expected");
+			assert_array(state.medoids().to_vec(), meds, "medoids not as expected");
+		}
+	}
+
+	#[test]
+	fn testfasterpam_maxiter0() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for init in [vec![1], vec![1, 2], vec![1, 2, 4]] {
+			let mut meds = init.clone();
+			let (loss, assi, n_iter, n_swap): (i64, _, _, _) = fasterpam(&data, &mut meds, 0, 0);
+			let (expected_loss, expected_assi): (i64, _) = assign_to_medoids(&data, &init);
+			assert_eq!(loss, expected_loss, "loss not as expected");
+			assert_eq!(n_swap, 0, "swaps not as expected");
+			assert_eq!(n_iter, 0, "iterations not as expected");
+			assert_array(assi, expected_assi, "assignment not as expected");
+			assert_array(meds, init, "medoids not as expected");
+		}
+	}
 }
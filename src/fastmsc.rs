@@ -1,6 +1,7 @@
 use crate::arrayadapter::ArrayAdapter;
 use crate::fastermsc::{initial_assignment,update_removal_loss,find_best_swap,do_swap};
 use crate::fastermsc::{initial_assignment_k2,find_best_swap_k2,do_swap_k2};
+use crate::profiling::{self, timed, Phase, Stopwatch};
 use crate::util::*;
 use core::ops::AddAssign;
 use num_traits::{Signed, Zero, Float, FromPrimitive};
@@ -37,7 +38,7 @@ use std::convert::From;
 /// ```
 /// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
 /// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
-/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fastpam1(&data, &mut meds, 100);
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fastpam1(&data, &mut meds, 0, 100);
 /// println!("Loss is: {}", loss);
 /// ```
 pub fn fastmsc<M, N, L>(
@@ -51,29 +52,34 @@ pub fn fastmsc<M, N, L>(
 		L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
 		M: ArrayAdapter<N>,
 {
+	profiling::reset();
 	let (n, k) = (mat.len(), med.len());
 	if k == 1 {
 		let assi = vec![0; n];
-		let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, med, 0);
-		return (loss, assi, 1, if swapped { 1 } else { 0 });
+		if maxiter == 0 {
+			return (L::one(), assi, 0, 0); // only evaluate the given medoid
+		}
+		let (swapped, _) = choose_medoid_within_partition::<M, N, L>(mat, &assi, med, 0);
+		return (L::one(), assi, 1, if swapped { 1 } else { 0 }); // as medoid_silhouette
 	}
 	if k == 2 {
 		return fastmsc_k2(mat, med, maxiter)
 	}
-	let (mut loss, mut data): (L,_) = initial_assignment(mat, med);
+	let (mut loss, mut data): (L,_) = timed(Phase::InitialAssignment, || initial_assignment(mat, med));
 	debug_assert_assignment_th(mat, med, &data);
 
 	let mut removal_loss = vec![L::zero(); k];
 	let (mut n_swaps, mut iter) = (0, 0);
 	while iter < maxiter {
 		iter += 1;
+		let _iteration = Stopwatch::start(Phase::Iteration);
 		let mut best = (L::zero(), usize::MAX, usize::MAX);
 		update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
 		for j in 0..n {
 			if j == med[data[j].near.i as usize] {
 				continue; // This already is a medoid
 			}
-			let (change, b) = find_best_swap(mat, &removal_loss, &data, j);
+			let (change, b) = timed(Phase::FindBestSwap, || find_best_swap(mat, &removal_loss, &data, j));
 			if change <= best.0 {
 				continue; // No improvement
 			}
@@ -82,7 +88,8 @@ pub fn fastmsc<M, N, L>(
 		if best.0 > L::zero() {
 			n_swaps += 1;
 			// perform the swap
-			let newloss = do_swap(mat, med, &mut data, best.1, best.2);
+			let newloss = timed(Phase::DoSwap, || do_swap(mat, med, &mut data, best.1, best.2));
+			debug_assert_assignment_th(mat, med, &data);
 			if newloss >= loss {
 				break; // Probably numerically unstable now.
 			}
@@ -108,16 +115,17 @@ fn fastmsc_k2<M, N, L>(
 {
 	let (n, k) = (mat.len(), med.len());
 	assert!(k == 2, "Only valid for k=2");
-	let (mut loss, mut assi, mut data): (L,_,_) = initial_assignment_k2(mat, med);
+	let (mut loss, mut assi, mut data): (L,_,_) = timed(Phase::InitialAssignment, || initial_assignment_k2(mat, med));
 	let (mut n_swaps, mut iter) = (0, 0);
 	while iter < maxiter {
 		iter += 1;
+		let _iteration = Stopwatch::start(Phase::Iteration);
 		let mut best = (L::zero(), k, usize::MAX);
 		for j in 0..n {
 			if j == med[assi[j] as usize] {
 				continue; // This already is a medoid
 			}
-			let (newloss, b): (L, _) = find_best_swap_k2(mat, &data, j); // assi not used, see below
+			let (newloss, b): (L, _) = timed(Phase::FindBestSwap, || find_best_swap_k2(mat, &data, j)); // assi not used, see below
 			if best.2 == usize::MAX || newloss < best.0 {
 				best = (newloss, b, j);
 			}
@@ -127,7 +135,7 @@ fn fastmsc_k2<M, N, L>(
 		}
 		// perform the swap
 		n_swaps += 1;
-		let newloss = do_swap_k2(mat, med, &mut assi, &mut data, best.1, best.2);
+		let newloss = timed(Phase::DoSwap, || do_swap_k2(mat, med, &mut assi, &mut data, best.1, best.2));
 		if !(newloss < loss) {
 			break; // Probably numerically unstable now.
 		}
@@ -140,7 +148,7 @@ fn fastmsc_k2<M, N, L>(
 #[cfg(test)]
 mod tests {
 	// TODO: use a larger, much more interesting example.
-	use crate::{arrayadapter::LowerTriangle, fastmsc, silhouette, medoid_silhouette, util::assert_array};
+	use crate::{canonicalize, arrayadapter::LowerTriangle, fastmsc, silhouette, medoid_silhouette, util::assert_array};
 
 	#[test]
 	fn testfastpammedsil_simple() {
@@ -149,7 +157,8 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![0, 1, 2];
-		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = fastmsc(&data, &mut meds, 10);
+		let (loss, mut assi, n_iter, n_swap): (f64, _, _, _) = fastmsc(&data, &mut meds, 0, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
 		print!("FastMSC: {:?} {:?} {:?} {:?} {:?} {:?}", loss, n_iter, n_swap, sil, assi, meds);
@@ -157,8 +166,8 @@ mod tests {
 		assert_eq!(msil, 0.9047619047619048, "Medoid Silhouette not as expected");
 		assert_eq!(n_swap, 1, "swaps not as expected");
 		assert_eq!(n_iter, 2, "iterations not as expected");
-		assert_array(assi, vec![0, 0, 2, 1, 1], "assignment not as expected");
-		assert_array(meds, vec![0, 3, 2], "medoids not as expected");
+		assert_array(assi, vec![0, 0, 1, 2, 2], "assignment not as expected");
+		assert_array(meds, vec![0, 2, 3], "medoids not as expected");
 		assert_eq!(sil, 0.5622222222222222, "Silhouette not as expected");
 	}
 
@@ -169,7 +178,8 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![0, 1];
-		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = fastmsc(&data, &mut meds, 10);
+		let (loss, mut assi, n_iter, n_swap): (f64, _, _, _) = fastmsc(&data, &mut meds, 0, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
 		print!("FastMSC: {:?} {:?} {:?} {:?} {:?} {:?}", loss, n_iter, n_swap, sil, assi, meds);
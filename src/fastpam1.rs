@@ -1,5 +1,6 @@
 use crate::arrayadapter::ArrayAdapter;
 use crate::fasterpam::{do_swap, find_best_swap, initial_assignment, update_removal_loss};
+use crate::profiling::{self, timed, Phase, Stopwatch};
 use crate::util::*;
 use core::ops::AddAssign;
 use num_traits::{Signed, Zero, FromPrimitive};
@@ -36,7 +37,7 @@ use std::convert::From;
 /// ```
 /// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
 /// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
-/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fastpam1(&data, &mut meds, 100);
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fastpam1(&data, &mut meds, 0, 100);
 /// println!("Loss is: {}", loss);
 /// ```
 pub fn fastpam1<M, N, L>(
@@ -50,25 +51,30 @@ where
 	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
 	M: ArrayAdapter<N>,
 {
+	profiling::reset();
 	let (n, k) = (mat.len(), med.len());
 	if k == 1 {
 		let assi = vec![0; n];
+		if maxiter == 0 {
+			return (single_medoid_loss(mat, med[0]), assi, 0, 0); // only evaluate the given medoid
+		}
 		let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, med, 0);
 		return (loss, assi, 1, if swapped { 1 } else { 0 });
 	}
-	let (mut loss, mut data) = initial_assignment(mat, med);
+	let (mut loss, mut data) = timed(Phase::InitialAssignment, || initial_assignment(mat, med));
 	debug_assert_assignment(mat, med, &data);
 	let mut removal_loss = vec![L::zero(); k];
 	let (mut n_swaps, mut iter) = (0, 0);
 	while iter < maxiter {
 		iter += 1;
+		let _iteration = Stopwatch::start(Phase::Iteration);
 		let mut best = (L::zero(), usize::MAX, usize::MAX);
 		update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
 		for j in 0..n {
 			if j == med[data[j].near.i as usize] {
 				continue; // This already is a medoid
 			}
-			let (change, b) = find_best_swap(mat, &removal_loss, &data, j);
+			let (change, b) = timed(Phase::FindBestSwap, || find_best_swap(mat, &removal_loss, &data, j));
 			if change >= best.0 {
 				continue; // No improvement
 			}
@@ -77,7 +83,7 @@ where
 		if best.0 < L::zero() {
 			n_swaps += 1;
 			// perform the swap
-			let newloss = do_swap(mat, med, &mut data, best.1, best.2);
+			let newloss = timed(Phase::DoSwap, || do_swap(mat, med, &mut data, best.1, best.2));
 			if newloss >= loss {
 				break; // Probably numerically unstable now.
 			}
@@ -93,7 +99,7 @@ where
 #[cfg(test)]
 mod tests {
 	// TODO: use a larger, much more interesting example.
-	use crate::{arrayadapter::LowerTriangle, fastpam1, silhouette, util::assert_array};
+	use crate::{canonicalize, arrayadapter::LowerTriangle, fastpam1, silhouette, util::assert_array};
 
 	#[test]
 	fn test_fastpam1_simple() {
@@ -102,7 +108,8 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![0, 1];
-		let (loss, assi, n_iter, n_swap): (i64, _, _, _) = fastpam1(&data, &mut meds, 10);
+		let (loss, mut assi, n_iter, n_swap): (i64, _, _, _) = fastpam1(&data, &mut meds, 0, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		assert_eq!(loss, 4, "loss not as expected");
 		assert_eq!(n_swap, 1, "swaps not as expected");
@@ -0,0 +1,165 @@
+use crate::arrayadapter::ArrayAdapter;
+use num_traits::Float;
+use std::collections::BinaryHeap;
+use std::sync::Mutex;
+
+/// Shortest path distances in a weighted graph, computed on demand
+///
+/// The graph is given as an adjacency list: entry `i` lists the neighbors of node `i`
+/// with the edge weights. The distance of two nodes is the length of the shortest path,
+/// or infinity if they are not connected. When a distance is needed, Dijkstra's algorithm
+/// computes all distances from one of the two nodes, and these are kept. The graph is
+/// assumed to be undirected, i.e., every edge should be listed for both of its nodes, so
+/// the distances from either node can be used. This allows clustering the nodes of a graph
+/// without computing all pairwise distances first, but all computed rows are kept, so the
+/// memory use can grow to O(N²). When used from multiple threads at the same time, a row
+/// may occasionally be computed twice, as the cache is not locked during the computation.
+///
+/// ## Example
+/// ```
+/// // a path 0 - 1 - 2 - 3 - 4
+/// let edges = vec![vec![(1, 1.)], vec![(0, 1.), (2, 1.)], vec![(1, 1.), (3, 1.)], vec![(2, 1.), (4, 1.)], vec![(3, 1.)]];
+/// let data = kmedoids::DijkstraMatrix::new(edges);
+/// let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = kmedoids::pam(&data, 1, 10);
+/// assert_eq!((loss, meds), (6., vec![2]));
+/// ```
+#[derive(Debug)]
+pub struct DijkstraMatrix<W> {
+	adjacency: Vec<Vec<(usize, W)>>,
+	rows: Mutex<Vec<Option<Vec<W>>>>,
+}
+impl<W: Float> DijkstraMatrix<W> {
+	/// Wrap the adjacency list of a graph
+	///
+	/// ## Panics
+	///
+	/// * panics when a neighbor index is not less than the number of nodes
+	/// * panics when an edge weight is negative or NaN
+	pub fn new(adjacency: Vec<Vec<(usize, W)>>) -> Self {
+		let n = adjacency.len();
+		for (j, w) in adjacency.iter().flatten() {
+			assert!(*j < n, "invalid neighbor index, must be less than the number of nodes");
+			assert!(*w >= W::zero(), "edge weights must be non-negative");
+		}
+		DijkstraMatrix { adjacency, rows: Mutex::new(vec![None; n]) }
+	}
+
+	/// Number of nodes the shortest paths have been computed from so far
+	pub fn cached_sources(&self) -> usize {
+		self.rows.lock().unwrap().iter().filter(|r| r.is_some()).count()
+	}
+
+	/// Distances from a source node to all nodes, with Dijkstra's algorithm
+	fn shortest_paths(&self, source: usize) -> Vec<W> {
+		let mut dist = vec![W::infinity(); self.adjacency.len()];
+		let mut heap = BinaryHeap::new();
+		dist[source] = W::zero();
+		heap.push(HeapEntry { d: W::zero(), node: source });
+		while let Some(HeapEntry { d, node }) = heap.pop() {
+			if d > dist[node] {
+				continue; // already reached on a shorter path
+			}
+			for &(next, w) in self.adjacency[node].iter() {
+				let nd = d + w;
+				if nd < dist[next] {
+					dist[next] = nd;
+					heap.push(HeapEntry { d: nd, node: next });
+				}
+			}
+		}
+		dist
+	}
+}
+/// Adapter implementation for DijkstraMatrix
+impl<W: Float> ArrayAdapter<W> for DijkstraMatrix<W> {
+	#[inline]
+	fn len(&self) -> usize {
+		self.adjacency.len()
+	}
+	#[inline]
+	fn is_square(&self) -> bool {
+		true
+	}
+	fn get(&self, x: usize, y: usize) -> W {
+		// The lock is not held while computing, as a search may be slow
+		{
+			let rows = self.rows.lock().unwrap();
+			if let Some(row) = &rows[x] {
+				return row[y];
+			}
+			if let Some(row) = &rows[y] {
+				return row[x];
+			}
+		}
+		let row = self.shortest_paths(x);
+		let d = row[y];
+		self.rows.lock().unwrap()[x].get_or_insert(row);
+		d
+	}
+}
+
+/// Heap entry of the Dijkstra search, with the smallest distance first
+struct HeapEntry<W> {
+	d: W,
+	node: usize,
+}
+impl<W: PartialOrd> Ord for HeapEntry<W> {
+	fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+		// reversed, as the binary heap pops the largest entry
+		other.d.partial_cmp(&self.d).unwrap_or(std::cmp::Ordering::Equal)
+	}
+}
+impl<W: PartialOrd> PartialOrd for HeapEntry<W> {
+	fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+		Some(self.cmp(other))
+	}
+}
+impl<W: PartialOrd> PartialEq for HeapEntry<W> {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == std::cmp::Ordering::Equal
+	}
+}
+impl<W: PartialOrd> Eq for HeapEntry<W> {}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::ArrayAdapter, pam, DijkstraMatrix};
+
+	/// Undirected adjacency list of the edges (a, b, w)
+	fn graph(n: usize, edges: &[(usize, usize, f64)]) -> Vec<Vec<(usize, f64)>> {
+		let mut adjacency = vec![Vec::new(); n];
+		for &(a, b, w) in edges.iter() {
+			adjacency[a].push((b, w));
+			adjacency[b].push((a, w));
+		}
+		adjacency
+	}
+
+	#[test]
+	fn test_dijkstra_matrix() {
+		// two stars with centers 0 and 5, connected by a long edge between leaves 4 and 9
+		let mut edges = (1..5).map(|i| (0, i, 1.)).chain((6..10).map(|i| (5, i, 1.))).collect::<Vec<_>>();
+		edges.push((4, 9, 5.));
+		edges.push((1, 2, 3.)); // a longer alternative to the path over the center
+		let data = DijkstraMatrix::new(graph(10, &edges));
+		assert_eq!(data.get(1, 2), 2., "shortest path not as expected");
+		assert_eq!(data.get(2, 1), 2., "distance not symmetric");
+		assert_eq!(data.get(3, 8), 1. + 1. + 5. + 1. + 1., "shortest path not as expected");
+		assert_eq!(data.cached_sources(), 2, "cache not as expected");
+		let (loss, assi, meds, _, _): (f64, _, _, _, _) = pam(&data, 2, 10);
+		assert_eq!((loss, meds), (8., vec![0, 5]), "medoids not as expected");
+		assert_eq!(assi, vec![0, 0, 0, 0, 0, 1, 1, 1, 1, 1], "assignment not as expected");
+		assert!(data.cached_sources() <= 10, "cache not as expected");
+		// disconnected components
+		let data = DijkstraMatrix::new(graph(4, &[(0, 1, 1.), (2, 3, 2.)]));
+		assert_eq!(data.get(0, 1), 1., "distance not as expected");
+		assert_eq!(data.get(0, 2), f64::INFINITY, "disconnected distance not infinite");
+		assert_eq!(data.get(3, 3), 0., "diagonal not zero");
+	}
+
+	#[test]
+	#[should_panic(expected = "non-negative")]
+	fn test_dijkstra_matrix_negative() {
+		DijkstraMatrix::new(graph(2, &[(0, 1, -1.)]));
+	}
+}
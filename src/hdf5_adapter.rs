@@ -0,0 +1,119 @@
+use crate::arrayadapter::ArrayAdapter;
+use hdf5::{Dataset, File, H5Type, Result};
+use std::path::Path;
+
+/// Square distance matrix stored in a two-dimensional HDF5 dataset
+///
+/// The data is not loaded into memory: every access reads a single element from the file,
+/// using a hyperslab selection. This allows clustering distance matrices larger than the
+/// main memory, but is much slower than in-memory adapters. When the values of one row are
+/// used many times in a row (e.g., for one point in the inner loop), load it with
+/// [`Hdf5Matrix::prefetch_row`] first, then accesses to this row use the buffer.
+///
+/// Requires the `hdf5` feature, and the HDF5 library to be installed.
+///
+/// ## Example
+/// ```no_run
+/// let data = kmedoids::Hdf5Matrix::<f64>::open("distances.h5", "distances").unwrap();
+/// let mut meds = vec![0, 1];
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 100);
+/// println!("Loss is {}", loss);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Hdf5Matrix<N> {
+	dataset: Dataset,
+	shape: (usize, usize),
+	row: Option<(usize, Vec<N>)>,
+}
+impl<N: H5Type + Copy> Hdf5Matrix<N> {
+	/// Open the dataset `name` in the HDF5 file at `path`
+	///
+	/// returns the matrix, or an error if the file cannot be opened or the dataset is
+	/// not two-dimensional
+	pub fn open<P: AsRef<Path>>(path: P, name: &str) -> Result<Self> {
+		Self::new(File::open(path)?.dataset(name)?)
+	}
+
+	/// Wrap an open two-dimensional dataset
+	///
+	/// returns the matrix, or an error if the dataset is not two-dimensional
+	pub fn new(dataset: Dataset) -> Result<Self> {
+		let shape = match dataset.shape()[..] {
+			[rows, cols] => (rows, cols),
+			_ => return Err("dataset is not two-dimensional".into()),
+		};
+		Ok(Hdf5Matrix { dataset, shape, row: None })
+	}
+
+	/// Read row `i` into the buffer, replacing the previously buffered row
+	///
+	/// returns an error if reading from the file fails
+	pub fn prefetch_row(&mut self, i: usize) -> Result<()> {
+		let values = self.dataset.read_slice_1d::<N, _>((i, ..))?;
+		self.row = Some((i, values.into_raw_vec()));
+		Ok(())
+	}
+
+	/// Get the underlying dataset
+	pub fn into_inner(self) -> Dataset {
+		self.dataset
+	}
+}
+/// Adapter implementation for Hdf5Matrix
+///
+/// ## Panics
+///
+/// * panics when reading from the file fails
+impl<N: H5Type + Copy> ArrayAdapter<N> for Hdf5Matrix<N> {
+	#[inline]
+	fn len(&self) -> usize {
+		self.shape.0
+	}
+	#[inline]
+	fn is_square(&self) -> bool {
+		self.shape.0 == self.shape.1
+	}
+	fn get(&self, x: usize, y: usize) -> N {
+		if let Some((i, values)) = &self.row {
+			if *i == x {
+				return values[y];
+			}
+		}
+		self.dataset.read_slice_1d::<N, _>((x, y..y + 1)).expect("reading from the HDF5 dataset failed")[0]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Hdf5Matrix;
+	use crate::{arrayadapter::{ArrayAdapter, LowerTriangle}, pam};
+
+	/// Write the 5 point example to a temporary HDF5 file
+	fn write_fixture(path: &std::path::Path) -> LowerTriangle<f64> {
+		let data = LowerTriangle { n: 5, data: vec![1., 2., 3., 4., 5., 6., 7., 8., 9., 1.] };
+		let mat = ndarray::Array2::from_shape_fn((5, 5), |(i, j)| data.get(i, j));
+		let file = hdf5::File::create(path).unwrap();
+		file.new_dataset_builder().with_data(&mat).create("distances").unwrap();
+		data
+	}
+
+	#[test]
+	#[ignore = "requires the HDF5 library"]
+	fn test_hdf5_matrix() {
+		let path = std::env::temp_dir().join(format!("kmedoids-test-{}.h5", std::process::id()));
+		let data = write_fixture(&path);
+		let mut mat = Hdf5Matrix::<f64>::open(&path, "distances").unwrap();
+		assert_eq!(mat.len(), 5, "size not as expected");
+		assert!(mat.is_square(), "matrix not square");
+		let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = pam(&mat, 2, 10);
+		let expect: (f64, _, _, _, _) = pam(&data, 2, 10);
+		assert_eq!((loss, assi, meds, n_iter, n_swap), expect, "result not as expected");
+		mat.prefetch_row(3).unwrap();
+		for i in 0..5 {
+			for j in 0..5 {
+				assert_eq!(mat.get(i, j), data.get(i, j), "value not as expected");
+			}
+		}
+		std::fs::remove_file(&path).unwrap();
+	}
+}
@@ -0,0 +1,110 @@
+use core::fmt;
+use core::ops::{Add, AddAssign, Deref, Sub, SubAssign};
+
+/// Index of a point in the data set, e.g., a medoid.
+///
+/// The algorithms use plain `usize` for both point indexes and cluster indexes. Use this
+/// type and [`ClusterIndex`] to let the compiler catch when one is used for the other,
+/// e.g., with [`crate::KMedoidsResult::medoid`] and [`crate::KMedoidsResult::cluster_of`].
+///
+/// ## Example
+/// ```
+/// let p = kmedoids::PointIndex::from(3);
+/// assert_eq!(*p + 1, 4);
+/// assert_eq!(usize::from(p + 1), 4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+#[repr(transparent)]
+pub struct PointIndex(pub usize);
+
+/// Index of a cluster, i.e., the position of its medoid in the list of medoids.
+///
+/// Cluster assignments contain cluster indexes, medoid lists contain [`PointIndex`]es.
+/// Mixing them up fails to compile:
+/// ```compile_fail
+/// let result = kmedoids::KMedoidsResult::new(0, vec![0, 0, 1], vec![0, 2], 1, 0);
+/// let p = result.medoid(kmedoids::ClusterIndex(1));
+/// let m = result.medoid(p); // a point index is not a cluster index
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+#[repr(transparent)]
+pub struct ClusterIndex(pub usize);
+
+/// Conversions and arithmetic shared by the index types
+macro_rules! impl_index {
+	($($t:ident),*) => {$(
+		impl Deref for $t {
+			type Target = usize;
+			#[inline]
+			fn deref(&self) -> &usize {
+				&self.0
+			}
+		}
+		impl From<usize> for $t {
+			#[inline]
+			fn from(i: usize) -> Self {
+				$t(i)
+			}
+		}
+		impl From<$t> for usize {
+			#[inline]
+			fn from(i: $t) -> usize {
+				i.0
+			}
+		}
+		impl Add<usize> for $t {
+			type Output = $t;
+			#[inline]
+			fn add(self, rhs: usize) -> $t {
+				$t(self.0 + rhs)
+			}
+		}
+		impl Sub<usize> for $t {
+			type Output = $t;
+			#[inline]
+			fn sub(self, rhs: usize) -> $t {
+				$t(self.0 - rhs)
+			}
+		}
+		impl AddAssign<usize> for $t {
+			#[inline]
+			fn add_assign(&mut self, rhs: usize) {
+				self.0 += rhs;
+			}
+		}
+		impl SubAssign<usize> for $t {
+			#[inline]
+			fn sub_assign(&mut self, rhs: usize) {
+				self.0 -= rhs;
+			}
+		}
+		impl fmt::Display for $t {
+			fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+				self.0.fmt(f)
+			}
+		}
+	)*};
+}
+impl_index!(PointIndex, ClusterIndex);
+
+#[cfg(test)]
+mod tests {
+	use crate::{ClusterIndex, PointIndex};
+
+	#[test]
+	fn test_index_conversions() {
+		let mut p = PointIndex::from(3);
+		assert_eq!(*p, 3, "deref not as expected");
+		p += 2;
+		assert_eq!(p, PointIndex(5), "addition not as expected");
+		p -= 1;
+		assert_eq!(usize::from(p - 1), 3, "subtraction not as expected");
+		let c: ClusterIndex = 1.into();
+		assert_eq!((c + 1).to_string(), "2", "display not as expected");
+		assert!(ClusterIndex(0) < c, "ordering not as expected");
+		// deref gives access to the usize methods
+		assert_eq!(c.pow(2), 1, "usize method not as expected");
+	}
+}
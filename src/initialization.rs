@@ -1,7 +1,18 @@
+#[cfg(feature = "rand")]
+use crate::arrayadapter::ArrayAdapter;
+
 /// Random initialization (requires the `rand` crate)
 ///
 /// This is simply a call to `rand::seq::index::sample`.
 ///
+/// With a seeded generator, such as `rand::rngs::StdRng::seed_from_u64`, the result is
+/// reproducible, and the same on all platforms (including 32 bit platforms such as wasm32)
+/// for n < 2³², as `sample` then uses 32 bit indexes. The crate depends on `rand` 0.8,
+/// and `rand` only changes the output of seeded generators and of `sample` in a new
+/// minor (0.x) version, so the output is stable within 0.8. The reference values are
+/// tested against `tests/fixtures/random_initialization.txt`. For reproducibility beyond
+/// that, use a generator with a stable algorithm, e.g., from the `rand_chacha` crate.
+///
 /// * `n` - size of the data set
 /// * `k` - number of clusters to find
 /// * `rng` - random number generator
@@ -38,3 +49,164 @@ pub fn random_initialization(n: usize, k: usize, rng: &mut impl rand::Rng) -> Ve
 pub fn first_k(k: usize) -> Vec<usize> {
 	(0..k).collect()
 }
+
+/// Choose initial medoids spread across known groups of the objects.
+///
+/// Each group first contributes its 1-medoid (the member with the smallest sum of
+/// distances to the members of the group; on ties, the smallest index). The remaining
+/// medoids are sampled with k-medoids++, i.e., with probability proportional to the
+/// squared distance to the nearest medoid chosen so far, but only from groups that
+/// have fewer medoids than their share of k proportional to the group size (or from
+/// all groups, if there is no such group).
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `groups` - the group of each object, as consecutive integers
+/// * `k` - number of clusters to find
+/// * `rng` - random number generator
+///
+/// returns the initial medoids, starting with the 1-medoids of the groups
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when the number of groups is not of length N
+/// * panics when k is larger than N
+/// * panics when there are more (non-empty) groups than k
+///
+/// ## Example
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let meds = kmedoids::stratified_initialization(&data, &[0, 0, 1, 1], 3, &mut rand::thread_rng());
+/// println!("Chosen medoids: {:?}", meds);
+/// ```
+#[cfg(feature = "rand")]
+pub fn stratified_initialization<M, N>(mat: &M, groups: &[usize], k: usize, rng: &mut impl rand::Rng) -> Vec<usize>
+where
+	N: num_traits::ToPrimitive + Copy,
+	M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert_eq!(groups.len(), n, "groups must have length N");
+	assert!(k <= n, "k must be at most N");
+	let ng = groups.iter().max().map_or(0, |&g| g + 1);
+	let mut members = vec![Vec::new(); ng];
+	for (i, &g) in groups.iter().enumerate() {
+		members[g].push(i);
+	}
+	members.retain(|m| !m.is_empty());
+	assert!(
+		members.len() <= k,
+		"the number of groups ({}) exceeds k ({}), cannot choose a medoid for each group",
+		members.len(),
+		k
+	);
+	// The 1-medoid of each group
+	let mut meds = members
+		.iter()
+		.map(|m| {
+			let cost = |&j: &usize| m.iter().map(|&i| mat.get(i, j).to_f64().unwrap()).sum::<f64>();
+			m.iter().fold((m[0], cost(&m[0])), |b, j| {
+				let c = cost(j);
+				if c < b.1 { (*j, c) } else { b }
+			}).0
+		})
+		.collect::<Vec<usize>>();
+	let mut count = vec![1; members.len()];
+	let mut dist = (0..n)
+		.map(|i| meds.iter().map(|&m| mat.get(i, m).to_f64().unwrap()).fold(f64::INFINITY, f64::min))
+		.collect::<Vec<f64>>();
+	// k-medoids++ for the remaining medoids, in under-represented groups
+	while meds.len() < k {
+		let under = (0..members.len())
+			.map(|g| (count[g] as f64) < (k * members[g].len()) as f64 / n as f64 && count[g] < members[g].len())
+			.collect::<Vec<bool>>();
+		let any = under.iter().any(|&u| u);
+		let candidates = members
+			.iter()
+			.enumerate()
+			.filter(|&(g, _)| !any || under[g])
+			.flat_map(|(g, m)| m.iter().map(move |&i| (g, i)))
+			.filter(|&(_, i)| !meds.contains(&i))
+			.collect::<Vec<(usize, usize)>>();
+		let total = candidates.iter().map(|&(_, i)| dist[i] * dist[i]).sum::<f64>();
+		let (g, j) = if total > 0. {
+			let mut r = rng.gen::<f64>() * total;
+			*candidates.iter().find(|&&(_, i)| {
+				r -= dist[i] * dist[i];
+				r < 0.
+			}).unwrap_or(candidates.last().unwrap())
+		} else {
+			candidates[rng.gen_range(0..candidates.len())]
+		};
+		meds.push(j);
+		count[g] += 1;
+		for (i, di) in dist.iter_mut().enumerate() {
+			*di = di.min(mat.get(i, j).to_f64().unwrap());
+		}
+	}
+	meds
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::LowerTriangle, stratified_initialization};
+	use rand::{rngs::StdRng, Rng, SeedableRng};
+
+	fn grouped_data(rng: &mut StdRng) -> (LowerTriangle<u32>, Vec<usize>) {
+		// four groups of different size, on a line
+		let groups = (0..40).map(|i| [0, 0, 0, 0, 1, 1, 1, 2, 2, 3][i % 10]).collect::<Vec<usize>>();
+		let points = groups.iter().map(|&g| g as i32 * 100 + rng.gen_range(0..50)).collect::<Vec<i32>>();
+		let mut data = Vec::with_capacity(40 * 39 / 2);
+		for i in 1..40 {
+			for j in 0..i {
+				data.push((points[i] - points[j]).unsigned_abs());
+			}
+		}
+		(LowerTriangle { n: 40, data }, groups)
+	}
+
+	#[test]
+	fn test_stratified_initialization() {
+		let mut rng = StdRng::seed_from_u64(1);
+		let (data, groups) = grouped_data(&mut rng);
+		let meds = stratified_initialization(&data, &groups, 4, &mut rng);
+		let mut medgroups = meds.iter().map(|&m| groups[m]).collect::<Vec<_>>();
+		medgroups.sort();
+		assert_eq!(medgroups, vec![0, 1, 2, 3], "groups not as expected");
+		for k in 5..=12 {
+			let meds = stratified_initialization(&data, &groups, k, &mut rng);
+			assert_eq!(meds.len(), k, "number of medoids not as expected");
+			assert_eq!(&meds[..4], &stratified_initialization(&data, &groups, 4, &mut rng)[..], "1-medoids not as expected");
+			let mut sorted = meds.clone();
+			sorted.sort();
+			sorted.dedup();
+			assert_eq!(sorted.len(), k, "medoids not unique");
+		}
+	}
+
+	#[test]
+	#[should_panic(expected = "exceeds k")]
+	fn test_stratified_too_many_groups() {
+		let mut rng = StdRng::seed_from_u64(1);
+		let (data, groups) = grouped_data(&mut rng);
+		stratified_initialization(&data, &groups, 3, &mut rng);
+	}
+
+	/// Reference output of the seeded random initialization, which must be the same on all platforms
+	#[test]
+	fn test_random_initialization_reference() {
+		let mut out = String::new();
+		for seed in 0..4 {
+			for (n, k) in [(10, 3), (100, 5), (1000, 10), (100000, 20)] {
+				let meds = crate::random_initialization(n, k, &mut StdRng::seed_from_u64(seed));
+				let meds = meds.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(" ");
+				out += &format!("{} {} {}: {}\n", seed, n, k, meds);
+			}
+		}
+		let expected = include_str!("../tests/fixtures/random_initialization.txt");
+		assert_eq!(out, expected, "random initialization not as expected");
+	}
+}
@@ -0,0 +1,115 @@
+use crate::arrayadapter::SquareMatrix;
+use num_traits::Float;
+
+/// Convert a kernel matrix into the distance matrix of the feature space.
+///
+/// The distance of two objects in the reproducing kernel Hilbert space is
+/// `D(i,j) = sqrt(K(i,i) + K(j,j) - 2*K(i,j))`. This allows applying k-medoids in the
+/// kernel space. Small negative values due to numerical imprecision are treated as zero.
+///
+/// * `kernel` - a symmetric, positive semi-definite kernel matrix
+///
+/// returns the distance matrix
+///
+/// ## Panics
+///
+/// * panics when the kernel matrix does not have n x n entries
+///
+/// ## Example
+/// ```
+/// let kernel = kmedoids::arrayadapter::SquareMatrix::new(2, vec![1., 0.5, 0.5, 1.]);
+/// let data = kmedoids::kernel_to_distance_matrix(&kernel);
+/// assert_eq!(data.data, vec![0., 1., 1., 0.]);
+/// ```
+pub fn kernel_to_distance_matrix(kernel: &SquareMatrix<f64>) -> SquareMatrix<f64> {
+	let n = kernel.n;
+	assert_eq!(kernel.data.len(), n * n, "Kernel matrix is not square");
+	let mut data = vec![0.; n * n];
+	for i in 0..n {
+		for j in 0..i {
+			let d = (kernel.data[i * n + i] + kernel.data[j * n + j] - 2. * kernel.data[i * n + j]).max(0.).sqrt();
+			data[i * n + j] = d;
+			data[j * n + i] = d;
+		}
+	}
+	SquareMatrix::new(n, data)
+}
+
+/// Double-center a matrix in place.
+///
+/// Subtracts the row and column means, and adds the grand mean, such that all rows
+/// and columns sum to zero. For a kernel matrix, this corresponds to centering the
+/// data in the feature space, as required for kernel PCA. The distances obtained with
+/// [`kernel_to_distance_matrix`] do not change.
+///
+/// * type `N` - number data type such as `f64` (must be a float)
+/// * `mat` - the matrix to center
+///
+/// ## Panics
+///
+/// * panics when the matrix does not have n x n entries
+///
+/// ## Example
+/// ```
+/// let mut kernel = kmedoids::arrayadapter::SquareMatrix::new(2, vec![1., 0.5, 0.5, 1.]);
+/// kmedoids::double_center(&mut kernel);
+/// assert_eq!(kernel.data, vec![0.25, -0.25, -0.25, 0.25]);
+/// ```
+pub fn double_center<N: Float>(mat: &mut SquareMatrix<N>) {
+	let n = mat.n;
+	assert_eq!(mat.data.len(), n * n, "Matrix is not square");
+	if n == 0 {
+		return;
+	}
+	let nn = N::from(n).unwrap();
+	let rows = mat.data.chunks(n).map(|r| r.iter().fold(N::zero(), |a, &b| a + b) / nn).collect::<Vec<N>>();
+	let cols = (0..n).map(|j| (0..n).fold(N::zero(), |a, i| a + mat.data[i * n + j]) / nn).collect::<Vec<N>>();
+	let mean = rows.iter().fold(N::zero(), |a, &b| a + b) / nn;
+	for (row, &ri) in mat.data.chunks_mut(n).zip(rows.iter()) {
+		for (v, &cj) in row.iter_mut().zip(cols.iter()) {
+			*v = *v - ri - cj + mean;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::SquareMatrix, double_center, kernel_to_distance_matrix};
+	use rand::{rngs::StdRng, Rng, SeedableRng};
+
+	#[test]
+	fn test_kernel_to_distance_matrix() {
+		// Radial basis function kernel of random points
+		let mut rng = StdRng::seed_from_u64(1);
+		let points = (0..20).map(|_| (rng.gen_range(0.0..3.0), rng.gen_range(0.0..3.0))).collect::<Vec<(f64, f64)>>();
+		let n = points.len();
+		let mut kernel = SquareMatrix::new(n, vec![0.; n * n]);
+		for i in 0..n {
+			for j in 0..n {
+				let d2: f64 = (points[i].0 - points[j].0).powi(2) + (points[i].1 - points[j].1).powi(2);
+				kernel.data[i * n + j] = (-0.5 * d2).exp();
+			}
+		}
+		let dist = kernel_to_distance_matrix(&kernel);
+		for i in 0..n {
+			assert_eq!(dist.data[i * n + i], 0., "diagonal not zero");
+			for j in 0..n {
+				assert_eq!(dist.data[i * n + j], dist.data[j * n + i], "not symmetric");
+				for k in 0..n {
+					assert!(dist.data[i * n + k] <= dist.data[i * n + j] + dist.data[j * n + k] + 1e-12, "triangle inequality violated");
+				}
+			}
+		}
+
+		// Double centering does not change the distances
+		double_center(&mut kernel);
+		for i in 0..n {
+			let sum = kernel.data[i * n..(i + 1) * n].iter().sum::<f64>();
+			assert!(sum.abs() < 1e-12, "row not centered");
+		}
+		let dist2 = kernel_to_distance_matrix(&kernel);
+		for (a, b) in dist.data.iter().zip(dist2.data.iter()) {
+			assert!((a - b).abs() < 1e-12, "distances not as expected");
+		}
+	}
+}
@@ -0,0 +1,151 @@
+use crate::arrayadapter::{LowerTriangle, SquareMatrix};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Result, Write};
+use std::path::Path;
+
+/// Read a distance matrix in the MatrixMarket coordinate format.
+///
+/// Supports real and integer coordinate matrices, in general or symmetric form.
+/// For symmetric matrices, the lower triangle is mirrored. Entries not present in
+/// the file (in particular the diagonal) are zero.
+///
+/// * `path` - the file to read
+///
+/// returns the dense distance matrix, or an error with `ErrorKind::InvalidData` if the
+/// file is not a square coordinate matrix in the supported format
+///
+/// ## Example
+/// ```no_run
+/// let data = kmedoids::read_matrix_market("distances.mtx").unwrap();
+/// let mut meds = vec![0, 1];
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 100);
+/// ```
+pub fn read_matrix_market<P: AsRef<Path>>(path: P) -> Result<SquareMatrix<f64>> {
+	let mut lines = BufReader::new(File::open(path)?).lines();
+	let header = lines.next().ok_or_else(|| invalid("empty file"))??;
+	let header = header.to_lowercase();
+	let fields = header.split_whitespace().collect::<Vec<_>>();
+	if fields.len() != 5 || fields[0] != "%%matrixmarket" || fields[1] != "matrix" || fields[2] != "coordinate" {
+		return Err(invalid("not a MatrixMarket coordinate matrix"));
+	}
+	if fields[3] != "real" && fields[3] != "integer" {
+		return Err(invalid("only real and integer matrices are supported"));
+	}
+	let symmetric = match fields[4] {
+		"general" => false,
+		"symmetric" => true,
+		_ => return Err(invalid("only general and symmetric matrices are supported")),
+	};
+	// Skip comments, and read the size line
+	let mut data_lines = lines.filter(|l| !matches!(l, Ok(l) if l.starts_with('%') || l.trim().is_empty()));
+	let size = data_lines.next().ok_or_else(|| invalid("missing size line"))??;
+	let size = parse_fields::<usize>(&size, 3)?;
+	let (n, nnz) = (size[0], size[2]);
+	if size[1] != n {
+		return Err(invalid("matrix is not square"));
+	}
+	let mut data = vec![0.; n * n];
+	let mut count = 0;
+	for line in data_lines {
+		let line = line?;
+		let mut parts = line.split_whitespace();
+		let (i, j) = match (parts.next().map(str::parse::<usize>), parts.next().map(str::parse::<usize>)) {
+			(Some(Ok(i)), Some(Ok(j))) if i >= 1 && i <= n && j >= 1 && j <= n => (i - 1, j - 1),
+			_ => return Err(invalid("invalid entry index")),
+		};
+		let v = parts.next().and_then(|v| v.parse::<f64>().ok()).ok_or_else(|| invalid("invalid entry value"))?;
+		data[i * n + j] = v;
+		if symmetric {
+			data[j * n + i] = v;
+		}
+		count += 1;
+	}
+	if count != nnz {
+		return Err(invalid("number of entries does not match the size line"));
+	}
+	Ok(SquareMatrix::new(n, data))
+}
+
+/// Write a distance matrix in the MatrixMarket coordinate format.
+///
+/// The matrix is written as a real symmetric matrix, with all entries of the lower
+/// triangle (except the diagonal, which is zero).
+///
+/// * `mat` - the distance matrix
+/// * `path` - the file to write
+///
+/// ## Example
+/// ```no_run
+/// let data = kmedoids::arrayadapter::LowerTriangle { n: 4, data: vec![1., 2., 3., 4., 5., 6.] };
+/// kmedoids::write_matrix_market(&data, "distances.mtx").unwrap();
+/// ```
+pub fn write_matrix_market<P: AsRef<Path>>(mat: &LowerTriangle<f64>, path: P) -> Result<()> {
+	let n = mat.n;
+	if mat.data.len() != n * n.saturating_sub(1) / 2 {
+		return Err(invalid("lower triangular matrix has the wrong size"));
+	}
+	let mut out = BufWriter::new(File::create(path)?);
+	writeln!(out, "%%MatrixMarket matrix coordinate real symmetric")?;
+	writeln!(out, "{} {} {}", n, n, mat.data.len())?;
+	let mut values = mat.data.iter();
+	for i in 1..n {
+		for j in 0..i {
+			writeln!(out, "{} {} {}", i + 1, j + 1, values.next().unwrap())?;
+		}
+	}
+	out.flush()
+}
+
+/// Parse exactly `count` whitespace separated fields
+fn parse_fields<T: std::str::FromStr>(line: &str, count: usize) -> Result<Vec<T>> {
+	let fields = line
+		.split_whitespace()
+		.map(|f| f.parse::<T>().map_err(|_| invalid("invalid number")))
+		.collect::<Result<Vec<_>>>()?;
+	if fields.len() != count {
+		return Err(invalid("unexpected number of fields"));
+	}
+	Ok(fields)
+}
+
+/// Construct an invalid data error
+fn invalid(msg: &str) -> Error {
+	Error::new(ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::{ArrayAdapter, LowerTriangle}, read_matrix_market, write_matrix_market};
+
+	#[test]
+	fn test_read_matrix_market() {
+		let path = std::env::temp_dir().join(format!("kmedoids-test-{}.mtx", std::process::id()));
+		std::fs::write(
+			&path,
+			"%%MatrixMarket matrix coordinate real symmetric\n% distances of 4 points\n4 4 6\n2 1 1\n3 1 2.5\n4 1 3\n3 2 4\n4 2 5\n4 3 6\n",
+		)
+		.unwrap();
+		let data = read_matrix_market(&path).unwrap();
+		let expect = [[0., 1., 2.5, 3.], [1., 0., 4., 5.], [2.5, 4., 0., 6.], [3., 5., 6., 0.]];
+		assert_eq!(data.len(), 4, "size not as expected");
+		for (i, row) in expect.iter().enumerate() {
+			for (j, &d) in row.iter().enumerate() {
+				assert_eq!(data.get(i, j), d, "distance not as expected");
+			}
+		}
+
+		// Round trip
+		let lt = LowerTriangle { n: 5, data: vec![1., 2., 3., 4., 5., 6., 7., 8., 9., 1.5] };
+		write_matrix_market(&lt, &path).unwrap();
+		let data = read_matrix_market(&path).unwrap();
+		for i in 0..5 {
+			for j in 0..5 {
+				assert_eq!(data.get(i, j), lt.get(i, j), "distance not as expected");
+			}
+		}
+
+		std::fs::write(&path, "%%MatrixMarket matrix coordinate real general\n2 3 0\n").unwrap();
+		assert!(read_matrix_market(&path).is_err(), "non-square matrix not rejected");
+		std::fs::remove_file(&path).unwrap();
+	}
+}
@@ -25,40 +25,143 @@
 //! ```
 //! let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
 //! let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
-//! let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 100);
+//! let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 100);
 //! println!("Loss is: {}", loss);
 //! ```
+//!
+//! ## Supported types
+//!
+//! The distance type `N` must be convertible into the loss type `L` with `From<N>`.
+//! Distances should be non-negative; the loss is accumulated in `L`, so choose a type
+//! that cannot overflow for the total deviation (e.g., `i64` for small integer distances).
+//! The silhouette based methods (FasterMSC, PAMMEDSIL, silhouette) need a float loss type
+//! that also implements `From<u32>`, i.e., `f64`. The supported combinations are:
+//!
+//! | distance `N`         | total deviation `L` (PAM, FasterPAM) | silhouette `L` (FasterMSC, PAMMEDSIL, silhouette) |
+//! |----------------------|--------------------------------------|---------------------------------------------------|
+//! | `u8`, `u16`, `u32`   | `i64` or `f64`                       | `f64`                                             |
+//! | `u64`                | `i128`                               | not supported, convert to `f64` distances         |
+//! | `i32`                | `i64` or `f64`                       | `f64`                                             |
+//! | `i64`                | `i64` or `i128`                      | not supported, convert to `f64` distances         |
+//! | `f32`, `f64`         | `f64`                                | `f64`                                             |
+//!
+//! Unsupported combinations fail to compile, as `From<N>` is not implemented, e.g.:
+//! ```compile_fail
+//! let data = kmedoids::arrayadapter::LowerTriangle { n: 3, data: vec![1u64, 2, 3] };
+//! let mut meds = vec![0, 1];
+//! let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 10);
+//! ```
 mod alternating;
+mod analysis;
 pub mod arrayadapter;
+#[cfg(feature = "arrow2")]
+mod arrow_adapter;
+#[cfg(feature = "rand")]
+mod autok;
+mod canonical;
+mod constrained;
 mod fasterpam;
 mod fastpam1;
 mod fastmsc;
 mod fastermsc;
 mod dynmsc;
+mod graph_adapter;
+#[cfg(feature = "hdf5")]
+mod hdf5_adapter;
+mod index;
 mod initialization;
+mod kernel;
+mod matrixmarket;
 mod pam;
 mod pamsil;
 mod pammedsil;
+#[cfg(feature = "petgraph")]
+mod petgraph_adapter;
+#[cfg(feature = "polars")]
+mod polars_adapter;
+#[cfg(feature = "parallel")]
+mod par_alternating;
+#[cfg(feature = "parallel")]
+mod par_analysis;
+#[cfg(feature = "parallel")]
+mod par_autok;
 #[cfg(feature = "parallel")]
 mod par_fasterpam;
 #[cfg(feature = "parallel")]
+mod par_predict;
+#[cfg(feature = "parallel")]
 mod par_silhouette;
+mod predict;
+#[cfg(feature = "profile")]
+pub mod profiling;
+#[cfg(not(feature = "profile"))]
+mod profiling;
+mod result;
 mod silhouette;
 mod util;
 
-pub use crate::alternating::*;
-pub use crate::arrayadapter::ArrayAdapter;
-pub use crate::fasterpam::*;
-pub use crate::fastpam1::*;
-pub use crate::fastmsc::*;
-pub use crate::fastermsc::*;
-pub use crate::dynmsc::*;
-pub use crate::initialization::*;
-pub use crate::pam::*;
-pub use crate::pamsil::*;
-pub use crate::pammedsil::*;
-#[cfg(feature = "parallel")]
-pub use crate::par_fasterpam::*;
-#[cfg(feature = "parallel")]
-pub use crate::par_silhouette::*;
-pub use crate::silhouette::*;
+/// All public functions, types, and traits of this crate.
+///
+/// The same items are available at the crate root; `use kmedoids::prelude::*` imports
+/// them without also importing the module names such as `arrayadapter`.
+///
+/// ## Example
+/// ```
+/// use kmedoids::prelude::*;
+/// let data = LowerTriangle { n: 4, data: vec![1, 2, 4, 3, 5, 6] };
+/// let mut meds = random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = fasterpam(&data, &mut meds, 0, 100);
+/// println!("Loss is: {}", loss);
+/// ```
+pub mod prelude {
+	pub use crate::alternating::*;
+	pub use crate::analysis::*;
+	pub use crate::arrayadapter::{
+		AlignedLowerTriangle, ArrayAdapter, CachingClosureMatrix, ClosureMatrix, DynArrayAdapter, JaggedMatrix,
+		LowerTriangle, RowIter, RowView, SquareMatrix, StaticLowerTriangle, TriangleIter,
+	};
+	#[cfg(feature = "lru")]
+	pub use crate::arrayadapter::LruCachedMatrix;
+	#[cfg(feature = "arrow2")]
+	pub use crate::arrow_adapter::*;
+	#[cfg(feature = "rand")]
+	pub use crate::autok::*;
+	pub use crate::canonical::*;
+	pub use crate::constrained::*;
+	pub use crate::fasterpam::*;
+	pub use crate::fastpam1::*;
+	pub use crate::fastmsc::*;
+	pub use crate::fastermsc::*;
+	pub use crate::dynmsc::*;
+	pub use crate::graph_adapter::*;
+	#[cfg(feature = "hdf5")]
+	pub use crate::hdf5_adapter::*;
+	pub use crate::index::*;
+	pub use crate::initialization::*;
+	pub use crate::kernel::*;
+	pub use crate::matrixmarket::*;
+	pub use crate::pam::*;
+	pub use crate::pamsil::*;
+	pub use crate::pammedsil::*;
+	#[cfg(feature = "polars")]
+	pub use crate::polars_adapter::*;
+	#[cfg(feature = "parallel")]
+	pub use crate::par_alternating::*;
+	#[cfg(feature = "parallel")]
+	pub use crate::par_analysis::*;
+	#[cfg(feature = "parallel")]
+	pub use crate::par_autok::*;
+	#[cfg(feature = "parallel")]
+	pub use crate::par_fasterpam::*;
+	#[cfg(feature = "parallel")]
+	pub use crate::par_silhouette::*;
+	pub use crate::predict::*;
+	#[cfg(feature = "profile")]
+	pub use crate::profiling::{last_run_profile, RunProfile};
+	pub use crate::result::*;
+	pub use crate::silhouette::*;
+	pub use crate::util::{safe_ratio, unsafe_ratio, verify_assignment, AssignmentError, DistancePair, Reco};
+}
+
+#[doc(inline)]
+pub use crate::prelude::*;
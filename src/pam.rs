@@ -1,9 +1,12 @@
 use crate::arrayadapter::ArrayAdapter;
+use crate::profiling::{self, timed, Phase, Stopwatch};
 use crate::fasterpam::{do_swap, initial_assignment};
+use crate::result::KMedoidsResult;
 use crate::util::*;
 use core::ops::AddAssign;
 use num_traits::{Signed, Zero, FromPrimitive};
 use std::convert::From;
+use std::sync::atomic::AtomicBool;
 
 /// Run the original PAM SWAP algorithm (no BUILD, but given initial medoids).
 ///
@@ -16,7 +19,7 @@ use std::convert::From;
 /// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
 /// * `mat` - a pairwise distance matrix
 /// * `med` - the list of medoids
-/// * `maxiter` - the maximum number of iterations allowed
+/// * `maxiter` - the maximum number of iterations allowed; with 0, the given medoids are only evaluated
 ///
 /// returns a tuple containing:
 /// * the final loss
@@ -34,7 +37,7 @@ use std::convert::From;
 /// ```
 /// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
 /// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
-/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::pam_swap(&data, &mut meds, 100);
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::pam_swap(&data, &mut meds, 0, 100);
 /// println!("Loss is: {}", loss);
 /// ```
 pub fn pam_swap<M, N, L>(
@@ -49,7 +52,58 @@ where
 	M: ArrayAdapter<N>,
 {
 	let (loss, mut data) = initial_assignment(mat, med);
-	pam_optimize(mat, med, &mut data, maxiter, loss)
+	let (loss, assi, n_iter, n_swap, _) = pam_optimize(mat, med, &mut data, maxiter, loss, None, None);
+	(loss, assi, n_iter, n_swap)
+}
+
+/// Run the original PAM SWAP algorithm (no BUILD, but given initial medoids), with cancellation.
+///
+/// The flag is checked (with relaxed ordering) before each candidate is evaluated.
+/// When it is set, the current solution is returned, which is a valid clustering,
+/// but not necessarily a local optimum.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `cancel` - cancellation flag, may be set from another thread
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+/// * whether the run was cancelled
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let cancel = std::sync::atomic::AtomicBool::new(false);
+/// let (loss, assi, n_iter, n_swap, cancelled): (f64, _, _, _, _) = kmedoids::pam_swap_cancellable(&data, &mut meds, 100, &cancel);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn pam_swap_cancellable<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+	cancel: &AtomicBool,
+) -> (L, Vec<usize>, usize, usize, bool)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let (loss, mut data) = initial_assignment(mat, med);
+	pam_optimize(mat, med, &mut data, maxiter, loss, Some(cancel), None)
 }
 
 /// Run the original PAM BUILD algorithm.
@@ -68,6 +122,9 @@ where
 /// * the initial cluster assignment
 /// * the initial medoids
 ///
+/// If there are fewer than k distinct points, BUILD stops early and returns fewer
+/// than k medoids. Use [`pam_with_shortfall`] to always obtain k medoids.
+///
 /// ## Panics
 ///
 /// * panics when the dissimilarity matrix is not square
@@ -93,16 +150,129 @@ where
 	assert!(k <= n, "k must be at most N");
 	let mut meds = Vec::<usize>::with_capacity(k);
 	let mut data = Vec::<Rec<N>>::with_capacity(n);
-	let loss = pam_build_initialize(mat, &mut meds, &mut data, k);
+	let loss = pam_build_initialize(mat, &mut meds, &mut data, k, None, BuildShortfall::Truncate, None);
 	let assi = data.iter().map(|x| x.near.i as usize).collect();
 	(loss, assi, meds)
 }
 
+/// Run a greedy BUILD with a custom score function.
+///
+/// Starting with no medoids, in every step the score function is evaluated for every point
+/// that is not yet a medoid, and the candidate with the lowest score is added (on ties, the
+/// lowest index). The score function gets the candidate and the nearest medoids of every point,
+/// where `near.i` is the position in the list of medoids chosen so far, and returns the change
+/// of the loss when adding the candidate (in the first step, the loss itself). BUILD stops
+/// early when no candidate has a negative score, as PAM BUILD does for duplicate points.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the score
+/// * type `F` - the score function
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `score_fn` - the score of a candidate given the nearest medoids of every point,
+///   e.g., [`pam_build_score`] or [`crate::pammedsil_build_score`]
+///
+/// returns the initial medoids
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let meds = kmedoids::custom_build(&data, 2, kmedoids::pam_build_score::<_, _, i64>(&data));
+/// println!("Medoids are: {:?}", meds);
+/// ```
+pub fn custom_build<M, N, L, F>(mat: &M, k: usize, score_fn: F) -> Vec<usize>
+where
+	N: Zero + PartialOrd + Copy,
+	L: Zero + PartialOrd + Copy,
+	M: ArrayAdapter<N>,
+	F: Fn(usize, &[Reco<N>]) -> L,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
+	assert!(k <= n, "k must be at most N");
+	let mut meds = Vec::<usize>::with_capacity(k);
+	let mut data = vec![Reco::<N>::empty(); n];
+	let mut is_med = vec![false; n];
+	for l in 0..k {
+		let mut best = (L::zero(), usize::MAX);
+		for i in (0..n).filter(|&i| !is_med[i]) {
+			let score = score_fn(i, &data);
+			if best.1 == usize::MAX || score < best.0 {
+				best = (score, i);
+			}
+		}
+		if l > 0 && best.0 >= L::zero() {
+			break; // No further improvements - duplicates etc.
+		}
+		for (j, recj) in data.iter_mut().enumerate() {
+			let dj = if j == best.1 { N::zero() } else { mat.get(j, best.1) };
+			if recj.near.is_sentinel() || dj < recj.near.d {
+				recj.third = recj.seco;
+				recj.seco = recj.near;
+				recj.near = DistancePair::new(l as u32, dj);
+			} else if recj.seco.is_sentinel() || dj < recj.seco.d {
+				recj.third = recj.seco;
+				recj.seco = DistancePair::new(l as u32, dj);
+			} else if recj.third.is_sentinel() || dj < recj.third.d {
+				recj.third = DistancePair::new(l as u32, dj);
+			}
+		}
+		is_med[best.1] = true;
+		meds.push(best.1);
+	}
+	meds
+}
+
+/// The score function of the original PAM BUILD, for use with [`custom_build`].
+///
+/// The score is the change of the total deviation when adding the candidate as medoid,
+/// hence `custom_build` with this score yields the same medoids as [`pam_build`].
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+///
+/// returns the score function
+pub fn pam_build_score<M, N, L>(mat: &M) -> impl Fn(usize, &[Reco<N>]) -> L + '_
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N>,
+	M: ArrayAdapter<N>,
+{
+	move |i, data| {
+		let mut sum = if data[i].near.is_sentinel() { L::zero() } else { -L::from(data[i].near.d) };
+		for (j, dj) in data.iter().enumerate() {
+			if j != i {
+				let d = mat.get(j, i);
+				if dj.near.is_sentinel() {
+					sum += L::from(d);
+				} else if d < dj.near.d {
+					sum += L::from(d) - L::from(dj.near.d)
+				}
+			}
+		}
+		sum
+	}
+}
+
 /// Run the original PAM algorithm (BUILD and SWAP).
 ///
 /// This is provided for academic reasons to see the performance difference.
 /// Quality-wise, FasterPAM is comparable to PAM, and much faster.
 ///
+/// If there are fewer than k distinct points, BUILD stops early and fewer than k
+/// medoids are returned. Use [`pam_with_shortfall`] to always obtain k medoids.
+///
 /// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
 /// * type `N` - number data type such as `u32` or `f64`
 /// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
@@ -142,11 +312,549 @@ where
 	assert!(k <= n, "k must be at most N");
 	let mut meds = Vec::<usize>::with_capacity(k);
 	let mut data = Vec::<Rec<N>>::with_capacity(n);
-	let loss = pam_build_initialize(mat, &mut meds, &mut data, k);
-	let (nloss, assi, n_iter, n_swap) = pam_optimize(mat, &mut meds, &mut data, maxiter, loss);
+	let loss = pam_build_initialize(mat, &mut meds, &mut data, k, None, BuildShortfall::Truncate, None);
+	let (nloss, assi, n_iter, n_swap, _) = pam_optimize(mat, &mut meds, &mut data, maxiter, loss, None, None);
 	(nloss, assi, meds, n_iter, n_swap) // also return medoids
 }
 
+/// Run the original PAM algorithm (BUILD and SWAP), with cancellation.
+///
+/// The flag is checked (with relaxed ordering) before each candidate is evaluated,
+/// in both BUILD and SWAP. When it is set, the current solution is returned.
+/// If the run is cancelled during BUILD, fewer than k medoids may be returned.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `cancel` - cancellation flag, may be set from another thread
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the final medoids
+/// * the number of iterations needed
+/// * the number of swaps performed
+/// * whether the run was cancelled
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let cancel = std::sync::atomic::AtomicBool::new(false);
+/// let (loss, assi, meds, n_iter, n_swap, cancelled): (f64, _, _, _, _, _) = kmedoids::pam_cancellable(&data, 2, 100, &cancel);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn pam_cancellable<M, N, L>(
+	mat: &M,
+	k: usize,
+	maxiter: usize,
+	cancel: &AtomicBool,
+) -> (L, Vec<usize>, Vec<usize>, usize, usize, bool)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
+	assert!(k <= n, "k must be at most N");
+	let mut meds = Vec::<usize>::with_capacity(k);
+	let mut data = Vec::<Rec<N>>::with_capacity(n);
+	let loss = pam_build_initialize(mat, &mut meds, &mut data, k, Some(cancel), BuildShortfall::Truncate, None);
+	if is_cancelled(Some(cancel)) {
+		let assi = data.iter().map(|x| x.near.i as usize).collect();
+		return (loss, assi, meds, 0, 0, true);
+	}
+	let (nloss, assi, n_iter, n_swap, cancelled) = pam_optimize(mat, &mut meds, &mut data, maxiter, loss, Some(cancel), None);
+	(nloss, assi, meds, n_iter, n_swap, cancelled) // also return medoids
+}
+
+/// Handling of BUILD finding no further improvement before k medoids are chosen.
+///
+/// This happens when the data has fewer than k distinct points, e.g., many duplicates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BuildShortfall {
+	/// stop early, and return fewer than k medoids (as [`pam`] and [`pammedsil`](crate::pammedsil))
+	#[default]
+	Truncate,
+	/// continue with the point farthest from its nearest medoid, to always return k medoids
+	FillFarthest,
+}
+
+/// Run the original PAM algorithm (BUILD and SWAP), with a choice of what to do
+/// when BUILD finds no further improvement before choosing k medoids.
+///
+/// With [`BuildShortfall::Truncate`], the result is the same as [`pam`], and the actual
+/// number of clusters is the length of the medoid list, which can be less than k.
+/// With [`BuildShortfall::FillFarthest`], BUILD continues with the point farthest from
+/// its nearest medoid (possibly a duplicate of a medoid), and exactly k medoids are returned.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `shortfall` - what to do when BUILD stops early
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the final medoids
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,0,1,1],[0,0,1,1],[1,1,0,0],[1,1,0,0]]);
+/// let shortfall = kmedoids::BuildShortfall::FillFarthest;
+/// let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = kmedoids::pam_with_shortfall(&data, 3, 100, shortfall);
+/// assert_eq!(meds.len(), 3);
+/// ```
+pub fn pam_with_shortfall<M, N, L>(
+	mat: &M,
+	k: usize,
+	maxiter: usize,
+	shortfall: BuildShortfall,
+) -> (L, Vec<usize>, Vec<usize>, usize, usize)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
+	assert!(k <= n, "k must be at most N");
+	let mut meds = Vec::<usize>::with_capacity(k);
+	let mut data = Vec::<Rec<N>>::with_capacity(n);
+	let loss = pam_build_initialize(mat, &mut meds, &mut data, k, None, shortfall, None);
+	let (nloss, assi, n_iter, n_swap, _) = pam_optimize(mat, &mut meds, &mut data, maxiter, loss, None, None);
+	(nloss, assi, meds, n_iter, n_swap) // also return medoids
+}
+
+/// Run a randomized PAM SWAP, which evaluates only some random swaps in each iteration.
+///
+/// In each iteration, `n_candidates` random (medoid, non-medoid) pairs are evaluated
+/// instead of all O(nk), and the best improving swap among them is performed. This needs
+/// only O(n · n_candidates) time per iteration. Only improving swaps are accepted, but
+/// the random neighborhood changes which local optimum is reached. When the sampled pairs
+/// contain no improving swap, all pairs are evaluated to check for a local optimum, where
+/// the iterations stop. Hence, unless `maxiter` is reached first, the result is a local
+/// optimum of PAM SWAP. With `n_candidates` of at least n·k, all pairs are sampled, and the
+/// result is the same as with [`pam_swap`].
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * type `R` - random number generator type
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `n_candidates` - the number of pairs to evaluate in each iteration
+/// * `rng` - random number generator for choosing the pairs
+///
+/// returns the clustering result
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = vec![0, 1];
+/// let result: kmedoids::KMedoidsResult<i64> = kmedoids::pam_random_swap(&data, &mut meds, 10, 3, &mut rand::thread_rng());
+/// println!("Loss is: {}", result.loss);
+/// ```
+#[cfg(feature = "rand")]
+pub fn pam_random_swap<M, N, L, R>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+	n_candidates: usize,
+	rng: &mut R,
+) -> KMedoidsResult<L>
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+	R: rand::Rng,
+{
+	let (n, k) = (mat.len(), med.len());
+	let (mut loss, mut data) = initial_assignment(mat, med);
+	if k == 1 {
+		let (loss, assi, n_iter, n_swap, _) = pam_optimize(mat, med, &mut data, maxiter, loss, None, None);
+		return KMedoidsResult::new(loss, assi, med.clone(), n_iter, n_swap);
+	}
+	let (all, n_candidates) = (n * k, n_candidates.min(n * k));
+	let (mut n_swaps, mut iter) = (0, 0);
+	while iter < maxiter {
+		iter += 1;
+		// the pairs as j * k + m, in the scan order of PAM SWAP
+		let mut pairs = rand::seq::index::sample(rng, all, n_candidates).into_vec();
+		pairs.sort_unstable();
+		let mut best: (L, _, _) = best_swap_of_pairs(mat, med, &data, pairs.into_iter());
+		if best.0 >= L::zero() && n_candidates < all {
+			// no improving swap sampled, check all pairs for a local optimum
+			best = best_swap_of_pairs(mat, med, &data, 0..all);
+		}
+		if best.0 >= L::zero() {
+			break; // No improvement, or NaN.
+		}
+		n_swaps += 1;
+		let newloss = do_swap(mat, med, &mut data, best.1, best.2);
+		if newloss >= loss {
+			break; // Probably numerically unstable now.
+		}
+		loss = newloss;
+	}
+	let assi = data.iter().map(|x| x.near.i as usize).collect();
+	KMedoidsResult::new(loss, assi, med.clone(), iter, n_swaps)
+}
+
+/// Find the best swap among the pairs j * k + m, in the given order
+#[cfg(feature = "rand")]
+fn best_swap_of_pairs<M, N, L>(mat: &M, med: &[usize], data: &[Rec<N>], pairs: impl Iterator<Item = usize>) -> (L, usize, usize)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let k = med.len();
+	let mut best = (L::zero(), k, usize::MAX);
+	for p in pairs {
+		let (j, m) = (p / k, p % k);
+		if j == med[data[j].near.i as usize] {
+			continue; // This already is a medoid
+		}
+		let change = swap_change_pam(mat, data, m, j);
+		if change < best.0 {
+			best = (change, m, j);
+		}
+	}
+	best
+}
+
+/// Run a PAM SWAP that chooses the swaps to evaluate with Thompson sampling.
+///
+/// Each (medoid, non-medoid) pair keeps the count, running mean and variance of its observed
+/// gains (the decrease of the loss when swapping). In each iteration, a gain is sampled for
+/// every pair from a Gaussian posterior with the pair's mean and variance, shrunk towards the
+/// mean and variance of all observations, and only the `budget_per_iter` pairs with the largest
+/// samples are evaluated, each in O(n) time. This focuses the evaluations on the pairs that
+/// were promising before, while unobserved pairs are still explored. The best improving swap
+/// found is performed. The statistics are kept after a swap, as the gains of most pairs change little.
+///
+/// When the sampled pairs contain no improving swap, one full SWAP iteration is run to check
+/// for a local optimum, which also updates the statistics of all pairs. Hence, unless `maxiter` is reached first, the result is a local optimum
+/// of PAM SWAP, not necessarily the same one as with [`pam_swap`].
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * type `R` - random number generator type
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `budget_per_iter` - the number of pairs to evaluate in each iteration
+/// * `rng` - random number generator for Thompson sampling
+///
+/// returns the clustering result
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+/// * panics when budget_per_iter is 0
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = vec![0, 1];
+/// let result: kmedoids::KMedoidsResult<i64> = kmedoids::pam_bandit_swap(&data, &mut meds, 10, 2, &mut rand::thread_rng());
+/// println!("Loss is: {}", result.loss);
+/// ```
+#[cfg(feature = "rand")]
+pub fn pam_bandit_swap<M, N, L, R>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+	budget_per_iter: usize,
+	rng: &mut R,
+) -> KMedoidsResult<L>
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + num_traits::ToPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+	R: rand::Rng,
+{
+	assert!(budget_per_iter > 0, "budget_per_iter must be positive");
+	let (n, k) = (mat.len(), med.len());
+	let (mut loss, mut data) = initial_assignment(mat, med);
+	if k == 1 {
+		let (loss, assi, n_iter, n_swap, _) = pam_optimize(mat, med, &mut data, maxiter, loss, None, None);
+		return KMedoidsResult::new(loss, assi, med.clone(), n_iter, n_swap);
+	}
+	// count, mean and sum of squared deviations of the gains of pair j * k + m (Welford)
+	let mut stats = vec![(0u32, 0f64, 0f64); n * k];
+	let mut global = (0u32, 0f64, 0f64);
+	let welford = |s: &mut (u32, f64, f64), x: f64| {
+		s.0 += 1;
+		let delta = x - s.1;
+		s.1 += delta / s.0 as f64;
+		s.2 += delta * (x - s.1);
+	};
+	let (mut n_swaps, mut iter) = (0, 0);
+	let mut samples = Vec::with_capacity(n * k);
+	while iter < maxiter {
+		iter += 1;
+		let gvar = if global.0 > 1 { global.2 / (global.0 - 1) as f64 } else { 1. };
+		samples.clear();
+		for (p, s) in stats.iter().enumerate() {
+			let j = p / k;
+			if j == med[data[j].near.i as usize] {
+				continue; // This already is a medoid
+			}
+			let var = if s.0 > 1 { s.2 / (s.0 - 1) as f64 } else { gvar };
+			let mean = (global.1 + s.0 as f64 * s.1) / (s.0 + 1) as f64;
+			// Box-Muller transform for a standard normal sample
+			let z = (-2. * (1. - rng.gen::<f64>()).ln()).sqrt() * (std::f64::consts::TAU * rng.gen::<f64>()).cos();
+			samples.push((mean + z * (var / (s.0 + 1) as f64).sqrt(), p));
+		}
+		let budget = budget_per_iter.min(samples.len());
+		if budget < samples.len() {
+			samples.select_nth_unstable_by(budget, |a, b| b.0.total_cmp(&a.0));
+		}
+		let mut best = (L::zero(), k, usize::MAX);
+		for &(_, p) in samples[..budget].iter() {
+			let (j, m) = (p / k, p % k);
+			let change: L = swap_change_pam(mat, &data, m, j);
+			let gain = -change.to_f64().unwrap();
+			welford(&mut stats[p], gain);
+			welford(&mut global, gain);
+			if change < best.0 {
+				best = (change, m, j);
+			}
+		}
+		if best.0 >= L::zero() {
+			// no improvement among the samples, check for a local optimum
+			for j in 0..n {
+				if j == med[data[j].near.i as usize] {
+					continue; // This already is a medoid
+				}
+				for m in 0..k {
+					let change: L = swap_change_pam(mat, &data, m, j);
+					let gain = -change.to_f64().unwrap();
+					welford(&mut stats[j * k + m], gain);
+					welford(&mut global, gain);
+					if change < best.0 {
+						best = (change, m, j);
+					}
+				}
+			}
+			if best.0 >= L::zero() {
+				break; // local optimum
+			}
+		}
+		n_swaps += 1;
+		let newloss = do_swap(mat, med, &mut data, best.1, best.2);
+		if newloss >= loss {
+			break; // Probably numerically unstable now.
+		}
+		loss = newloss;
+	}
+	let assi = data.iter().map(|x| x.near.i as usize).collect();
+	KMedoidsResult::new(loss, assi, med.clone(), iter, n_swaps)
+}
+
+/// Run PAM SWAP with random restarts, to escape from local optima.
+///
+/// PAM SWAP is run as usual, but when it makes no improving swap, or when `patience`
+/// iterations in a row did not improve on the best solution seen so far, a random medoid
+/// is replaced with a random non-medoid, and SWAP continues from there. This is repeated at
+/// most `max_restarts` times. The best solution seen is returned, so the result is never
+/// worse than with [`pam_swap`], which performs the same swaps until the first restart.
+/// The number of iterations includes those after the restarts, while the number of swaps
+/// does not include the random replacements.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * type `R` - random number generator type
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed, including those after restarts
+/// * `patience` - the number of iterations without a new best solution before a restart
+/// * `max_restarts` - the maximum number of restarts
+/// * `rng` - random number generator for the restarts
+///
+/// returns the clustering result
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+/// * panics when patience is 0
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = vec![0, 1];
+/// let result: kmedoids::KMedoidsResult<i64> = kmedoids::pam_swap_with_restarts(&data, &mut meds, 100, 3, 5, &mut rand::thread_rng());
+/// println!("Loss is: {}", result.loss);
+/// ```
+#[cfg(feature = "rand")]
+pub fn pam_swap_with_restarts<M, N, L, R>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+	patience: usize,
+	max_restarts: usize,
+	rng: &mut R,
+) -> KMedoidsResult<L>
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+	R: rand::Rng,
+{
+	assert!(patience > 0, "patience must be positive");
+	let (n, k) = (mat.len(), med.len());
+	let (mut loss, mut data) = initial_assignment(mat, med);
+	if k == 1 || k == n {
+		// optimal, or no non-medoid to restart with
+		let (loss, assi, n_iter, n_swap, _) = pam_optimize(mat, med, &mut data, maxiter, loss, None, None);
+		return KMedoidsResult::new(loss, assi, med.clone(), n_iter, n_swap);
+	}
+	debug_assert_assignment(mat, med, data.as_slice());
+	let mut best_seen = (loss, med.clone());
+	let (mut n_swaps, mut iter, mut stalls, mut restarts) = (0, 0, 0, 0);
+	while iter < maxiter {
+		iter += 1;
+		let mut best = (L::zero(), k, usize::MAX);
+		for j in 0..n {
+			if j == med[data[j].near.i as usize] {
+				continue; // This already is a medoid
+			}
+			let (change, b) = find_best_swap_pam(mat, med, &data, j);
+			if change >= best.0 {
+				continue; // No improvement
+			}
+			best = (change, b, j);
+		}
+		let mut converged = true;
+		if best.0 < L::zero() {
+			n_swaps += 1;
+			let newloss = do_swap(mat, med, &mut data, best.1, best.2);
+			converged = newloss >= loss; // Probably numerically unstable now.
+			loss = newloss;
+		}
+		if loss < best_seen.0 {
+			best_seen = (loss, med.clone());
+			stalls = 0;
+		} else {
+			stalls += 1;
+		}
+		if converged || stalls >= patience {
+			if restarts == max_restarts {
+				break;
+			}
+			// replace a random medoid with a random non-medoid
+			restarts += 1;
+			let m = rng.gen_range(0..k);
+			let j = loop {
+				let j = rng.gen_range(0..n);
+				if !med.contains(&j) {
+					break j;
+				}
+			};
+			loss = do_swap(mat, med, &mut data, m, j);
+			stalls = 0;
+		}
+	}
+	if best_seen.0 < loss {
+		// restore the best solution
+		*med = best_seen.1;
+		(loss, data) = initial_assignment(mat, med);
+	}
+	let assi = data.iter().map(|x| x.near.i as usize).collect();
+	KMedoidsResult::new(loss, assi, med.clone(), iter, n_swaps)
+}
+
+/// Run the original PAM algorithm (BUILD and SWAP), where only the given candidates
+/// can become medoids.
+///
+/// This is useful when the medoids must satisfy external criteria, e.g., be part of
+/// a validation set. Both BUILD and SWAP only consider the candidates, while all points
+/// are assigned to their nearest medoid. With all points as candidates, the result is
+/// the same as [`pam`].
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `candidates` - the points allowed as medoids
+/// * `k` - the number of medoids to pick
+/// * `maxiter` - the maximum number of iterations allowed
+///
+/// returns the clustering result
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a candidate index is not less than N
+/// * panics when k is 0 or larger than the number of candidates
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let result: kmedoids::KMedoidsResult<i64> = kmedoids::pam_with_medoid_candidates(&data, &[1, 2, 3], 2, 100);
+/// assert!(result.medoids.iter().all(|m| [1, 2, 3].contains(m)));
+/// ```
+pub fn pam_with_medoid_candidates<M, N, L>(mat: &M, candidates: &[usize], k: usize, maxiter: usize) -> KMedoidsResult<L>
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(candidates.iter().all(|&c| c < n), "invalid candidate index, must be less than N");
+	let mut allowed = vec![false; n];
+	candidates.iter().for_each(|&c| allowed[c] = true);
+	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
+	assert!(k <= allowed.iter().filter(|&&a| a).count(), "k must be at most the number of candidates");
+	let mut meds = Vec::<usize>::with_capacity(k);
+	let mut data = Vec::<Rec<N>>::with_capacity(n);
+	let loss = pam_build_initialize(mat, &mut meds, &mut data, k, None, BuildShortfall::Truncate, Some(&allowed));
+	let (nloss, assi, n_iter, n_swap, _) = pam_optimize(mat, &mut meds, &mut data, maxiter, loss, None, Some(&allowed));
+	KMedoidsResult::new(nloss, assi, meds, n_iter, n_swap)
+}
+
 /// Main optimization function of PAM, not exposed (use pam_swap or pam)
 fn pam_optimize<M, N, L>(
 	mat: &M,
@@ -154,37 +862,67 @@ fn pam_optimize<M, N, L>(
 	data: &mut Vec<Rec<N>>,
 	maxiter: usize,
 	mut loss: L,
-) -> (L, Vec<usize>, usize, usize)
+	cancel: Option<&AtomicBool>,
+	candidates: Option<&[bool]>,
+) -> (L, Vec<usize>, usize, usize, bool)
 where
 	N: Zero + PartialOrd + Copy,
 	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
 	M: ArrayAdapter<N>,
 {
+	profiling::reset();
 	let (n, k) = (mat.len(), med.len());
 	if k == 1 {
 		let assi = vec![0; n];
+		if maxiter == 0 {
+			return (single_medoid_loss(mat, med[0]), assi, 0, 0, false); // only evaluate the given medoid
+		}
+		if let Some(cand) = candidates {
+			// the best candidate for the single cluster
+			let first = med[0];
+			let mut best = (single_medoid_loss::<M, N, L>(mat, first), first);
+			for j in (0..n).filter(|&j| cand[j]) {
+				let lossj = single_medoid_loss(mat, j);
+				if lossj < best.0 {
+					best = (lossj, j);
+				}
+			}
+			med[0] = best.1;
+			return (best.0, assi, 1, if best.1 != first { 1 } else { 0 }, false);
+		}
 		let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, med, 0);
-		return (loss, assi, 1, if swapped { 1 } else { 0 });
+		return (loss, assi, 1, if swapped { 1 } else { 0 }, false);
 	}
 	debug_assert_assignment(mat, med, data);
-	let (mut n_swaps, mut iter) = (0, 0);
+	let (mut n_swaps, mut iter, mut cancelled) = (0, 0, false);
 	while iter < maxiter {
 		iter += 1;
+		let _iteration = Stopwatch::start(Phase::Iteration);
 		let mut best = (L::zero(), k, usize::MAX);
 		for j in 0..n {
+			if is_cancelled(cancel) {
+				cancelled = true;
+				break;
+			}
 			if j == med[data[j].near.i as usize] {
 				continue; // This already is a medoid
 			}
-			let (change, b) = find_best_swap_pam(mat, med, data, j);
+			if matches!(candidates, Some(c) if !c[j]) {
+				continue; // Not allowed as medoid
+			}
+			let (change, b) = timed(Phase::FindBestSwap, || find_best_swap_pam(mat, med, data, j));
 			if change >= best.0 {
 				continue; // No improvement
 			}
 			best = (change, b, j);
 		}
+		if cancelled {
+			break; // keep the current, consistent, solution
+		}
 		if best.0 < L::zero() {
 			n_swaps += 1;
 			// perform the swap
-			let newloss = do_swap(mat, med, data, best.1, best.2);
+			let newloss = timed(Phase::DoSwap, || do_swap(mat, med, data, best.1, best.2));
 			if newloss >= loss {
 				break; // Probably numerically unstable now.
 			}
@@ -194,7 +932,7 @@ where
 		}
 	}
 	let assi = data.iter().map(|x| x.near.i as usize).collect();
-	(loss, assi, iter, n_swaps)
+	(loss, assi, iter, n_swaps, cancelled)
 }
 
 /// Find the best swap for object j - slower PAM version
@@ -205,29 +943,9 @@ where
 	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
 	M: ArrayAdapter<N>,
 {
-	let recj = &data[j];
 	let mut best = (L::zero(), usize::MAX);
 	for (m, _) in med.iter().enumerate() {
-		let mut acc: L = -L::from(recj.near.d); // j becomes medoid
-		for (o, reco) in data.iter().enumerate() {
-			if o == j {
-				continue;
-			}
-			let doj = mat.get(o, j);
-			// Current medoid is being replaced:
-			if reco.near.i as usize == m {
-				if doj < reco.seco.d {
-					// Assign to new medoid:
-					acc += L::from(doj) - L::from(reco.near.d)
-				} else {
-					// Assign to second nearest instead:
-					acc += L::from(reco.seco.d) - L::from(reco.near.d)
-				}
-			} else if doj < reco.near.d {
-				// new mediod is closer:
-				acc += L::from(doj) - L::from(reco.near.d)
-			} // else no change
-		}
+		let acc = swap_change_pam(mat, data, m, j);
 		if acc < best.0 {
 			best = (acc, m);
 		}
@@ -235,12 +953,46 @@ where
 	best
 }
 
+/// Change in the loss when replacing medoid m with j
+#[inline]
+fn swap_change_pam<M, N, L>(mat: &M, data: &[Rec<N>], m: usize, j: usize) -> L
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display,
+	M: ArrayAdapter<N>,
+{
+	let mut acc: L = -L::from(data[j].near.d); // j becomes medoid
+	for (o, reco) in data.iter().enumerate() {
+		if o == j {
+			continue;
+		}
+		let doj = mat.get(o, j);
+		// Current medoid is being replaced:
+		if reco.near.i as usize == m {
+			if doj < reco.seco.d {
+				// Assign to new medoid:
+				acc += L::from(doj) - L::from(reco.near.d)
+			} else {
+				// Assign to second nearest instead:
+				acc += L::from(reco.seco.d) - L::from(reco.near.d)
+			}
+		} else if doj < reco.near.d {
+			// new mediod is closer:
+			acc += L::from(doj) - L::from(reco.near.d)
+		} // else no change
+	}
+	acc
+}
+
 /// Not exposed. Use pam_build or pam.
 pub(crate) fn pam_build_initialize<M, N, L>(
 	mat: &M,
 	meds: &mut Vec<usize>,
 	data: &mut Vec<Rec<N>>,
 	k: usize,
+	cancel: Option<&AtomicBool>,
+	shortfall: BuildShortfall,
+	candidates: Option<&[bool]>,
 ) -> L
 where
 	N: Zero + PartialOrd + Copy,
@@ -249,28 +1001,35 @@ where
 {
 	let n = mat.len();
 	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	let allowed = |i: usize| match candidates { Some(c) => c[i], None => true };
 	// choose first medoid
-	let mut best = (L::zero(), k);
-	for i in 0..n {
+	let mut best = (L::zero(), usize::MAX);
+	for i in (0..n).filter(|&i| allowed(i)) {
 		let mut sum = L::zero();
 		for j in 0..n {
 			if j != i {
 				sum += L::from(mat.get(j, i));
 			}
 		}
-		if i == 0 || sum < best.0 {
+		if best.1 == usize::MAX || sum < best.0 {
 			best = (sum, i);
 		}
 	}
 	let mut loss = best.0;
 	meds.push(best.1);
 	for j in 0..n {
-		data.push(Rec::new(0, mat.get(j, best.1), u32::MAX, N::zero()));
+		data.push(Rec { near: DistancePair::new(0, mat.get(j, best.1)), seco: DistancePair::sentinel() });
 	}
 	// choose remaining medoids
 	for l in 1..k {
-		best = (L::zero(), k);
+		best = (L::zero(), usize::MAX);
 		for (i, _) in data.iter().enumerate() {
+			if is_cancelled(cancel) {
+				return loss; // keep the medoids chosen so far
+			}
+			if !allowed(i) {
+				continue;
+			}
 			let mut sum = -L::from(data[i].near.d);
 			for (j, dj) in data.iter().enumerate() {
 				if j != i {
@@ -280,11 +1039,17 @@ where
 					}
 				}
 			}
-			if i == 0 || sum < best.0 {
+			if best.1 == usize::MAX || sum < best.0 {
 				best = (sum, i);
 			}
 		}
-		if best.0 >= L::zero() { break; } // No further improvements - duplicates etc.
+		if best.0 >= L::zero() {
+			// No further improvements - duplicates etc.
+			match shortfall {
+				BuildShortfall::Truncate => break,
+				BuildShortfall::FillFarthest => best.1 = farthest_non_medoid(meds, data.iter().map(|r| r.near.d)),
+			}
+		}
 		// Update assignments:
 		loss = L::zero();
 		for (j, recj) in data.iter_mut().enumerate() {
@@ -297,7 +1062,7 @@ where
 			if dj < recj.near.d {
 				recj.seco = recj.near;
 				recj.near = DistancePair::new(l as u32, dj);
-			} else if recj.seco.i == u32::MAX || dj < recj.seco.d {
+			} else if recj.seco.is_sentinel() || dj < recj.seco.d {
 				recj.seco = DistancePair::new(l as u32, dj);
 			}
 			loss += L::from(recj.near.d);
@@ -311,8 +1076,11 @@ where
 mod tests {
 	// TODO: use a larger, much more interesting example.
 	use crate::{
-		arrayadapter::LowerTriangle, pam, pam_build, pam_swap, silhouette, util::assert_array,
+		canonicalize, arrayadapter::{ArrayAdapter, LowerTriangle}, assign_to_medoids, custom_build, pam, pam_bandit_swap, pam_build, pam_build_score, pam_random_swap, pam_swap, pam_swap_with_restarts,
+		pam_swap_cancellable, pam_cancellable, pam_with_medoid_candidates, pam_with_shortfall, silhouette, BuildShortfall, KMedoidsResult, util::assert_array,
 	};
+	use rand::{rngs::StdRng, Rng, SeedableRng};
+	use std::sync::atomic::{AtomicBool, Ordering};
 
 	#[test]
 	fn test_pam_swap_simple() {
@@ -321,7 +1089,8 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![0, 1];
-		let (loss, assi, n_iter, n_swap): (i64, _, _, _) = pam_swap(&data, &mut meds, 10);
+		let (loss, mut assi, n_iter, n_swap): (i64, _, _, _) = pam_swap(&data, &mut meds, 0, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		assert_eq!(loss, 4, "loss not as expected");
 		assert_eq!(n_swap, 1, "swaps not as expected");
@@ -337,7 +1106,8 @@ mod tests {
 			n: 5,
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
-		let (loss, assi, meds): (i64, _, _) = pam_build(&data, 2);
+		let (loss, mut assi, mut meds): (i64, _, _) = pam_build(&data, 2);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		assert_eq!(loss, 4, "loss not as expected");
 		assert_array(assi, vec![0, 0, 0, 1, 1], "assignment not as expected");
@@ -345,13 +1115,120 @@ mod tests {
 		assert_eq!(sil, 0.7522494172494172, "Silhouette not as expected");
 	}
 
+	#[test]
+	fn test_custom_build_pam_build_score() {
+		let mut rng = StdRng::seed_from_u64(1);
+		let data = LowerTriangle { n: 30, data: (0..435).map(|_| rng.gen_range(1..20)).collect::<Vec<u32>>() };
+		for k in 1..=6 {
+			let (_, _, meds): (i64, _, _) = pam_build(&data, k);
+			assert_array(custom_build(&data, k, pam_build_score::<_, _, i64>(&data)), meds, "medoids not as expected");
+		}
+		// only two distinct points, BUILD stops early
+		let data = LowerTriangle { n: 6, data: vec![0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1, 1, 0, 0] };
+		let (_, _, meds): (i64, _, _) = pam_build(&data, 3);
+		assert_eq!(meds.len(), 2, "number of medoids not as expected");
+		assert_array(custom_build(&data, 3, pam_build_score::<_, _, i64>(&data)), meds, "medoids not as expected");
+	}
+
+	#[test]
+	fn test_pam_random_swap() {
+		use crate::util::{assert_monotone_decreasing, loss_history};
+		let mut rng = StdRng::seed_from_u64(2);
+		let data = LowerTriangle { n: 40, data: (0..780).map(|_| rng.gen_range(1..100)).collect::<Vec<u32>>() };
+		let k = 4;
+		// sampling all pairs is PAM SWAP
+		let mut meds = (0..k).collect::<Vec<_>>();
+		let (loss, assi, n_iter, n_swap): (i64, _, _, _) = pam_swap(&data, &mut meds, 0, 100);
+		let mut rmeds = (0..k).collect::<Vec<_>>();
+		let result = pam_random_swap(&data, &mut rmeds, 100, 40 * k, &mut StdRng::seed_from_u64(1));
+		assert_eq!(result, KMedoidsResult::new(loss, assi, meds, n_iter, n_swap), "result not as expected");
+		assert_eq!(rmeds, result.medoids, "medoids not as expected");
+		// fewer pairs, starting from BUILD: reproducible with a seed, and consistent
+		let (bloss, _, bmeds): (i64, _, _) = pam_build(&data, k);
+		let run = |maxiter, seed| pam_random_swap::<_, _, i64, _>(&data, &mut bmeds.clone(), maxiter, 20, &mut StdRng::seed_from_u64(seed));
+		let result = run(100, 3);
+		assert_eq!(result, run(100, 3), "not reproducible");
+		let (eloss, _): (i64, _) = assign_to_medoids(&data, &result.medoids);
+		assert_eq!(result.loss, eloss, "loss not consistent");
+		assert!(result.n_swap > 0 && result.loss < bloss, "no improvement over BUILD");
+		// the loss never increases, and the sampling stops in a local optimum of PAM SWAP
+		let history = loss_history(|i| { let r = run(i, 3); (r.loss, r.n_iter) }, "random swap");
+		assert_monotone_decreasing(&history, "random swap");
+		assert!(result.n_iter < 100, "not stopped at the local optimum");
+		let (sloss, _, _, sn_swap): (i64, _, _, _) = pam_swap(&data, &mut result.medoids.clone(), 0, 100);
+		assert_eq!((sloss, sn_swap), (result.loss, 0), "not a local optimum");
+	}
+
+	#[test]
+	fn test_pam_swap_with_restarts() {
+		// uniform random points, where SWAP ends in different local optima
+		let mut rng = StdRng::seed_from_u64(5);
+		let points = (0..100).map(|_| (rng.gen::<f64>(), rng.gen::<f64>())).collect::<Vec<_>>();
+		let data = LowerTriangle { n: 100, data: (1..100).flat_map(|i| (0..i).map(move |j| (i, j))).map(|(i, j)| {
+			let (a, b): (&(f64, f64), &(f64, f64)) = (&points[i], &points[j]);
+			((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+		}).collect::<Vec<f64>>() };
+		let (mut total, mut rtotal) = (0., 0.);
+		for seed in 0..10 {
+			let mut rng = StdRng::seed_from_u64(seed);
+			let init = rand::seq::index::sample(&mut rng, 100, 10).into_vec();
+			let (loss, _, _, _): (f64, _, _, _) = pam_swap(&data, &mut init.clone(), 0, 100);
+			let mut meds = init.clone();
+			let result: KMedoidsResult<f64> = pam_swap_with_restarts(&data, &mut meds, 1000, 3, 30, &mut rng);
+			assert_eq!(meds, result.medoids, "medoids not as expected");
+			let (eloss, eassi): (f64, _) = assign_to_medoids(&data, &meds);
+			assert!((result.loss - eloss).abs() < 1e-9, "loss not consistent");
+			assert_array(result.assignment.clone(), eassi, "assignment not as expected");
+			assert!(result.loss <= loss + 1e-9, "restarts worse than SWAP: {} vs. {}", result.loss, loss);
+			(total, rtotal) = (total + loss, rtotal + result.loss);
+		}
+		assert!(rtotal <= total, "restarts worse on average: {} vs. {}", rtotal / 10., total / 10.);
+		assert!(rtotal < total, "restarts not better on average: {} vs. {}", rtotal / 10., total / 10.);
+		// without restarts, the same as PAM SWAP
+		let mut meds = (0..10).collect::<Vec<_>>();
+		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = pam_swap(&data, &mut meds.clone(), 0, 100);
+		let result = pam_swap_with_restarts(&data, &mut meds, 100, 1, 0, &mut rng);
+		assert_eq!((result.loss, result.assignment, result.n_iter, result.n_swap), (loss, assi, n_iter, n_swap), "result not as expected");
+	}
+
+	#[test]
+	fn test_pam_bandit_swap() {
+		use crate::arrayadapter::ClosureMatrix;
+		use std::sync::atomic::{AtomicUsize, Ordering};
+		let mut rng = StdRng::seed_from_u64(4);
+		let points = (0..200).map(|_| (rng.gen::<f64>(), rng.gen::<f64>())).collect::<Vec<_>>();
+		let calls = AtomicUsize::new(0);
+		let data = ClosureMatrix::new(200, |i: usize, j: usize| {
+			calls.fetch_add(1, Ordering::Relaxed);
+			((points[i].0 - points[j].0).powi(2) + (points[i].1 - points[j].1).powi(2)).sqrt()
+		});
+		let (loss, _, n_iter, _): (f64, _, _, _) = pam_swap(&data, &mut (0..5).collect(), 0, 100);
+		let full = calls.swap(0, Ordering::Relaxed);
+		let mut meds = (0..5).collect::<Vec<_>>();
+		let result: KMedoidsResult<f64> = pam_bandit_swap(&data, &mut meds, 100, 50, &mut rng);
+		let bandit = calls.swap(0, Ordering::Relaxed);
+		assert!(result.n_iter < 100, "not converged");
+		assert!(bandit * 10 < full * 7, "too many distances: {} of {}", bandit, full);
+		assert!(result.loss <= loss * 1.05, "loss worse than full SWAP: {} vs. {} in {} iterations", result.loss, loss, n_iter);
+		let (eloss, _): (f64, _) = assign_to_medoids(&data, &result.medoids);
+		assert!((result.loss - eloss).abs() < 1e-10, "loss not consistent");
+		// a local optimum: PAM SWAP makes no further swap
+		let (sloss, _, _, n_swap): (f64, _, _, _) = pam_swap(&data, &mut meds, 0, 100);
+		assert_eq!((n_swap, sloss), (0, result.loss), "not a local optimum");
+		// a budget of one is still a local optimum
+		let result: KMedoidsResult<f64> = pam_bandit_swap(&data, &mut (0..5).collect(), 1000, 1, &mut rng);
+		let (_, _, _, n_swap): (f64, _, _, _) = pam_swap(&data, &mut result.medoids.clone(), 0, 100);
+		assert_eq!(n_swap, 0, "not a local optimum");
+	}
+
 	#[test]
 	fn test_pam_simple() {
 		let data = LowerTriangle {
 			n: 5,
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
-		let (loss, assi, meds, n_iter, n_swap): (i64, _, _, _, _) = pam(&data, 2, 10);
+		let (loss, mut assi, mut meds, n_iter, n_swap): (i64, _, _, _, _) = pam(&data, 2, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		// no swaps, because BUILD does a decent job
 		assert_eq!(n_swap, 0, "swaps not as expected");
@@ -361,4 +1238,130 @@ mod tests {
 		assert_array(meds, vec![0, 3], "medoids not as expected");
 		assert_eq!(sil, 0.7522494172494172, "Silhouette not as expected");
 	}
+
+	/// Two distinct points, each three times
+	fn two_points() -> LowerTriangle<i64> {
+		LowerTriangle {
+			n: 6,
+			data: vec![0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1, 1, 0, 0],
+		}
+	}
+
+	#[test]
+	fn test_pam_with_shortfall() {
+		let data = two_points();
+		let (loss, assi, meds, _, _): (i64, _, _, _, _) = pam_with_shortfall(&data, 4, 10, BuildShortfall::Truncate);
+		assert_eq!(loss, 0, "loss not as expected");
+		assert_array(meds.clone(), vec![0, 3], "medoids not as expected");
+		assert_array(assi.clone(), vec![0, 0, 0, 1, 1, 1], "assignment not as expected");
+		let (loss2, assi2, meds2, _, _): (i64, _, _, _, _) = pam(&data, 4, 10);
+		assert_eq!((loss2, assi2, meds2), (loss, assi, meds), "not the same as pam");
+		let (loss, assi, meds, _, _): (i64, _, _, _, _) = pam_with_shortfall(&data, 4, 10, BuildShortfall::FillFarthest);
+		assert_eq!(loss, 0, "loss not as expected");
+		assert_array(meds.clone(), vec![0, 3, 1, 2], "medoids not as expected");
+		assert!(assi.iter().all(|&a| a < 4), "assignment not as expected");
+		for (i, &a) in assi.iter().enumerate() {
+			assert_eq!(data.get(i, meds[a]), 0, "assignment not as expected");
+		}
+	}
+
+	#[test]
+	fn test_pam_with_medoid_candidates() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for candidates in [vec![1, 2, 4], vec![2, 3], vec![4, 1, 1, 0]] {
+			for k in 1..=2 {
+				let result = pam_with_medoid_candidates::<_, _, i64>(&data, &candidates, k, 10);
+				assert_eq!(result.medoids.len(), k, "number of medoids not as expected");
+				assert!(result.medoids.iter().all(|m| candidates.contains(m)), "medoids not candidates");
+				let (loss, assi): (i64, _) = assign_to_medoids(&data, &result.medoids);
+				assert_eq!((loss, assi), (result.loss, result.assignment.clone()), "assignment not as expected");
+			}
+		}
+		let result = pam_with_medoid_candidates::<_, _, i64>(&data, &[1, 2, 4], 2, 10);
+		assert_eq!(result.loss, 5, "loss not as expected");
+		assert_array(result.medoids, vec![1, 4], "medoids not as expected");
+		let (loss, assi, meds, n_iter, n_swap): (i64, _, _, _, _) = pam(&data, 2, 10);
+		let result = pam_with_medoid_candidates::<_, _, i64>(&data, &[0, 1, 2, 3, 4], 2, 10);
+		assert_eq!(result, crate::KMedoidsResult::new(loss, assi, meds, n_iter, n_swap), "not the same as pam");
+	}
+
+	#[test]
+	#[should_panic]
+	fn test_pam_with_medoid_candidates_too_few() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		pam_with_medoid_candidates::<_, _, i64>(&data, &[1, 1], 2, 10);
+	}
+
+	#[test]
+	fn test_pam_swap_cancellable() {
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let n = 1000;
+		let mut rng = StdRng::seed_from_u64(42);
+		let data = LowerTriangle {
+			n,
+			data: (0..n * (n - 1) / 2).map(|_| rng.gen_range(1..10000)).collect::<Vec<i32>>(),
+		};
+		let mut meds = crate::random_initialization(n, 20, &mut rng);
+		let cancel = AtomicBool::new(false);
+		let start = std::time::Instant::now();
+		let (loss, assi, _, _, cancelled): (i64, _, _, _, _) = std::thread::scope(|s| {
+			s.spawn(|| {
+				std::thread::sleep(std::time::Duration::from_millis(10));
+				cancel.store(true, Ordering::Relaxed);
+			});
+			pam_swap_cancellable(&data, &mut meds, 100, &cancel)
+		});
+		assert!(cancelled, "run not cancelled");
+		assert!(start.elapsed().as_secs() < 5, "cancellation took too long");
+		// The partial result must be a consistent clustering:
+		assert_eq!(assi.len(), n, "assignment not as expected");
+		let mut expect = 0;
+		for (i, &a) in assi.iter().enumerate() {
+			let d = data.get(i, meds[a]);
+			assert!(meds.iter().all(|&m| data.get(i, m) >= d), "assignment not as expected");
+			expect += d as i64;
+		}
+		assert_eq!(loss, expect, "loss not as expected");
+	}
+
+	#[test]
+	fn test_pam_cancellable_not_cancelled() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let cancel = AtomicBool::new(false);
+		let (loss, mut assi, mut meds, n_iter, n_swap, cancelled): (i64, _, _, _, _, _) = pam_cancellable(&data, 2, 10, &cancel);
+		canonicalize(&mut meds, &mut assi);
+		assert!(!cancelled, "run cancelled");
+		assert_eq!(n_swap, 0, "swaps not as expected");
+		assert_eq!(n_iter, 1, "iterations not as expected");
+		assert_eq!(loss, 4, "loss not as expected");
+		assert_array(assi, vec![0, 0, 0, 1, 1], "assignment not as expected");
+		assert_array(meds, vec![0, 3], "medoids not as expected");
+	}
+
+	#[test]
+	fn test_pam_swap_maxiter0() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for init in [vec![1], vec![1, 2], vec![1, 2, 4]] {
+			let mut meds = init.clone();
+			let (loss, assi, n_iter, n_swap): (i64, _, _, _) = pam_swap(&data, &mut meds, 0, 0);
+			let (expected_loss, expected_assi): (i64, _) = assign_to_medoids(&data, &init);
+			assert_eq!(loss, expected_loss, "loss not as expected");
+			assert_eq!(n_swap, 0, "swaps not as expected");
+			assert_eq!(n_iter, 0, "iterations not as expected");
+			assert_array(assi, expected_assi, "assignment not as expected");
+			assert_array(meds, init, "medoids not as expected");
+		}
+	}
 }
@@ -1,73 +1,995 @@
 use crate::arrayadapter::ArrayAdapter;
-use crate::fastermsc::{do_swap, initial_assignment};
+use crate::fastermsc::{do_swap, initial_assignment, initial_assignment_with_tie_breaking};
+use crate::pam::BuildShortfall;
+use crate::profiling::{self, timed, Phase, Stopwatch};
+use crate::result::KMedoidsResult;
 use crate::util::*;
 use core::ops::AddAssign;
 use num_traits::{Signed, Zero, Float, FromPrimitive};
 use std::convert::From;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 
-#[inline]
-fn _loss<N, L>(a: N, b: N) -> L
+/// Run the original PAMMEDSIL SWAP algorithm (no initialization, but given initial medoids).
+///
+/// This is provided for academic reasons to see the performance difference.
+/// Quality-wise, FasterMSC is not worse on average, but much faster.
+/// FastMSC is supposed to do the same swaps, and find the same result, but faster.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed; with 0, the given medoids are only evaluated
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::pamsil_swap(&data, &mut meds, 100);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn pammedsil_swap<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+) -> (L, Vec<usize>, usize, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, mut data) = initial_assignment(mat, med);
+	let (loss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, med, &mut data, maxiter, loss, SwapOptions::default(), |_| {});
+	(loss, assi, n_iter, n_swap)
+}
+
+/// Run the original PAMMEDSIL SWAP algorithm, with a choice of how ties in the
+/// initial assignment are broken.
+///
+/// When an object is equally near to multiple medoids, [`pammedsil_swap`] assigns it
+/// to the first of these medoids; see [`BuildTieBreaking`] for the alternatives. This
+/// does not change the initial loss, but the assignment, and hence which swaps are
+/// found during SWAP if there are ties. A medoid is always assigned to itself.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed; with 0, the given medoids are only evaluated
+/// * `ties` - how to choose among equally near medoids
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,1,3],[1,0,2,5],[1,2,0,6],[3,5,6,0]]);
+/// let ties = kmedoids::BuildTieBreaking::LastIndex;
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::pammedsil_swap_with_tie_breaking(&data, &mut vec![1, 2], 0, ties);
+/// assert_eq!(assi, vec![1, 0, 1, 0]);
+/// ```
+pub fn pammedsil_swap_with_tie_breaking<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+	mut ties: BuildTieBreaking,
+) -> (L, Vec<usize>, usize, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, mut data) = initial_assignment_with_tie_breaking(mat, med, &mut ties);
+	let (loss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, med, &mut data, maxiter, loss, SwapOptions::default(), |_| {});
+	(loss, assi, n_iter, n_swap)
+}
+
+/// Resume PAMMEDSIL from previously computed medoids, e.g., loaded from disk.
+///
+/// This skips the BUILD phase: the nearest and second nearest medoids of every point are
+/// computed from the given medoids in O(nk) time, then SWAP continues from there. It is the
+/// same as [`pammedsil_swap`], named for the use case and returning a [`KMedoidsResult`].
+/// When the given medoids are already good, few SWAP iterations remain, and this is much
+/// cheaper than running [`pammedsil`] again.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the saved medoids, updated in place
+/// * `maxiter` - the maximum number of iterations allowed; with 0, the given medoids are only evaluated
+///
+/// returns the clustering result
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = vec![0, 3]; // e.g., from a previous run
+/// let result: kmedoids::KMedoidsResult<f64> = kmedoids::pammedsil_resume(&data, &mut meds, 100);
+/// println!("Loss is: {}", result.loss);
+/// ```
+pub fn pammedsil_resume<M, N, L>(mat: &M, med: &mut Vec<usize>, maxiter: usize) -> KMedoidsResult<L>
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, assi, n_iter, n_swap) = pammedsil_swap(mat, med, maxiter);
+	KMedoidsResult::new(loss, assi, med.clone(), n_iter, n_swap)
+}
+
+/// Run the PAMMEDSIL SWAP algorithm on a shared matrix.
+///
+/// Same as [`pammedsil_swap`], but takes ownership of an `Arc` of the matrix and of the
+/// medoids, so the call does not borrow from the caller. This allows running it on another
+/// thread, e.g., with `std::thread::spawn` or `tokio::task::spawn_blocking`, while other
+/// threads keep using the same matrix.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a shared pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed; with 0, the given medoids are only evaluated
+///
+/// returns the clustering result
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = std::sync::Arc::new(ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]));
+/// let shared = std::sync::Arc::clone(&data);
+/// let handle = std::thread::spawn(move || kmedoids::pammedsil_swap_arc::<_, _, f64>(shared, vec![0, 1], 100));
+/// let result = handle.join().unwrap();
+/// println!("Loss is: {}", result.loss);
+/// ```
+pub fn pammedsil_swap_arc<M, N, L>(mat: Arc<M>, mut med: Vec<usize>, maxiter: usize) -> KMedoidsResult<L>
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, assi, n_iter, n_swap) = pammedsil_swap(mat.as_ref(), &mut med, maxiter);
+	KMedoidsResult::new(loss, assi, med, n_iter, n_swap)
+}
+
+/// Run the PAMMEDSIL SWAP algorithm, with the candidates in random order.
+///
+/// PAMMEDSIL performs the best swap of each iteration, so the order of the candidates
+/// only matters if multiple swaps are equally good. By default, the one with the
+/// lowest index wins; here the candidates are scanned in a new random order in every
+/// iteration, to break such ties randomly.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `rng` - random number generator for shuffling the candidates
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::rand_pammedsil_swap(&data, &mut meds, 100, &mut rand::thread_rng());
+/// println!("Loss is: {}", loss);
+/// ```
+#[cfg(feature = "rand")]
+pub fn rand_pammedsil_swap<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+	rng: &mut impl rand::Rng,
+) -> (L, Vec<usize>, usize, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, mut data) = initial_assignment(mat, med);
+	let (loss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, med, &mut data, maxiter, loss, SwapOptions { order: ScanOrder::Shuffled(rng), ..Default::default() }, |_| {});
+	(loss, assi, n_iter, n_swap)
+}
+
+/// Run the PAMMEDSIL SWAP algorithm, scanning the candidates in the given order.
+///
+/// The same order is used in every iteration; of equally good swaps, the candidate
+/// that comes first in the order wins.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `order` - a permutation of 0..n, the order of the candidates
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+/// * panics when the order is not a permutation of 0..n
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = vec![0, 1];
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::pammedsil_swap_with_order(&data, &mut meds, 100, &[3, 2, 1, 0]);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn pammedsil_swap_with_order<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+	order: &[usize],
+) -> (L, Vec<usize>, usize, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, mut data) = initial_assignment(mat, med);
+	let (loss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, med, &mut data, maxiter, loss, SwapOptions { order: ScanOrder::Fixed(order), ..Default::default() }, |_| {});
+	(loss, assi, n_iter, n_swap)
+}
+
+/// Run the original PAMMEDSIL SWAP algorithm (no initialization, but given initial medoids), with cancellation.
+///
+/// The flag is checked (with relaxed ordering) before each candidate is evaluated.
+/// When it is set, the current solution is returned, which is a valid clustering,
+/// but not necessarily a local optimum.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `cancel` - cancellation flag, may be set from another thread
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+/// * whether the run was cancelled
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let cancel = std::sync::atomic::AtomicBool::new(false);
+/// let (loss, assi, n_iter, n_swap, cancelled): (f64, _, _, _, _) = kmedoids::pammedsil_swap_cancellable(&data, &mut meds, 100, &cancel);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn pammedsil_swap_cancellable<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+	cancel: &AtomicBool,
+) -> (L, Vec<usize>, usize, usize, bool)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, mut data) = initial_assignment(mat, med);
+	pammedsil_optimize(mat, med, &mut data, maxiter, loss, SwapOptions { cancel: Some(cancel), ..Default::default() }, |_| {})
+}
+
+/// Run the PAMMEDSIL SWAP algorithm with pruning via the triangle inequality.
+///
+/// For each candidate j, the distances of j to the current medoids give a lower bound
+/// on the distance of every other point to j. Points that are provably farther from j
+/// than their third nearest medoid only change if their nearest or second nearest
+/// medoid is removed, by an amount that does not depend on j. Their distance to j is
+/// not needed, and their contribution is computed once instead of for every medoid.
+/// This is most effective with well-separated clusters.
+///
+/// If the dissimilarity satisfies the triangle inequality (i.e., is a metric), this
+/// performs the same swaps as [`pammedsil_swap`], up to rounding of the loss changes.
+/// Otherwise, the result may differ.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,3,4],[2,3,0,5],[3,4,5,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 3, &mut rand::thread_rng());
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::pammedsil_swap_pruned(&data, &mut meds, 100);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn pammedsil_swap_pruned<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+) -> (L, Vec<usize>, usize, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, mut data) = initial_assignment(mat, med);
+	let (loss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, med, &mut data, maxiter, loss, SwapOptions { prune: true, ..Default::default() }, |_| {});
+	(loss, assi, n_iter, n_swap)
+}
+
+/// Run the PAMMEDSIL SWAP algorithm, stopping early when the best swap is a small gain.
+///
+/// The algorithm stops when the gain of the best swap, relative to the current loss
+/// (the sum of the ratios of the distances to the nearest and second nearest medoid)
+/// is less than `eps`. Note that this is a relative criterion on the best candidate,
+/// and that the swap is not performed then. With `eps = 0`, this is the same as
+/// [`pammedsil_swap`].
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `eps` - the minimum relative gain of a swap
+///
+/// returns the result, with the final loss, assignment, medoids and the number of iterations and swaps
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let result: kmedoids::KMedoidsResult<f64> = kmedoids::pammedsil_swap_eps(&data, &mut meds, 100, 0.01);
+/// println!("Loss is: {}", result.loss);
+/// ```
+pub fn pammedsil_swap_eps<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+	eps: L,
+) -> KMedoidsResult<L>
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, mut data) = initial_assignment(mat, med);
+	let (loss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, med, &mut data, maxiter, loss, SwapOptions { eps, ..Default::default() }, |_| {});
+	KMedoidsResult::new(loss, assi, med.clone(), n_iter, n_swap)
+}
+
+/// Run the PAMMEDSIL SWAP algorithm, continuing a few iterations on a loss plateau.
+///
+/// [`pammedsil_swap`] stops when a swap that was expected to improve the loss does not,
+/// e.g., because of rounding with near-tied swaps. Here, up to `patience` such swaps in a
+/// row are performed, as they may lead to further improvements. If they do not, the best
+/// solution found before them is returned. With `patience = 0`, this is the same as
+/// [`pammedsil_swap`].
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed; with 0, the given medoids are only evaluated
+/// * `patience` - the number of swaps in a row without improvement before stopping
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::pammedsil_swap_patience(&data, &mut meds, 100, 3);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn pammedsil_swap_patience<M, N, L>(
+	mat: &M,
+	med: &mut Vec<usize>,
+	maxiter: usize,
+	patience: usize,
+) -> (L, Vec<usize>, usize, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let (loss, mut data) = initial_assignment(mat, med);
+	let (loss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, med, &mut data, maxiter, loss, SwapOptions { patience, ..Default::default() }, |_| {});
+	(loss, assi, n_iter, n_swap)
+}
+
+/// A swap performed by [`pammedsil_swap_traced`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SwapEvent<N, L> {
+	/// the medoid that was removed
+	pub old_medoid: usize,
+	/// the point that became a medoid instead
+	pub new_medoid: usize,
+	/// the distance of the old and the new medoid
+	pub distance: N,
+	/// the Medoid Silhouette before the swap
+	pub loss_before: L,
+	/// the Medoid Silhouette after the swap
+	pub loss_after: L,
+}
+
+/// Run the PAMMEDSIL SWAP algorithm, calling a function after every swap.
+///
+/// Same as [`pammedsil_swap`], but `on_swap` is called with every swap performed, e.g., to
+/// log the progress. As the function is a type parameter, a no-op function such as `|_| {}`
+/// is optimized out. With a single medoid, the medoid is chosen directly, and not reported.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * type `F` - the function called after every swap
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed; with 0, the given medoids are only evaluated
+/// * `on_swap` - the function called with every swap
+///
+/// returns the clustering result
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = vec![0, 1];
+/// let result: kmedoids::KMedoidsResult<f64> = kmedoids::pammedsil_swap_traced(&data, &mut meds, 100, |e| {
+///     println!("Swapped {} for {}, loss {} -> {}", e.old_medoid, e.new_medoid, e.loss_before, e.loss_after);
+/// });
+/// println!("Loss is: {}", result.loss);
+/// ```
+pub fn pammedsil_swap_traced<M, N, L, F>(mat: &M, med: &mut Vec<usize>, maxiter: usize, on_swap: F) -> KMedoidsResult<L>
 	where
-		N: Zero,
-		L: Float + From<N> + FromPrimitive,
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+		F: FnMut(SwapEvent<N, L>),
 {
-	if N::is_zero(&a) || N::is_zero(&b) { L::zero() } else { <L as From<N>>::from(a) / <L as From<N>>::from(b) } 
+	let (loss, mut data) = initial_assignment(mat, med);
+	let (loss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, med, &mut data, maxiter, loss, SwapOptions::default(), on_swap);
+	KMedoidsResult::new(loss, assi, med.clone(), n_iter, n_swap)
 }
 
-/// Run the original PAMMEDSIL SWAP algorithm (no initialization, but given initial medoids).
+/// Run the original PAM BUILD algorithm combined with the PAMMEDSIL SWAP.
 ///
-/// This is provided for academic reasons to see the performance difference.
-/// Quality-wise, FasterMSC is not worse on average, but much faster.
-/// FastMSC is supposed to do the same swaps, and find the same result, but faster.
+/// If multiple candidates are equally good in BUILD, the one with the lowest index is
+/// chosen. Use [`pammedsil_with_tie_breaking`] to choose differently.
+///
+/// If there are fewer than k distinct points, BUILD stops early and fewer than k
+/// medoids are returned. Use [`pammedsil_with_shortfall`] to always obtain k medoids.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `maxiter` - the maximum number of iterations allowed
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the final medoids
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = kmedoids::pamsil(&data, 2, 100);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn pammedsil<M, N, L>(mat: &M, k: usize, maxiter: usize) -> (L, Vec<usize>, Vec<usize>, usize, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
+	assert!(k <= n, "k must be at most N");
+	let mut meds = Vec::<usize>::with_capacity(k);
+	let mut data = Vec::<Reco<N>>::with_capacity(n);
+	let loss = pammedsil_build_initialize(mat, &mut meds, &mut data, k, None, &mut BuildTieBreaking::FirstIndex, BuildShortfall::Truncate);
+	let (nloss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, &mut meds, &mut data, maxiter, loss, SwapOptions::default(), |_| {});
+	(nloss, assi, meds, n_iter, n_swap) // also return medoids
+}
+
+/// The score function of the BUILD of [`pammedsil`], for use with [`crate::custom_build`].
+///
+/// PAMMEDSIL initializes with the PAM BUILD, which minimizes the total deviation, so the score
+/// is the same as [`crate::pam_build_score`], but with the float loss type of PAMMEDSIL.
+/// `custom_build` with this score yields the same medoids as the BUILD of `pammedsil`.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+///
+/// returns the score function
+pub fn pammedsil_build_score<M, N, L>(mat: &M) -> impl Fn(usize, &[Reco<N>]) -> L + '_
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N>,
+		M: ArrayAdapter<N>,
+{
+	crate::pam::pam_build_score(mat)
+}
+
+/// Run the original PAM BUILD algorithm combined with the PAMMEDSIL SWAP, with cancellation.
+///
+/// The flag is checked (with relaxed ordering) before each candidate is evaluated,
+/// in both BUILD and SWAP. When it is set, the current solution is returned.
+/// If the run is cancelled during BUILD, fewer than k medoids may be returned.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `cancel` - cancellation flag, may be set from another thread
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the final medoids
+/// * the number of iterations needed
+/// * the number of swaps performed
+/// * whether the run was cancelled
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let cancel = std::sync::atomic::AtomicBool::new(false);
+/// let (loss, assi, meds, n_iter, n_swap, cancelled): (f64, _, _, _, _, _) = kmedoids::pammedsil_cancellable(&data, 2, 100, &cancel);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn pammedsil_cancellable<M, N, L>(
+	mat: &M,
+	k: usize,
+	maxiter: usize,
+	cancel: &AtomicBool,
+) -> (L, Vec<usize>, Vec<usize>, usize, usize, bool)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
+	assert!(k <= n, "k must be at most N");
+	let mut meds = Vec::<usize>::with_capacity(k);
+	let mut data = Vec::<Reco<N>>::with_capacity(n);
+	let loss = pammedsil_build_initialize(mat, &mut meds, &mut data, k, Some(cancel), &mut BuildTieBreaking::FirstIndex, BuildShortfall::Truncate);
+	if is_cancelled(Some(cancel)) {
+		let assi = data.iter().map(|x| x.near.i as usize).collect();
+		let loss = if meds.len() > 1 { L::one() - loss / <L as From<u32>>::from(n as u32) } else { L::one() };
+		return (loss, assi, meds, 0, 0, true);
+	}
+	let (nloss, assi, n_iter, n_swap, cancelled) = pammedsil_optimize(mat, &mut meds, &mut data, maxiter, loss, SwapOptions { cancel: Some(cancel), ..Default::default() }, |_| {});
+	(nloss, assi, meds, n_iter, n_swap, cancelled) // also return medoids
+}
+
+/// Choice among equally good candidates in BUILD, or among equally near medoids in the
+/// assignment of [`pammedsil_swap_with_tie_breaking`].
+#[derive(Default)]
+pub enum BuildTieBreaking<'a> {
+	/// choose the candidate with the lowest index (the default)
+	#[default]
+	FirstIndex,
+	/// choose the candidate with the highest index
+	LastIndex,
+	/// choose one of the candidates uniformly at random
+	#[cfg(feature = "rand")]
+	Random(&'a mut dyn rand::RngCore),
+	#[cfg(not(feature = "rand"))]
+	#[doc(hidden)]
+	_Unused(core::marker::PhantomData<&'a mut ()>),
+}
+
+impl BuildTieBreaking<'_> {
+	/// Whether a candidate with loss `sum` replaces the current best.
+	/// `ties` is the number of candidates tied with the current best.
+	#[inline]
+	pub(crate) fn replaces<L: PartialOrd>(&mut self, sum: L, best: L, ties: &mut u32) -> bool {
+		if sum < best {
+			*ties = 1;
+			return true;
+		}
+		if sum != best {
+			return false;
+		}
+		*ties += 1;
+		match self {
+			BuildTieBreaking::FirstIndex => false,
+			BuildTieBreaking::LastIndex => true,
+			#[cfg(feature = "rand")]
+			BuildTieBreaking::Random(rng) => rand::Rng::gen_range(rng, 0..*ties) == 0, // reservoir sampling
+			#[cfg(not(feature = "rand"))]
+			BuildTieBreaking::_Unused(_) => false,
+		}
+	}
+}
+
+/// Run the original PAM BUILD algorithm combined with the PAMMEDSIL SWAP, with a choice
+/// of how ties in BUILD are broken.
+///
+/// On data with many ties (e.g., integer distances), the choice among the equally good
+/// candidates in BUILD can make a difference. With [`BuildTieBreaking::Random`], different
+/// runs can yield different solutions.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `ties` - how to choose among equally good candidates in BUILD
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the final medoids
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,1],[1,0,1,2],[2,1,0,1],[1,2,1,0]]);
+/// let mut rng = rand::thread_rng();
+/// let ties = kmedoids::BuildTieBreaking::Random(&mut rng);
+/// let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = kmedoids::pammedsil_with_tie_breaking(&data, 2, 100, ties);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn pammedsil_with_tie_breaking<M, N, L>(
+	mat: &M,
+	k: usize,
+	maxiter: usize,
+	mut ties: BuildTieBreaking,
+) -> (L, Vec<usize>, Vec<usize>, usize, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
+	assert!(k <= n, "k must be at most N");
+	let mut meds = Vec::<usize>::with_capacity(k);
+	let mut data = Vec::<Reco<N>>::with_capacity(n);
+	let loss = pammedsil_build_initialize(mat, &mut meds, &mut data, k, None, &mut ties, BuildShortfall::Truncate);
+	let (nloss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, &mut meds, &mut data, maxiter, loss, SwapOptions::default(), |_| {});
+	(nloss, assi, meds, n_iter, n_swap) // also return medoids
+}
+
+/// Find all equally good first medoids of the PAMMEDSIL BUILD, for analysis.
+///
+/// The first medoid of BUILD minimizes the sum of distances to all other points. If
+/// multiple points have the same sum, [`pammedsil`] chooses the one with the lowest
+/// index, and [`pammedsil_with_tie_breaking`] can choose any of them; this lists them all.
+/// The sums are computed in the loss type, exactly as in BUILD.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+///
+/// returns the candidates for the first medoid, in ascending order
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,1],[1,0,1,2],[2,1,0,1],[1,2,1,0]]);
+/// let ties = kmedoids::pammedsil_build_all_ties::<_, _, f64>(&data);
+/// assert_eq!(ties, vec![0, 1, 2, 3]);
+/// ```
+pub fn pammedsil_build_all_ties<M, N, L>(mat: &M) -> Vec<usize>
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N>,
+		M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	let sums = (0..n)
+		.map(|i| {
+			let mut sum = L::zero();
+			for j in 0..n {
+				if j != i {
+					sum += <L as From<N>>::from(mat.get(j, i));
+				}
+			}
+			sum
+		})
+		.collect::<Vec<L>>();
+	let best = sums.iter().copied().fold(L::infinity(), L::min);
+	(0..n).filter(|&i| sums[i] == best).collect()
+}
+
+/// Run the original PAM BUILD algorithm combined with the PAMMEDSIL SWAP, with a choice
+/// of what to do when BUILD finds no further improvement before choosing k medoids.
+///
+/// With [`BuildShortfall::Truncate`], the result is the same as [`pammedsil`], and the
+/// actual number of clusters is the length of the medoid list, which can be less than k.
+/// With [`BuildShortfall::FillFarthest`], BUILD continues with the point farthest from
+/// its nearest medoid (possibly a duplicate of a medoid), and exactly k medoids are returned.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `maxiter` - the maximum number of iterations allowed
+/// * `shortfall` - what to do when BUILD stops early
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the final medoids
+/// * the number of iterations needed
+/// * the number of swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,0,1,1],[0,0,1,1],[1,1,0,0],[1,1,0,0]]);
+/// let shortfall = kmedoids::BuildShortfall::FillFarthest;
+/// let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = kmedoids::pammedsil_with_shortfall(&data, 3, 100, shortfall);
+/// assert_eq!(meds.len(), 3);
+/// ```
+pub fn pammedsil_with_shortfall<M, N, L>(
+	mat: &M,
+	k: usize,
+	maxiter: usize,
+	shortfall: BuildShortfall,
+) -> (L, Vec<usize>, Vec<usize>, usize, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
+	assert!(k <= n, "k must be at most N");
+	let mut meds = Vec::<usize>::with_capacity(k);
+	let mut data = Vec::<Reco<N>>::with_capacity(n);
+	let loss = pammedsil_build_initialize(mat, &mut meds, &mut data, k, None, &mut BuildTieBreaking::FirstIndex, shortfall);
+	let (nloss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, &mut meds, &mut data, maxiter, loss, SwapOptions::default(), |_| {});
+	(nloss, assi, meds, n_iter, n_swap) // also return medoids
+}
+
+/// Choice of the initial medoids of [`pammedsil_with_init`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InitStrategy {
+	/// the greedy PAM BUILD, as in [`pammedsil`]
+	#[default]
+	Build,
+	/// the best pair of medoids, see [`pammedsil_build_k2`]; only for k = 2
+	ExhaustiveK2,
+}
+
+/// Find the best pair of medoids for the Medoid Silhouette, by trying all pairs.
+///
+/// The greedy BUILD chooses the best single medoid first, and then the best second medoid
+/// given the first, which is not necessarily the best pair. This evaluates all n(n-1)/2
+/// pairs. The distances are cached, so each distance is only read once from the matrix,
+/// at the cost of O(n²) memory. Each pair still takes up to O(n) arithmetic. But the sum
+/// of a pair is abandoned once it exceeds the best pair so far, which starts from the greedy
+/// choice, so most pairs are rejected after a few points. On ties, the pair with the lowest
+/// indexes is chosen.
 ///
 /// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
 /// * type `N` - number data type such as `u32` or `f64`
-/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
 /// * `mat` - a pairwise distance matrix
-/// * `med` - the list of medoids
-/// * `maxiter` - the maximum number of iterations allowed
 ///
-/// returns a tuple containing:
-/// * the final loss
-/// * the final cluster assignment
-/// * the number of iterations needed
-/// * the number of swaps performed
+/// returns the two medoids, in ascending order
 ///
 /// ## Panics
 ///
 /// * panics when the dissimilarity matrix is not square
-/// * panics when k is 0 or larger than N
+/// * panics when N is less than 2
 ///
 /// ## Example
 /// Given a dissimilarity matrix of size 4 x 4, use:
 /// ```
 /// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
-/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
-/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::pamsil_swap(&data, &mut meds, 100);
-/// println!("Loss is: {}", loss);
+/// let meds = kmedoids::pammedsil_build_k2::<_, _, f64>(&data);
+/// println!("Medoids: {:?}", meds);
 /// ```
-pub fn pammedsil_swap<M, N, L>(
-	mat: &M,
-	med: &mut Vec<usize>,
-	maxiter: usize,
-) -> (L, Vec<usize>, usize, usize)
+pub fn pammedsil_build_k2<M, N, L>(mat: &M) -> [usize; 2]
 	where
 		N: Zero + PartialOrd + Copy,
-		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
+		L: Float + AddAssign + From<N>,
 		M: ArrayAdapter<N>,
 {
-	let (loss, mut data) = initial_assignment(mat, med);
-	pammedsil_optimize(mat, med, &mut data, maxiter, loss)
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n >= 2, "at least two points are required");
+	// cache the distances of all points to each candidate
+	let cols = (0..n).map(|a| (0..n).map(|o| mat.get(o, a)).collect::<Vec<_>>()).collect::<Vec<_>>();
+	// sum of the ratios of a pair, abandoned once it exceeds the bound
+	let pair_loss = |a: usize, b: usize, bound: L| {
+		let mut sum = L::zero();
+		for (&da, &db) in cols[a].iter().zip(cols[b].iter()) {
+			sum += if da <= db { safe_ratio::<N, L>(da, db) } else { safe_ratio::<N, L>(db, da) };
+			if sum > bound {
+				break;
+			}
+		}
+		sum
+	};
+	// start with the greedy choice: the best single medoid, and its best partner
+	let first = (0..n)
+		.map(|a| (cols[a].iter().fold(L::zero(), |acc, &d| acc + <L as From<N>>::from(d)), a))
+		.fold((L::infinity(), 0), |best, c| if c.0 < best.0 { c } else { best })
+		.1;
+	let mut best = (L::infinity(), [0, 1]);
+	for b in (0..n).filter(|&b| b != first) {
+		let sum = pair_loss(first, b, best.0);
+		if sum < best.0 {
+			best = (sum, [first.min(b), first.max(b)]);
+		}
+	}
+	for a in 0..n {
+		for b in (a + 1)..n {
+			let sum = pair_loss(a, b, best.0);
+			if sum < best.0 || (sum == best.0 && [a, b] < best.1) {
+				best = (sum, [a, b]);
+			}
+		}
+	}
+	best.1
 }
 
-/// Run the original PAM BUILD algorithm combined with the PAMMEDSIL SWAP.
+/// Run PAMMEDSIL SWAP, with a choice of the initial medoids.
+///
+/// With [`InitStrategy::Build`], this is the same as [`pammedsil`]. With
+/// [`InitStrategy::ExhaustiveK2`], SWAP starts from the best pair of medoids, from
+/// [`pammedsil_build_k2`]; as SWAP never makes the loss worse, the result is then the
+/// best pair, too.
 ///
 /// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
 /// * type `N` - number data type such as `u32` or `f64`
-/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
 /// * `mat` - a pairwise distance matrix
 /// * `k` - the number of medoids to pick
 /// * `maxiter` - the maximum number of iterations allowed
+/// * `init` - how to choose the initial medoids
 ///
 /// returns a tuple containing:
 /// * the final loss
@@ -80,75 +1002,141 @@ pub fn pammedsil_swap<M, N, L>(
 ///
 /// * panics when the dissimilarity matrix is not square
 /// * panics when k is 0 or larger than N
+/// * panics when k is not 2 with [`InitStrategy::ExhaustiveK2`]
 ///
 /// ## Example
 /// Given a dissimilarity matrix of size 4 x 4, use:
 /// ```
 /// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
-/// let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = kmedoids::pamsil(&data, 2, 100);
+/// let init = kmedoids::InitStrategy::ExhaustiveK2;
+/// let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = kmedoids::pammedsil_with_init(&data, 2, 100, init);
 /// println!("Loss is: {}", loss);
 /// ```
-pub fn pammedsil<M, N, L>(mat: &M, k: usize, maxiter: usize) -> (L, Vec<usize>, Vec<usize>, usize, usize)
+pub fn pammedsil_with_init<M, N, L>(
+	mat: &M,
+	k: usize,
+	maxiter: usize,
+	init: InitStrategy,
+) -> (L, Vec<usize>, Vec<usize>, usize, usize)
 	where
 		N: Zero + PartialOrd + Copy,
 		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
 		M: ArrayAdapter<N>,
 {
-	let n = mat.len();
-	assert!(mat.is_square(), "Dissimilarity matrix is not square");
-	assert!(n <= u32::MAX as usize, "N is too large");
-	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
-	assert!(k <= n, "k must be at most N");
-	let mut meds = Vec::<usize>::with_capacity(k);
-	let mut data = Vec::<Reco<N>>::with_capacity(n);
-	let loss = pammedsil_build_initialize(mat, &mut meds, &mut data, k);
-	let (nloss, assi, n_iter, n_swap) = pammedsil_optimize(mat, &mut meds, &mut data, maxiter, loss);
-	(nloss, assi, meds, n_iter, n_swap) // also return medoids
+	match init {
+		InitStrategy::Build => pammedsil(mat, k, maxiter),
+		InitStrategy::ExhaustiveK2 => {
+			assert!(k == 2, "exhaustive initialization requires k = 2");
+			let mut meds = pammedsil_build_k2::<M, N, L>(mat).to_vec();
+			let (loss, mut data) = initial_assignment(mat, &meds);
+			let (nloss, assi, n_iter, n_swap, _) = pammedsil_optimize(mat, &mut meds, &mut data, maxiter, loss, SwapOptions::default(), |_| {});
+			(nloss, assi, meds, n_iter, n_swap) // also return medoids
+		}
+	}
+}
+
+/// Options of the PAMMEDSIL SWAP, not exposed
+struct SwapOptions<'a, L> {
+	/// flag checked before each candidate
+	cancel: Option<&'a AtomicBool>,
+	/// prune the medoids using a third nearest medoid
+	prune: bool,
+	/// order of the swap candidates
+	order: ScanOrder<'a>,
+	/// minimum relative improvement of a swap
+	eps: L,
+	/// number of swaps without improvement to continue with
+	patience: usize,
+}
+
+impl<L: Zero> Default for SwapOptions<'_, L> {
+	fn default() -> Self {
+		SwapOptions { cancel: None, prune: false, order: ScanOrder::Sequential, eps: L::zero(), patience: 0 }
+	}
 }
 
 /// Main optimization function of PAMMEDSIL, not exposed (use pammedsil_swap or pammedsil)
-fn pammedsil_optimize<M, N, L>(
+fn pammedsil_optimize<M, N, L, F>(
 	mat: &M,
 	med: &mut Vec<usize>,
 	data: &mut Vec<Reco<N>>,
 	maxiter: usize,
 	mut loss: L,
-) -> (L, Vec<usize>, usize, usize)
+	opts: SwapOptions<L>,
+	mut on_swap: F,
+) -> (L, Vec<usize>, usize, usize, bool)
 	where
 		N: Zero + PartialOrd + Copy,
 		L: Float + Signed + AddAssign + From<N> + std::convert::From<u32> + FromPrimitive + std::fmt::Display,
 		M: ArrayAdapter<N>,
+		F: FnMut(SwapEvent<N, L>),
 {
+	let SwapOptions { cancel, prune, mut order, eps, patience } = opts;
+	profiling::reset();
 	let (n, k) = (mat.len(), med.len());
 	if k == 1 {
 		let assi = vec![0; n];
-		let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, med, 0);
-		return (loss, assi, 1, if swapped { 1 } else { 0 });
+		if maxiter == 0 {
+			return (L::one(), assi, 0, 0, false); // only evaluate the given medoid
+		}
+		let (swapped, _) = choose_medoid_within_partition::<M, N, L>(mat, &assi, med, 0);
+		return (L::one(), assi, 1, if swapped { 1 } else { 0 }, false); // as medoid_silhouette
 	}
 	debug_assert_assignment_th(mat, med, data);
-	let (mut n_swaps, mut iter) = (0, 0);
+	let (mut n_swaps, mut iter, mut cancelled) = (0, 0, false);
+	// buffers for pruning, which needs a third nearest medoid
+	let prune = prune && k > 2;
+	let (mut dj, mut accm, mut close) = (vec![L::zero(); k], vec![L::zero(); k], Vec::with_capacity(n));
+	let mut seq = order.initial(n);
+	// with patience, the best solution before the swaps that did not improve
+	let (mut stalls, mut best_seen) = (0, None);
 	while iter < maxiter {
 		iter += 1;
+		let _iteration = Stopwatch::start(Phase::Iteration);
+		order.next_iteration(&mut seq);
 		let mut best = (L::zero(), k, usize::MAX);
-		for j in 0..n {
+		for &j in seq.iter() {
+			if is_cancelled(cancel) {
+				cancelled = true;
+				break;
+			}
 			if j == med[data[j].near.i as usize] {
 				continue; // This already is a medoid
 			}
-			let (change, b): (L, usize) = if k == 2 {
+			let (change, b): (L, usize) = timed(Phase::FindBestSwap, || if k == 2 {
 				find_best_swap_pammedsil_k2(mat, med, data, j)
+			} else if prune {
+				find_best_swap_pammedsil_pruned(mat, med, data, j, &mut dj, &mut accm, &mut close)
 			} else {
 				find_best_swap_pammedsil(mat, med, data, j)
-			};
+			});
 			if change <= best.0 {
 				continue; // No improvement
 			}
 			best = (change, b, j);
 		}
+		if cancelled {
+			break; // keep the current, consistent, solution
+		}
+		if best.0 < eps * loss {
+			break; // Relative improvement too small
+		}
 		if best.0 > L::zero() {
 			n_swaps += 1;
+			if patience > 0 && stalls == 0 {
+				best_seen = Some((med.clone(), data.clone(), loss));
+			}
 			// perform the swap
-			let newloss : L = do_swap(mat, med, data, best.1, best.2);
-			if newloss >= loss {
+			let (old_medoid, distance) = (med[best.1], mat.get(med[best.1], best.2));
+			let newloss : L = timed(Phase::DoSwap, || do_swap(mat, med, data, best.1, best.2));
+			let nn = <L as From<u32>>::from(n as u32);
+			on_swap(SwapEvent { old_medoid, new_medoid: best.2, distance, loss_before: L::one() - loss / nn, loss_after: L::one() - newloss / nn });
+			debug_assert_assignment_th(mat, med, data);
+			if newloss < best_seen.as_ref().map_or(loss, |b| b.2) {
+				stalls = 0;
+			} else if stalls < patience {
+				stalls += 1; // continue on the plateau
+			} else {
 				break; // Probably numerically unstable now.
 			}
 			loss = newloss;
@@ -156,9 +1144,17 @@ fn pammedsil_optimize<M, N, L>(
 			break; // No improvement, or NaN.
 		}
 	}
+	if stalls > 0 {
+		// the swaps since the best solution did not improve, restore it
+		if let Some((bmed, bdata, bloss)) = best_seen {
+			if bloss <= loss {
+				(*med, *data, loss) = (bmed, bdata, bloss);
+			}
+		}
+	}
 	let assi = data.iter().map(|x| x.near.i as usize).collect();
 	loss = L::one() - loss / <L as From<u32>>::from(n as u32);
-	(loss, assi, iter, n_swaps)
+	(loss, assi, iter, n_swaps, cancelled)
 }
 
 /// Find the best swap for object j
@@ -172,38 +1168,103 @@ fn find_best_swap_pammedsil<M, N, L>(mat: &M, med: &[usize], data: &[Reco<N>], j
 	let recj = &data[j];
 	let mut best = (L::zero(), usize::MAX);
 	for (m, _) in med.iter().enumerate() {
-		let mut acc: L = _loss::<N, L>(recj.near.d, recj.seco.d); // j becomes medoid
+		let mut acc: L = safe_ratio::<N, L>(recj.near.d, recj.seco.d); // j becomes medoid
 		for (o, reco) in data.iter().enumerate() {
 			if o == j {
 				continue;
 			}
 			let doj = mat.get(o, j);
-			// Current medoid is being replaced:
-			if reco.near.i as usize == m {
-				if doj < reco.seco.d {
-					// Assign to new medoid:
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(doj, reco.seco.d);
-				} else if doj < reco.third.d {
-					// Assign to second nearest instead:
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(reco.seco.d, doj);
-				} else {
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(reco.seco.d, reco.third.d);
-				}
-			} else if reco.seco.i as usize == m  {
-				if doj < reco.near.d {
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(doj, reco.near.d);
-				} else if doj < reco.third.d {
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(reco.near.d, doj);
-				} else {
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(reco.near.d, reco.third.d);
-				}
-			} else {
-				if doj < reco.near.d {
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(doj, reco.near.d);
-				} else if doj < reco.seco.d {
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(reco.near.d, doj);
-				}
-			}
+			debug_assert_symmetric(mat, o, j, doj);
+			swap_gain(&mut acc, reco, doj, m);
+		}
+		if acc > best.0 {
+			best = (acc, m);
+		}
+	}
+	best
+}
+
+/// Change in loss of object o, when medoid m is replaced with j
+#[inline]
+fn swap_gain<N, L>(acc: &mut L, reco: &Reco<N>, doj: N, m: usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + AddAssign + From<N> + FromPrimitive,
+{
+	// Current medoid is being replaced:
+	if reco.near.i as usize == m {
+		if doj < reco.seco.d {
+			// Assign to new medoid:
+			*acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(doj, reco.seco.d);
+		} else if doj < reco.third.d {
+			// Assign to second nearest instead:
+			*acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(reco.seco.d, doj);
+		} else {
+			*acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(reco.seco.d, reco.third.d);
+		}
+	} else if reco.seco.i as usize == m  {
+		if doj < reco.near.d {
+			*acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(doj, reco.near.d);
+		} else if doj < reco.third.d {
+			*acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(reco.near.d, doj);
+		} else {
+			*acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(reco.near.d, reco.third.d);
+		}
+	} else {
+		if doj < reco.near.d {
+			*acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(doj, reco.near.d);
+		} else if doj < reco.seco.d {
+			*acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(reco.near.d, doj);
+		}
+	}
+}
+
+/// Find the best swap for object j, with pruning via the triangle inequality
+///
+/// By the triangle inequality, |d(o,m) - d(j,m)| is a lower bound for d(o,j),
+/// which we evaluate for the nearest and second nearest medoid of o. If o is
+/// at least as far from j as from its third nearest medoid, it only changes
+/// when its nearest or second nearest medoid is removed.
+#[inline]
+fn find_best_swap_pammedsil_pruned<M, N, L>(
+	mat: &M,
+	med: &[usize],
+	data: &[Reco<N>],
+	j: usize,
+	dj: &mut [L],
+	accm: &mut [L],
+	close: &mut Vec<(usize, N)>,
+) -> (L, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + AddAssign + From<N> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	for (d, &m) in dj.iter_mut().zip(med.iter()) {
+		*d = <L as From<N>>::from(mat.get(j, m));
+	}
+	accm.fill(L::zero());
+	close.clear();
+	for (o, reco) in data.iter().enumerate() {
+		if o == j {
+			continue;
+		}
+		let lb1 = (<L as From<N>>::from(reco.near.d) - dj[reco.near.i as usize]).abs();
+		let lb2 = (<L as From<N>>::from(reco.seco.d) - dj[reco.seco.i as usize]).abs();
+		if lb1.max(lb2) >= <L as From<N>>::from(reco.third.d) {
+			let cur = safe_ratio::<N, L>(reco.near.d, reco.seco.d);
+			accm[reco.near.i as usize] += cur - safe_ratio::<N, L>(reco.seco.d, reco.third.d);
+			accm[reco.seco.i as usize] += cur - safe_ratio::<N, L>(reco.near.d, reco.third.d);
+		} else {
+			close.push((o, mat.get(o, j)));
+		}
+	}
+	let recj = &data[j];
+	let mut best = (L::zero(), usize::MAX);
+	for (m, &accm) in accm.iter().enumerate() {
+		let mut acc: L = safe_ratio::<N, L>(recj.near.d, recj.seco.d) + accm; // j becomes medoid
+		for &(o, doj) in close.iter() {
+			swap_gain(&mut acc, &data[o], doj, m);
 		}
 		if acc > best.0 {
 			best = (acc, m);
@@ -223,7 +1284,7 @@ fn find_best_swap_pammedsil_k2<M, N, L>(mat: &M, med: &[usize], data: &[Reco<N>]
 	let recj = &data[j];
 	let mut best = (L::zero(), usize::MAX);
 	for (m, _) in med.iter().enumerate() {
-		let mut acc: L = _loss::<N, L>(recj.near.d, recj.seco.d); // j becomes medoid
+		let mut acc: L = safe_ratio::<N, L>(recj.near.d, recj.seco.d); // j becomes medoid
 		for (o, reco) in data.iter().enumerate() {
 			if o == j {
 				continue;
@@ -233,22 +1294,22 @@ fn find_best_swap_pammedsil_k2<M, N, L>(mat: &M, med: &[usize], data: &[Reco<N>]
 			if reco.near.i as usize == m {
 				if doj < reco.seco.d {
 					// Assign to new medoid:
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(doj, reco.seco.d);
+					acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(doj, reco.seco.d);
 				} else {
 					// Assign to second nearest instead:
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(reco.seco.d, doj);
+					acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(reco.seco.d, doj);
 				}
 			} else if reco.seco.i as usize == m  {
 				if doj < reco.near.d {
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(doj, reco.near.d);
+					acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(doj, reco.near.d);
 				} else {
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(reco.near.d, doj);
+					acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(reco.near.d, doj);
 				}
 			} else {
 				if doj < reco.near.d {
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(doj, reco.near.d);
+					acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(doj, reco.near.d);
 				} else if doj < reco.seco.d {
-					acc += _loss::<N, L>(reco.near.d, reco.seco.d) - _loss::<N, L>(reco.near.d, doj);
+					acc += safe_ratio::<N, L>(reco.near.d, reco.seco.d) - safe_ratio::<N, L>(reco.near.d, doj);
 				}
 			}
 		}
@@ -265,6 +1326,9 @@ fn pammedsil_build_initialize<M, N, L>(
 	meds: &mut Vec<usize>,
 	data: &mut Vec<Reco<N>>,
 	k: usize,
+	cancel: Option<&AtomicBool>,
+	ties: &mut BuildTieBreaking,
+	shortfall: BuildShortfall,
 ) -> L
 	where
 		N: Zero + PartialOrd + Copy,
@@ -272,8 +1336,8 @@ fn pammedsil_build_initialize<M, N, L>(
 		M: ArrayAdapter<N>,
 {
 	let n = mat.len();
-	// choose first medoid
-	let mut best = (L::zero(), k);
+	// choose first medoid, on equal sums by `ties` (see pammedsil_build_all_ties)
+	let (mut best, mut n_ties) = ((L::zero(), k), 0);
 	for i in 0..n {
 		let mut sum = L::zero();
 		for j in 0..n {
@@ -281,19 +1345,24 @@ fn pammedsil_build_initialize<M, N, L>(
 				sum += <L as From<N>>::from(mat.get(j, i));
 			}
 		}
-		if i == 0 || sum < best.0 {
+		if i == 0 {
+			(best, n_ties) = ((sum, i), 1);
+		} else if ties.replaces(sum, best.0, &mut n_ties) {
 			best = (sum, i);
 		}
 	}
 	let mut loss = best.0;
 	meds.push(best.1);
 	for j in 0..n {
-		data.push(Reco::new(0, mat.get(j, best.1), u32::MAX, N::zero(), u32::MAX, N::zero()));
+		data.push(Reco { near: DistancePair::new(0, mat.get(j, best.1)), seco: DistancePair::sentinel(), third: DistancePair::sentinel() });
 	}
 	// choose remaining medoids
 	for l in 1..k {
-		best = (L::zero(), k);
-		for (i, _) in data.iter().enumerate().skip(1) {
+		(best, n_ties) = ((L::zero(), k), 1);
+		for (i, _) in data.iter().enumerate() {
+			if is_cancelled(cancel) {
+				return loss; // keep the medoids chosen so far
+			}
 			let mut sum = -<L as From<N>>::from(data[i].near.d);
 			for (j, dj) in data.iter().enumerate() {
 				if j != i {
@@ -303,11 +1372,17 @@ fn pammedsil_build_initialize<M, N, L>(
 					}
 				}
 			}
-			if i == 0 || sum < best.0 {
+			if ties.replaces(sum, best.0, &mut n_ties) {
 				best = (sum, i);
 			}
 		}
-		if best.0 >= L::zero() { break; } // No more improvement, duplicates
+		if best.0 >= L::zero() {
+			// No more improvement, duplicates
+			match shortfall {
+				BuildShortfall::Truncate => break,
+				BuildShortfall::FillFarthest => best.1 = farthest_non_medoid(meds, data.iter().map(|r| r.near.d)),
+			}
+		}
 		// Update assignments:
 		loss = L::zero();
 		for (j, recj) in data.iter_mut().enumerate() {
@@ -321,14 +1396,14 @@ fn pammedsil_build_initialize<M, N, L>(
 					recj.third = recj.seco;
 					recj.seco = recj.near;
 					recj.near = DistancePair::new(l as u32, dj);
-				} else if recj.seco.i == u32::MAX || dj < recj.seco.d {
+				} else if recj.seco.is_sentinel() || dj < recj.seco.d {
 					recj.third = recj.seco;
 					recj.seco = DistancePair::new(l as u32, dj);
-				} else if recj.third.i == u32::MAX || dj < recj.third.d {
+				} else if recj.third.is_sentinel() || dj < recj.third.d {
 					recj.third = DistancePair::new(l as u32, dj);
 				}
 			}
-			loss += _loss::<N, L>(recj.near.d, recj.seco.d);
+			loss += safe_ratio::<N, L>(recj.near.d, recj.seco.d);
 		}
 		meds.push(best.1);
 	}
@@ -339,8 +1414,12 @@ fn pammedsil_build_initialize<M, N, L>(
 mod tests {
 	// TODO: use a larger, much more interesting example.
 	use crate::{
-		arrayadapter::LowerTriangle, pammedsil, pammedsil_swap, silhouette, medoid_silhouette, util::assert_array,
+		apply_swap, canonicalize, arrayadapter::{ArrayAdapter, LowerTriangle}, custom_build, initial_reco, pammedsil, pammedsil_build_score, pammedsil_resume, pammedsil_swap_arc, pammedsil_swap, pammedsil_swap_traced, pammedsil_swap_patience, pammedsil_swap_with_tie_breaking, pammedsil_swap_cancellable, pammedsil_cancellable, pammedsil_swap_pruned,
+		assign_to_medoids, pammedsil_swap_with_order, pammedsil_with_tie_breaking, pammedsil_build_all_ties, pammedsil_swap_eps, pammedsil_with_shortfall, pammedsil_build_k2, pammedsil_with_init, BuildShortfall, BuildTieBreaking, InitStrategy, KMedoidsResult, silhouette, medoid_silhouette, util::assert_array,
 	};
+	use rand::{rngs::StdRng, Rng, SeedableRng};
+	use std::sync::atomic::AtomicBool;
+	use std::sync::Arc;
 
 	#[test]
 	fn test_pammedsil() {
@@ -348,7 +1427,8 @@ mod tests {
 			n: 5,
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
-		let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = pammedsil(&data, 3, 10);
+		let (loss, mut assi, mut meds, n_iter, n_swap): (f64, _, _, _, _) = pammedsil(&data, 3, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
 		println!("PAMMedSil: {:?} {:?} {:?} {:?} {:?} {:?}", loss, n_iter, n_swap, sil, assi, meds);
@@ -356,8 +1436,8 @@ mod tests {
 		assert_eq!(n_iter, 1, "iterations not as expected");
 		assert_eq!(loss, 0.9047619047619048, "loss not as expected");
 		assert_eq!(msil, 0.9047619047619048, "Medoid Silhouettte not as expected");
-		assert_array(assi, vec![0, 0, 2, 1, 1], "assignment not as expected");
-		assert_array(meds, vec![0, 3, 2], "medoids not as expected");
+		assert_array(assi, vec![0, 0, 1, 2, 2], "assignment not as expected");
+		assert_array(meds, vec![0, 2, 3], "medoids not as expected");
 		assert_eq!(sil, 0.5622222222222222, "Silhouette not as expected");
 	}
 
@@ -368,7 +1448,8 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![0, 1, 2];
-		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds, 10);
+		let (loss, mut assi, n_iter, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
 		println!("PAMMedSil: {:?} {:?} {:?} {:?} {:?} {:?}", loss, n_iter, n_swap, sil, assi, meds);
@@ -376,11 +1457,165 @@ mod tests {
 		assert_eq!(msil, 0.9047619047619048, "Medoid Silhouette not as expected");
 		assert_eq!(n_swap, 1, "swaps not as expected");
 		assert_eq!(n_iter, 2, "iterations not as expected");
-		assert_array(assi, vec![0, 0, 2, 1, 1], "assignment not as expected");
-		assert_array(meds, vec![0, 3, 2], "medoids not as expected");
+		assert_array(assi, vec![0, 0, 1, 2, 2], "assignment not as expected");
+		assert_array(meds, vec![0, 2, 3], "medoids not as expected");
 		assert_eq!(sil, 0.5622222222222222, "Silhouette not as expected");
 	}
 
+	#[test]
+	fn testpammedsil_swap_traced() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let mut events = Vec::new();
+		let result: KMedoidsResult<f64> = pammedsil_swap_traced(&data, &mut vec![0, 1, 2], 10, |e| events.push(e));
+		let mut meds = vec![0, 1, 2];
+		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds, 10);
+		assert_eq!(result, KMedoidsResult::new(loss, assi, meds, n_iter, n_swap), "result not as expected");
+		assert_eq!(events.len(), 1, "swaps not as expected");
+		let e = events[0];
+		assert_eq!((e.old_medoid, e.new_medoid, e.distance), (1, 3, 5), "swap not as expected");
+		let (before, _): (f64, _) = medoid_silhouette(&data, &[0, 1, 2], false);
+		assert_eq!((e.loss_before, e.loss_after), (before, loss), "losses not as expected");
+		assert!(e.loss_after > e.loss_before, "swap did not improve");
+	}
+
+	#[test]
+	fn testpammedsil_swap_arc() {
+		let mut rng = StdRng::seed_from_u64(6);
+		let data = LowerTriangle { n: 30, data: (0..435).map(|_| rng.gen_range(1..100) as f64).collect::<Vec<f64>>() };
+		let mut meds = vec![0, 1, 2];
+		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds, 100);
+		let shared = Arc::new(data);
+		let handle = {
+			let shared = Arc::clone(&shared);
+			std::thread::spawn(move || pammedsil_swap_arc::<_, _, f64>(shared, vec![0, 1, 2], 100))
+		};
+		let result = handle.join().unwrap();
+		assert_eq!(result, KMedoidsResult::new(loss, assi, meds, n_iter, n_swap), "result not as expected");
+		assert_eq!(Arc::strong_count(&shared), 1, "matrix not released");
+	}
+
+	#[test]
+	fn testpammedsil_apply_swap() {
+		let mut rng = StdRng::seed_from_u64(7);
+		let data = LowerTriangle { n: 30, data: (0..435).map(|_| rng.gen_range(1..100)).collect::<Vec<u32>>() };
+		let mut meds = vec![0, 1, 2, 3];
+		let (mut loss, mut reco): (f64, _) = initial_reco(&data, &meds);
+		for _ in 0..3 {
+			let (mut expect, mut best) = (meds.clone(), (0., 0, 0));
+			let (eloss, eassi, _, _): (f64, _, _, _) = pammedsil_swap(&data, &mut expect, 1);
+			for j in 0..data.n {
+				if meds.contains(&j) {
+					continue;
+				}
+				let (change, b): (f64, usize) = super::find_best_swap_pammedsil(&data, &meds, &reco, j);
+				if change > best.0 {
+					best = (change, b, j);
+				}
+			}
+			let newloss: f64 = apply_swap(&data, &mut meds, &mut reco, best.1, best.2);
+			assert!((loss - best.0 - newloss).abs() < 1e-12, "change not as expected");
+			loss = newloss;
+			assert_eq!(meds, expect, "medoids not as expected");
+			assert_eq!(1. - loss / data.n as f64, eloss, "loss not as expected");
+			assert_eq!(reco.iter().map(|r| r.near.i as usize).collect::<Vec<_>>(), eassi, "assignment not as expected");
+		}
+	}
+
+	#[test]
+	fn testpammedsil_swap_patience() {
+		// {0, 1} and {1, 3} have the same loss, and the best pair {2, 3} is only one swap from {1, 3}
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![3, 2, 1, 1, 2, 2, 1, 2, 1, 3],
+		};
+		let msil = |meds: &[usize]| medoid_silhouette::<_, u32, f64>(&data, meds, false).0;
+		assert_eq!(msil(&[0, 1]), msil(&[1, 3]), "no plateau");
+		assert_eq!(pammedsil_build_k2::<_, _, f64>(&data), [2, 3], "best pair not as expected");
+		// rounding makes the swap onto the plateau look like an improvement
+		let mut swaps = vec![];
+		let result: KMedoidsResult<f64> = pammedsil_swap_traced(&data, &mut vec![0, 1], 100, |e| swaps.push((e.old_medoid, e.new_medoid, e.loss_before == e.loss_after)));
+		assert_eq!(swaps, vec![(0, 3, true)], "swaps not as expected");
+		// without patience, stop on the plateau
+		let mut meds0 = vec![0, 1];
+		let (loss0, assi0, n_iter0, n_swap0): (f64, _, _, _) = pammedsil_swap(&data, &mut meds0, 100);
+		assert_eq!((loss0, n_swap0), (result.loss, 1), "not stopped on the plateau");
+		let mut meds = vec![0, 1];
+		let result: (f64, _, _, _) = pammedsil_swap_patience(&data, &mut meds, 100, 0);
+		assert_eq!(result, (loss0, assi0, n_iter0, n_swap0), "patience 0 not as pammedsil_swap");
+		// with patience, continue to the best pair
+		for patience in 1..=3 {
+			let mut meds = vec![0, 1];
+			let (loss, assi, _, n_swap): (f64, _, _, _) = pammedsil_swap_patience(&data, &mut meds, 100, patience);
+			let mut sorted = meds.clone();
+			sorted.sort_unstable();
+			assert_eq!((sorted, n_swap), (vec![2, 3], 2), "medoids not as expected");
+			assert_eq!(loss, msil(&[2, 3]), "loss not the medoid silhouette");
+			let (_, eassi): (f64, _) = assign_to_medoids(&data, &meds);
+			assert_array(assi, eassi, "assignment not as expected");
+		}
+	}
+
+	#[test]
+	fn testpammedsil_build_k2() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let best = pammedsil_build_k2::<_, _, f64>(&data);
+		let (exhaustive, _): (f64, _) = medoid_silhouette(&data, &best, false);
+		let (greedy, _, meds, _, _): (f64, _, _, _, _) = pammedsil(&data, 2, 0);
+		assert!(exhaustive >= greedy, "exhaustive BUILD worse than greedy BUILD: {:?} {:?}", best, meds);
+		// the best of all pairs, on random data
+		let mut rng = StdRng::seed_from_u64(8);
+		let data = LowerTriangle { n: 20, data: (0..190).map(|_| rng.gen_range(1..100)).collect::<Vec<u32>>() };
+		let best = pammedsil_build_k2::<_, _, f64>(&data);
+		let (exhaustive, _): (f64, _) = medoid_silhouette(&data, &best, false);
+		for a in 0..20 {
+			for b in (a + 1)..20 {
+				let (msil, _): (f64, _) = medoid_silhouette(&data, &[a, b], false);
+				assert!(msil <= exhaustive + 1e-12, "pair {} {} better than {:?}", a, b, best);
+			}
+		}
+		let (loss, assi, meds, _, n_swap): (f64, _, _, _, _) = pammedsil_with_init(&data, 2, 100, InitStrategy::ExhaustiveK2);
+		assert_eq!((loss, meds.clone(), n_swap), (exhaustive, best.to_vec(), 0), "result not as expected");
+		let (_, eassi): (f64, _) = assign_to_medoids(&data, &meds);
+		assert_array(assi, eassi, "assignment not as expected");
+		let result: (f64, _, _, _, _) = pammedsil_with_init(&data, 3, 100, InitStrategy::Build);
+		assert_eq!(result, pammedsil(&data, 3, 100), "default not as pammedsil");
+		// every distance is read once
+		use crate::arrayadapter::ClosureMatrix;
+		use std::sync::atomic::{AtomicUsize, Ordering};
+		let calls = AtomicUsize::new(0);
+		let counted = ClosureMatrix::new(20, |i: usize, j: usize| {
+			calls.fetch_add(1, Ordering::Relaxed);
+			data.get(i, j)
+		});
+		assert_eq!(pammedsil_build_k2::<_, _, f64>(&counted), best, "medoids not as expected");
+		assert_eq!(calls.load(Ordering::Relaxed), 20 * 20, "distance calls not as expected");
+		// on ties, the lowest indexes
+		let data = LowerTriangle { n: 4, data: vec![1, 1, 1, 1, 1, 1] };
+		assert_eq!(pammedsil_build_k2::<_, _, f64>(&data), [0, 1], "medoids not as expected");
+	}
+
+	#[test]
+	fn testpammedsil_resume() {
+		let mut rng = StdRng::seed_from_u64(5);
+		let data = LowerTriangle { n: 30, data: (0..435).map(|_| rng.gen_range(1..100)).collect::<Vec<u32>>() };
+		for init in [vec![0, 1, 2], vec![29, 3, 17, 8]] {
+			let (mut meds, mut rmeds) = (init.clone(), init.clone());
+			let (loss, assi, n_iter, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds, 100);
+			let result: KMedoidsResult<f64> = pammedsil_resume(&data, &mut rmeds, 100);
+			assert_eq!(result, KMedoidsResult::new(loss, assi, meds.clone(), n_iter, n_swap), "result not as expected");
+			assert_eq!(rmeds, meds, "medoids not as expected");
+			// resuming from the result makes no further swaps
+			let again: KMedoidsResult<f64> = pammedsil_resume(&data, &mut rmeds, 100);
+			assert_eq!((again.loss, again.n_swap, again.n_iter), (result.loss, 0, 1), "resumed result not as expected");
+		}
+	}
+
 	#[test]
 	fn testpammedsil_simple2() {
 		let data = LowerTriangle {
@@ -388,7 +1623,8 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![0, 1];
-		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds, 10);
+		let (loss, mut assi, n_iter, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
 		println!("PAMMedSil: {:?} {:?} {:?} {:?} {:?} {:?}", loss, n_iter, n_swap, sil, assi, meds);
@@ -400,4 +1636,335 @@ mod tests {
 		assert_array(meds, vec![0, 4], "medoids not as expected");
 		assert_eq!(sil, 0.7522494172494172, "Silhouette not as expected");
 	}
+
+	#[test]
+	fn testpammedsil_cancelled() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let cancel = AtomicBool::new(true);
+		let mut meds = vec![0, 1, 2];
+		let (loss, mut assi, n_iter, n_swap, cancelled): (f64, _, _, _, _) = pammedsil_swap_cancellable(&data, &mut meds, 10, &cancel);
+		canonicalize(&mut meds, &mut assi);
+		let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
+		assert!(cancelled, "run not cancelled");
+		assert_eq!(n_swap, 0, "swaps not as expected");
+		assert_eq!(n_iter, 1, "iterations not as expected");
+		assert_eq!(loss, msil, "loss not as expected");
+		assert_array(assi, vec![0, 1, 2, 0, 0], "assignment not as expected");
+		assert_array(meds, vec![0, 1, 2], "medoids not as expected");
+
+		// Cancelled during BUILD, after the first medoid
+		let (loss, assi, meds, _, _, cancelled): (f64, _, _, _, _, _) = pammedsil_cancellable(&data, 3, 10, &cancel);
+		assert!(cancelled, "run not cancelled");
+		assert_eq!(loss, 1., "loss not as expected");
+		assert_array(assi, vec![0, 0, 0, 0, 0], "assignment not as expected");
+		assert_array(meds, vec![0], "medoids not as expected");
+
+		let cancel = AtomicBool::new(false);
+		let (loss, _, meds, _, _, cancelled): (f64, _, _, _, _, _) = pammedsil_cancellable(&data, 3, 10, &cancel);
+		assert!(!cancelled, "run cancelled");
+		assert_eq!(loss, 0.9047619047619048, "loss not as expected");
+		assert_array(meds, vec![0, 3, 2], "medoids not as expected");
+	}
+
+	#[cfg(feature = "rand")]
+	#[test]
+	fn testpammedsil_pruned() {
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		// Clustered points on an integer grid, with Manhattan distance (a metric)
+		let mut rng = StdRng::seed_from_u64(1);
+		let points = (0..150)
+			.map(|i| (i % 5 * 100 + rng.gen_range(0..20), i % 5 * 50 + rng.gen_range(0..20)))
+			.collect::<Vec<(i32, i32)>>();
+		let n = points.len();
+		let mut data = Vec::with_capacity(n * (n - 1) / 2);
+		for i in 1..n {
+			for j in 0..i {
+				data.push(((points[i].0 - points[j].0).abs() + (points[i].1 - points[j].1).abs()) as u32);
+			}
+		}
+		let data = LowerTriangle { n, data };
+		for k in 2..8 {
+			let init = crate::random_initialization(n, k, &mut rng);
+			let mut meds = init.clone();
+			let (loss, assi, n_iter, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds, 100);
+			let mut meds2 = init.clone();
+			let (loss2, assi2, n_iter2, n_swap2): (f64, _, _, _) = pammedsil_swap_pruned(&data, &mut meds2, 100);
+			assert_eq!(loss, loss2, "loss not as expected");
+			assert_eq!(n_iter, n_iter2, "iterations not as expected");
+			assert_eq!(n_swap, n_swap2, "swaps not as expected");
+			assert_array(assi, assi2, "assignment not as expected");
+			assert_array(meds, meds2, "medoids not as expected");
+		}
+	}
+
+	#[test]
+	fn testpammedsil_maxiter0() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for init in [vec![1], vec![1, 2], vec![1, 2, 4]] {
+			let mut meds = init.clone();
+			let (loss, assi, n_iter, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds, 0);
+			let (msil, _): (f64, _) = medoid_silhouette(&data, &init, false);
+			let (_, expected_assi): (f64, _) = assign_to_medoids(&data, &init);
+			assert_eq!(loss, msil, "loss not as expected");
+			assert_eq!(n_swap, 0, "swaps not as expected");
+			assert_eq!(n_iter, 0, "iterations not as expected");
+			assert_array(assi, expected_assi, "assignment not as expected");
+			assert_array(meds, init, "medoids not as expected");
+		}
+		// with k = 1, the loss is the medoid silhouette, with and without SWAP
+		let (loss0, _, _, _): (f64, _, _, _) = pammedsil_swap(&data, &mut vec![1], 0);
+		let (loss1, _, meds, _, _): (f64, _, _, _, _) = pammedsil(&data, 1, 1);
+		let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
+		assert_eq!((loss0, loss1), (msil, msil), "loss not as expected");
+	}
+
+	#[cfg(feature = "rand")]
+	#[test]
+	fn testrand_pammedsil_swap() {
+		use crate::rand_pammedsil_swap;
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let n = 100;
+		let mut rng = StdRng::seed_from_u64(1);
+		let data = LowerTriangle {
+			n,
+			data: (0..n * (n - 1) / 2).map(|_| rng.gen_range(1..1000000)).collect::<Vec<u32>>(),
+		};
+		let order = (0..n).rev().collect::<Vec<_>>();
+		for seed in 0..5 {
+			let init = crate::random_initialization(n, 4, &mut rng);
+			let mut meds = init.clone();
+			let (loss, assi, n_iter, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds, 100);
+			// Without ties, the best swap does not depend on the order
+			let mut meds2 = init.clone();
+			let (loss2, assi2, n_iter2, n_swap2): (f64, _, _, _) =
+				rand_pammedsil_swap(&data, &mut meds2, 100, &mut StdRng::seed_from_u64(seed));
+			assert_eq!(loss, loss2, "loss not as expected");
+			assert_eq!((n_iter, n_swap), (n_iter2, n_swap2), "iterations not as expected");
+			assert_array(assi.clone(), assi2, "assignment not as expected");
+			assert_array(meds.clone(), meds2, "medoids not as expected");
+			let mut meds3 = init.clone();
+			let (loss3, assi3, _, _): (f64, _, _, _) = pammedsil_swap_with_order(&data, &mut meds3, 100, &order);
+			assert_eq!(loss, loss3, "loss not as expected");
+			assert_array(assi, assi3, "assignment not as expected");
+			assert_array(meds, meds3, "medoids not as expected");
+		}
+	}
+
+	#[test]
+	fn testpammedsil_tie_breaking() {
+		// A cycle of 4 points, all column sums are equal
+		let data = LowerTriangle {
+			n: 4,
+			data: vec![1, 2, 1, 1, 2, 1],
+		};
+		// With maxiter = 0, we get the BUILD result
+		let (_, _, meds, _, _): (f64, _, _, _, _) = pammedsil(&data, 2, 0);
+		assert_array(meds, vec![0, 1], "medoids not as expected");
+		let (_, _, meds, _, _): (f64, _, _, _, _) = pammedsil_with_tie_breaking(&data, 2, 0, BuildTieBreaking::FirstIndex);
+		assert_array(meds, vec![0, 1], "medoids not as expected");
+		let (_, _, meds, _, _): (f64, _, _, _, _) = pammedsil_with_tie_breaking(&data, 2, 0, BuildTieBreaking::LastIndex);
+		assert_array(meds, vec![3, 2], "medoids not as expected");
+		let (_, _, meds, _, _): (f64, _, _, _, _) = pammedsil_with_tie_breaking(&data, 2, 0, BuildTieBreaking::default());
+		assert_array(meds, vec![0, 1], "medoids not as expected");
+
+		#[cfg(feature = "rand")]
+		{
+			use rand::{rngs::StdRng, SeedableRng};
+			let mut seen = [false; 4];
+			for seed in 0..20 {
+				let mut rng = StdRng::seed_from_u64(seed);
+				let (_, _, meds, _, _): (f64, _, _, _, _) =
+					pammedsil_with_tie_breaking(&data, 1, 0, BuildTieBreaking::Random(&mut rng));
+				seen[meds[0]] = true;
+			}
+			assert!(seen.iter().all(|&s| s), "random tie breaking not diverse");
+		}
+	}
+
+	#[test]
+	fn testpammedsil_swap_assignment_ties() {
+		// Points at 0, 2, 1 and 5: point 2 is equally near to both medoids
+		let pos = [0i32, 2, 1, 5];
+		let data = ndarray::Array2::from_shape_fn((4, 4), |(i, j)| (pos[i] - pos[j]).unsigned_abs());
+		let swap = |ties: BuildTieBreaking<'_>| pammedsil_swap_with_tie_breaking::<_, _, f64>(&data.view(), &mut vec![0, 1], 0, ties);
+		let (loss, assi, _, _) = swap(BuildTieBreaking::FirstIndex);
+		assert_array(assi, vec![0, 1, 0, 1], "assignment not as expected");
+		let (eloss, eassi, _, _): (f64, _, _, _) = pammedsil_swap(&data.view(), &mut vec![0, 1], 0);
+		assert_eq!(loss, eloss, "loss not as expected");
+		assert_array(eassi, vec![0, 1, 0, 1], "default assignment not as expected");
+		let (lloss, assi, _, _) = swap(BuildTieBreaking::LastIndex);
+		assert_array(assi, vec![0, 1, 1, 1], "assignment not as expected");
+		assert_eq!(lloss, loss, "loss depends on ties");
+		// a medoid is always assigned to itself, even at distance zero to another medoid
+		let dup = ndarray::arr2(&[[0, 0, 1], [0, 0, 1], [1, 1, 0]]);
+		let (_, assi, _, _): (f64, _, _, _) = pammedsil_swap_with_tie_breaking(&dup.view(), &mut vec![0, 1], 0, BuildTieBreaking::LastIndex);
+		assert_array(assi, vec![0, 1, 1], "medoid assignment not as expected");
+		#[cfg(feature = "rand")]
+		{
+			let mut seen = [false; 2];
+			for seed in 0..20 {
+				let mut rng = StdRng::seed_from_u64(seed);
+				let (rloss, assi, _, _) = swap(BuildTieBreaking::Random(&mut rng));
+				assert_eq!(rloss, loss, "loss depends on ties");
+				seen[assi[2]] = true;
+			}
+			assert!(seen.iter().all(|&s| s), "random tie breaking not diverse");
+		}
+	}
+
+	#[test]
+	fn testpammedsil_do_swap_consistency() {
+		use crate::fastermsc::{do_swap, initial_assignment};
+		let mut rng = StdRng::seed_from_u64(6);
+		// random distances, without ties
+		let data = LowerTriangle { n: 12, data: (0..66).map(|_| rng.gen::<f64>()).collect::<Vec<f64>>() };
+		let pairs = |reco: &[crate::util::Reco<f64>]| reco.iter().map(|r| [r.near, r.seco, r.third].map(|p| (p.i, p.d))).collect::<Vec<_>>();
+		for init in [vec![0, 5], vec![0, 5, 9], vec![3, 1, 10, 7]] {
+			for b in 0..init.len() {
+				for j in (0..12).filter(|j| !init.contains(j)) {
+					let mut meds = init.clone();
+					let (_, mut reco): (f64, _) = initial_assignment(&data, &meds);
+					let loss: f64 = do_swap(&data, &mut meds, &mut reco, b, j);
+					assert_eq!(meds[b], j, "medoid not replaced");
+					let (eloss, expect): (f64, _) = initial_assignment(&data, &meds);
+					assert_eq!(pairs(&reco), pairs(&expect), "data not consistent after swapping {} with {} of {:?}", b, j, init);
+					assert!((loss - eloss).abs() < 1e-12, "loss not consistent after swapping {} with {} of {:?}", b, j, init);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn testpammedsil_build_first_medoid_ties() {
+		// Points at -1, 1, -3, 3, -5, 5: the first two are symmetric, with the smallest column sum
+		let pos = [-1i32, 1, -3, 3, -5, 5];
+		let data = ndarray::Array2::from_shape_fn((6, 6), |(i, j)| (pos[i] - pos[j]).unsigned_abs());
+		let sums = (0..6).map(|j| data.column(j).sum()).collect::<Vec<u32>>();
+		assert_eq!(sums[0], sums[1], "column sums not equal");
+		assert_eq!(pammedsil_build_all_ties::<_, _, f64>(&data.view()), vec![0, 1], "ties not as expected");
+		// BUILD deterministically chooses the lowest index
+		let (_, _, meds, _, _): (f64, _, _, _, _) = pammedsil(&data.view(), 1, 0);
+		assert_array(meds, vec![0], "first medoid not as expected");
+		let (_, _, meds, _, _): (f64, _, _, _, _) = pammedsil(&data.view(), 3, 0);
+		assert_eq!(meds[0], 0, "first medoid not as expected");
+		let (_, _, meds, _, _): (f64, _, _, _, _) = pammedsil_with_tie_breaking(&data.view(), 1, 0, BuildTieBreaking::LastIndex);
+		assert_array(meds, vec![1], "first medoid not as expected");
+	}
+
+	#[test]
+	fn testpammedsil_eps() {
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		// Uniform random points, with medoids initialized on the left
+		let mut rng = StdRng::seed_from_u64(6);
+		let mut points = (0..50).map(|_| (rng.gen_range(0..100), rng.gen_range(0..100))).collect::<Vec<(i32, i32)>>();
+		points.sort();
+		let n = points.len();
+		let mut data = Vec::with_capacity(n * (n - 1) / 2);
+		for i in 1..n {
+			for j in 0..i {
+				data.push(((points[i].0 - points[j].0).abs() + (points[i].1 - points[j].1).abs()) as u32);
+			}
+		}
+		let data = LowerTriangle { n, data };
+		let mut meds = (0..10).collect::<Vec<_>>();
+		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = pammedsil_swap(&data, &mut meds, 100);
+		let mut meds2 = (0..10).collect::<Vec<_>>();
+		let exact: KMedoidsResult<f64> = pammedsil_swap_eps(&data, &mut meds2, 100, 0.);
+		assert_eq!(exact, KMedoidsResult::new(loss, assi, meds, n_iter, n_swap), "result not as expected");
+		let mut meds3 = (0..10).collect::<Vec<_>>();
+		let early: KMedoidsResult<f64> = pammedsil_swap_eps(&data, &mut meds3, 100, 0.01);
+		assert!(early.n_iter * 2 <= n_iter, "iterations not reduced enough: {} vs. {}", early.n_iter, n_iter);
+		assert!(early.loss > 0.98 * loss, "loss not as expected: {} vs. {}", early.loss, loss);
+		assert_eq!(early.n_swap, early.n_iter - 1, "swaps not as expected");
+		assert_eq!(early.medoids, meds3, "medoids not as expected");
+	}
+
+	#[test]
+	fn test_custom_build_pammedsil_build_score() {
+		let mut rng = StdRng::seed_from_u64(1);
+		let data = LowerTriangle { n: 30, data: (0..435).map(|_| rng.gen_range(1..20)).collect::<Vec<u32>>() };
+		for k in 1..=6 {
+			// with maxiter = 0, only BUILD is performed
+			let (_, _, meds, _, _): (f64, _, _, _, _) = pammedsil(&data, k, 0);
+			assert_array(custom_build(&data, k, pammedsil_build_score::<_, _, f64>(&data)), meds, "medoids not as expected");
+		}
+	}
+
+	#[test]
+	fn test_pammedsil_build_first_object() {
+		// the first medoid is object 2, the best second medoid is object 0
+		let pos = [0i32, 10, 11, 12, 13];
+		let data = LowerTriangle {
+			n: 5,
+			data: (1..5).flat_map(|i| (0..i).map(move |j| pos[i].abs_diff(pos[j]))).collect(),
+		};
+		let (_, _, meds, _, _): (f64, _, _, _, _) = pammedsil(&data, 2, 0);
+		assert_array(meds.clone(), vec![2, 0], "medoids not as expected");
+		let (_, _, pmeds): (f64, _, _) = crate::pam_build(&data, 2);
+		assert_array(meds, pmeds, "medoids not as expected");
+	}
+
+	#[test]
+	fn testpammedsil_not_debug() {
+		// a distance type without Debug
+		#[derive(Clone, Copy, PartialEq, PartialOrd)]
+		struct Dist(u32);
+		impl std::ops::Add for Dist {
+			type Output = Dist;
+			fn add(self, other: Dist) -> Dist { Dist(self.0 + other.0) }
+		}
+		impl num_traits::Zero for Dist {
+			fn zero() -> Dist { Dist(0) }
+			fn is_zero(&self) -> bool { self.0 == 0 }
+		}
+		impl From<Dist> for f64 {
+			fn from(d: Dist) -> f64 { d.0.into() }
+		}
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1].into_iter().map(Dist).collect(),
+		};
+		let (loss, _, meds, _, _): (f64, _, _, _, _) = pammedsil(&data, 2, 10);
+		let (loss2, _, meds2, _, _): (f64, _, _, _, _) = pammedsil(&LowerTriangle { n: 5, data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1] }, 2, 10);
+		assert_eq!((loss, meds), (loss2, meds2), "result not as expected");
+	}
+
+	#[test]
+	#[cfg(all(debug_assertions, feature = "assertions"))]
+	#[should_panic(expected = "not symmetric")]
+	fn testpammedsil_swap_asymmetric() {
+		use crate::arrayadapter::SquareMatrix;
+		let mut dist = (0..25usize).map(|c| (c / 5).abs_diff(c % 5) as u32).collect::<Vec<_>>();
+		dist[3 * 5 + 4] = 7;
+		let mat = SquareMatrix::new(5, dist);
+		let _: (f64, _, _, _) = pammedsil_swap(&mat, &mut vec![0, 1, 2], 10);
+	}
+
+	#[test]
+	fn test_pammedsil_with_shortfall() {
+		// two distinct points, each three times
+		let data = LowerTriangle {
+			n: 6,
+			data: vec![0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1, 1, 0, 0],
+		};
+		let (loss, assi, meds, _, _): (f64, _, _, _, _) = pammedsil_with_shortfall(&data, 4, 10, BuildShortfall::Truncate);
+		assert_eq!(loss, 1., "loss not as expected");
+		assert_array(meds.clone(), vec![0, 3], "medoids not as expected");
+		assert_array(assi.clone(), vec![0, 0, 0, 1, 1, 1], "assignment not as expected");
+		let (loss2, assi2, meds2, _, _): (f64, _, _, _, _) = pammedsil(&data, 4, 10);
+		assert_eq!((loss2, assi2, meds2), (loss, assi, meds), "not the same as pammedsil");
+		let (loss, assi, meds, _, _): (f64, _, _, _, _) = pammedsil_with_shortfall(&data, 4, 10, BuildShortfall::FillFarthest);
+		assert_eq!(loss, 1., "loss not as expected");
+		assert_eq!(meds.len(), 4, "number of medoids not as expected");
+		assert!(assi.iter().all(|&a| a < 4), "assignment not as expected");
+		for (i, &a) in assi.iter().enumerate() {
+			assert_eq!(data.get(i, meds[a]), 0, "assignment not as expected");
+		}
+	}
 }
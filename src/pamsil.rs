@@ -97,6 +97,67 @@ pub fn pamsil<M, N, L>(mat: &M, k: usize, maxiter: usize) -> (L, Vec<usize>, Vec
 	(nloss, assi, meds, n_iter, n_swap) // also return medoids
 }
 
+/// Run FasterMSC, and refine the result with PAMSIL SWAP, optimizing the full silhouette.
+///
+/// PAMSIL is prohibitively slow, but starting from the FasterMSC result, a few
+/// iterations of the PAMSIL SWAP can capture most of the benefit. Each refinement swap
+/// is the best swap with respect to the full silhouette (steepest ascent), and only
+/// performed if it increases the silhouette, so the silhouette of the final result is
+/// never worse than the silhouette of the FasterMSC result.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `k` - the number of medoids to pick
+/// * `maxiter_msc` - the maximum number of iterations allowed for FasterMSC
+/// * `maxiter_sil` - the maximum number of iterations allowed for the refinement
+/// * `rng` - random number generator for the initial medoids
+///
+/// returns a tuple containing:
+/// * the final silhouette
+/// * the final medoid silhouette
+/// * the final cluster assignment
+/// * the final medoids
+/// * the number of refinement swaps performed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is less than 2 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let (sil, msil, assi, meds, n_swap): (f64, f64, _, _, _) = kmedoids::fastermsc_then_sil(&data, 2, 100, 10, &mut rand::thread_rng());
+/// println!("Silhouette is: {}", sil);
+/// ```
+#[cfg(feature = "rand")]
+pub fn fastermsc_then_sil<M, N, L>(
+	mat: &M,
+	k: usize,
+	maxiter_msc: usize,
+	maxiter_sil: usize,
+	rng: &mut impl rand::Rng,
+) -> (L, L, Vec<usize>, Vec<usize>, usize)
+	where
+		N: Zero + PartialOrd + Copy,
+		L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display,
+		M: ArrayAdapter<N>,
+{
+	let n = mat.len();
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(k >= 2 && k <= n, "k must be at least 2 and at most N");
+	let mut meds = crate::random_initialization(n, k, rng);
+	let _: (L, _, _, _) = crate::fastermsc(mat, &mut meds, 0, maxiter_msc);
+	let mut assi = vec![0; n];
+	assign_nearest::<M, N, L>(mat, &meds, &mut assi);
+	let (sil, _, n_swap) = pamsil_optimize(mat, &mut meds, &mut assi, maxiter_sil);
+	let (msil, _): (L, _) = medoid_silhouette(mat, &meds, false);
+	(sil, msil, assi, meds, n_swap)
+}
+
 /// Main optimization function of PAMSIL, not exposed (use pamsil_swap or pamsil)
 fn pamsil_optimize<M, N, L>(
 	mat: &M,
@@ -219,7 +280,7 @@ fn pamsil_build_initialize<M, N, L>(
 mod tests {
 	// TODO: use a larger, much more interesting example.
 	use crate::{
-		arrayadapter::LowerTriangle, pamsil, pamsil_swap, silhouette, util::assert_array,
+		canonicalize, arrayadapter::LowerTriangle, pamsil, pamsil_swap, fastermsc_then_sil, silhouette, medoid_silhouette, util::assert_array,
 	};
 
 	#[test]
@@ -228,7 +289,8 @@ mod tests {
 			n: 5,
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
-		let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = pamsil(&data, 2, 10);
+		let (loss, mut assi, mut meds, n_iter, n_swap): (f64, _, _, _, _) = pamsil(&data, 2, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		print!("PAMSil: {:?} {:?} {:?} {:?} {:?} {:?}", loss, n_iter, n_swap, sil, assi, meds);
 		assert_eq!(n_swap, 1, "swaps not as expected");
@@ -245,14 +307,15 @@ mod tests {
 			n: 5,
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
-		let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = pamsil(&data, 3, 10);
+		let (loss, mut assi, mut meds, n_iter, n_swap): (f64, _, _, _, _) = pamsil(&data, 3, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		print!("PAMSil k=3: {:?} {:?} {:?} {:?} {:?} {:?}", loss, n_iter, n_swap, sil, assi, meds);
 		assert_eq!(n_swap, 1, "swaps not as expected");
 		assert_eq!(n_iter, 2, "iterations not as expected");
 		assert_eq!(loss, 0.5622222222222222, "loss not as expected");
-		assert_array(assi, vec![0, 0, 2, 1, 1], "assignment not as expected");
-		assert_array(meds, vec![1, 3, 2], "medoids not as expected");
+		assert_array(assi, vec![0, 0, 1, 2, 2], "assignment not as expected");
+		assert_array(meds, vec![1, 2, 3], "medoids not as expected");
 		assert_eq!(sil, 0.5622222222222222, "Silhouette not as expected");
 	}
 
@@ -263,14 +326,48 @@ mod tests {
 			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
 		};
 		let mut meds = vec![0, 1, 2];
-		let (loss, assi, n_iter, n_swap): (f64, _, _, _) = pamsil_swap(&data, &mut meds, 10);
+		let (loss, mut assi, n_iter, n_swap): (f64, _, _, _) = pamsil_swap(&data, &mut meds, 10);
+		canonicalize(&mut meds, &mut assi);
 		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
 		print!("Fast: {:?} {:?} {:?} {:?} {:?} {:?}", loss, n_iter, n_swap, sil, assi, meds);
 		assert_eq!(loss, 0.5622222222222222, "loss not as expected");
 		assert_eq!(n_swap, 1, "swaps not as expected");
 		assert_eq!(n_iter, 2, "iterations not as expected");
-		assert_array(assi, vec![1, 1, 2, 0, 0], "assignment not as expected");
-		assert_array(meds, vec![3, 1, 2], "medoids not as expected");
+		assert_array(assi, vec![0, 0, 1, 2, 2], "assignment not as expected");
+		assert_array(meds, vec![1, 2, 3], "medoids not as expected");
 		assert_eq!(sil, 0.5622222222222222, "Silhouette not as expected");
 	}
+
+	#[test]
+	fn test_fastermsc_then_sil() {
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let fixture = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let mut rng = StdRng::seed_from_u64(1);
+		let points = (0..40).map(|_| (rng.gen_range(0..100), rng.gen_range(0..100))).collect::<Vec<(i32, i32)>>();
+		let mut data = Vec::with_capacity(40 * 39 / 2);
+		for i in 1..40 {
+			for j in 0..i {
+				data.push(((points[i].0 - points[j].0).abs() + (points[i].1 - points[j].1).abs()) as u32);
+			}
+		}
+		let random = LowerTriangle { n: 40, data };
+		for (data, k) in [(&fixture, 2), (&fixture, 3), (&random, 3), (&random, 5)] {
+			for seed in 0..5 {
+				let mut meds = crate::random_initialization(data.n, k, &mut StdRng::seed_from_u64(seed));
+				let (_, assi, _, _): (f64, _, _, _) = crate::fastermsc(data, &mut meds, 0, 100);
+				let (plain, _): (f64, _) = silhouette(data, &assi, false);
+				let (sil, msil, assi, meds, n_swap): (f64, f64, _, _, _) =
+					fastermsc_then_sil(data, k, 100, 10, &mut StdRng::seed_from_u64(seed));
+				let (sil2, _): (f64, _) = silhouette(data, &assi, false);
+				let (msil2, _): (f64, _) = medoid_silhouette(data, &meds, false);
+				assert!(sil >= plain, "silhouette decreased: {} < {}", sil, plain);
+				assert_eq!(sil, sil2, "silhouette not as expected");
+				assert_eq!(msil, msil2, "medoid silhouette not as expected");
+				assert!(n_swap <= 10, "swaps not as expected");
+			}
+		}
+	}
 }
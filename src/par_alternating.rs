@@ -0,0 +1,160 @@
+use crate::alternating::nearest_medoid;
+use crate::arrayadapter::ArrayAdapter;
+use core::ops::AddAssign;
+use num_traits::{Signed, Zero, FromPrimitive};
+use rayon::prelude::*;
+use std::convert::From;
+
+/// Run the Alternating algorithm, a k-means-style alternate optimization (parallel version).
+///
+/// Both the assignment of the points and the choice of the new medoids of the clusters
+/// are performed in parallel. The result is identical to [`alternating`](crate::alternating).
+/// For small data sets (n<1000) it is usually faster to use the non-parallel version.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `i64` or `f64` for the loss (must be signed)
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `maxiter` - the maximum number of iterations allowed
+///
+/// returns a tuple containing:
+/// * the final loss
+/// * the final cluster assignment
+/// * the number of iterations needed
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when k is 0 or larger than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter): (f64, _, _) = kmedoids::par_alternating(&data, &mut meds, 100);
+/// println!("Loss is: {}", loss);
+/// ```
+pub fn par_alternating<M, N, L>(mat: &M, med: &mut [usize], maxiter: usize) -> (L, Vec<usize>, usize)
+where
+	N: Zero + PartialOrd + Copy + Sync + Send,
+	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + FromPrimitive + std::fmt::Display + Sync + Send,
+	M: ArrayAdapter<N> + Sync + Send,
+{
+	let (n, k) = (mat.len(), med.len());
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(k > 0 && k < u32::MAX as usize, "invalid N");
+	assert!(k <= n, "k must be at most N");
+	let mut assi = vec![usize::MAX; n];
+	let mut loss: L = par_assign_nearest(mat, med, &mut assi);
+	let mut iter = 0;
+	while iter < maxiter {
+		iter += 1;
+		let mut members = vec![Vec::new(); k];
+		for (i, &a) in assi.iter().enumerate() {
+			members[a].push(i);
+		}
+		let changed = med
+			.par_iter_mut()
+			.zip(members.par_iter())
+			.map(|(m, members)| choose_medoid_of_members::<M, N, L>(mat, members, m))
+			.reduce(|| false, |a, b| a | b);
+		if !changed {
+			break;
+		}
+		loss = par_assign_nearest(mat, med, &mut assi);
+	}
+	(loss, assi, iter)
+}
+
+/// Assign each to the nearest medoid in parallel, return loss (summed sequentially, as the serial version)
+fn par_assign_nearest<M, N, L>(mat: &M, med: &[usize], assi: &mut [usize]) -> L
+where
+	N: PartialOrd + Copy + Sync + Send,
+	L: AddAssign + Zero + From<N>,
+	M: ArrayAdapter<N> + Sync + Send,
+{
+	let dist = assi
+		.par_iter_mut()
+		.enumerate()
+		.map(|(i, ai)| {
+			let best = nearest_medoid(mat, med, i);
+			*ai = best.0;
+			best.1
+		})
+		.collect::<Vec<N>>();
+	let mut loss = L::zero();
+	for d in dist {
+		loss += L::from(d);
+	}
+	loss
+}
+
+/// Choose the best medoid of a cluster, given its members in ascending order.
+///
+/// Equivalent to `choose_medoid_within_partition`, but only visiting the members.
+fn choose_medoid_of_members<M, N, L>(mat: &M, members: &[usize], med: &mut usize) -> bool
+where
+	N: PartialOrd + Copy,
+	L: AddAssign + Zero + PartialOrd + Copy + From<N>,
+	M: ArrayAdapter<N>,
+{
+	let first = *med;
+	let mut best = first;
+	let mut sumb = L::zero();
+	for &i in members.iter() {
+		if first != i {
+			sumb += L::from(mat.get(first, i));
+		}
+	}
+	for &j in members.iter() {
+		if j != first {
+			let mut sumj = L::zero();
+			for &i in members.iter() {
+				if i != j {
+					sumj += L::from(mat.get(j, i));
+				}
+			}
+			if sumj < sumb {
+				best = j;
+				sumb = sumj;
+			}
+		}
+	}
+	*med = best;
+	best != first
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{alternating, arrayadapter::LowerTriangle, par_alternating};
+	use rand::{rngs::StdRng, Rng, SeedableRng};
+
+	#[test]
+	fn test_par_alternating() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let mut meds = vec![0, 1];
+		let (loss, assi, n_iter): (i64, _, _) = alternating(&data, &mut meds, 10);
+		let mut meds2 = vec![0, 1];
+		let (loss2, assi2, n_iter2): (i64, _, _) = par_alternating(&data, &mut meds2, 10);
+		assert_eq!((loss, assi, n_iter, meds), (loss2, assi2, n_iter2, meds2), "result not as expected");
+
+		// Larger random example, with ties in the distances
+		let mut rng = StdRng::seed_from_u64(1);
+		let n = 200;
+		let data = LowerTriangle { n, data: (0..n * (n - 1) / 2).map(|_| rng.gen_range(1..20u32)).collect() };
+		for k in [1, 3, 10] {
+			let init = crate::random_initialization(n, k, &mut rng);
+			let mut meds = init.clone();
+			let (loss, assi, n_iter): (f64, _, _) = alternating(&data, &mut meds, 100);
+			let mut meds2 = init.clone();
+			let (loss2, assi2, n_iter2): (f64, _, _) = par_alternating(&data, &mut meds2, 100);
+			assert_eq!((loss, assi, n_iter, meds), (loss2, assi2, n_iter2, meds2), "result not as expected");
+		}
+	}
+}
@@ -0,0 +1,102 @@
+use crate::analysis::{check_medoids, medoid_edges, select_nearest};
+use crate::arrayadapter::ArrayAdapter;
+use rayon::prelude::*;
+
+/// Find the m nearest medoids of every point (parallel implementation).
+///
+/// This generalizes the nearest, second and third nearest medoid tracking of the
+/// algorithms, and uses the same tie-breaking: the earlier medoid wins, but a medoid
+/// is always nearest to itself. The result is identical to [`nearest_medoids`](crate::nearest_medoids).
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+/// * `m` - the number of nearest medoids to find (all medoids if m >= k)
+///
+/// returns for each point the m nearest medoids (position in the medoid list, i.e.,
+/// the cluster label) and their distances, sorted by distance
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let near = kmedoids::par_nearest_medoids(&data, &[0, 1, 3], 2);
+/// assert_eq!(near[2], vec![(0, 2), (1, 4)]);
+/// ```
+pub fn par_nearest_medoids<M, N>(mat: &M, meds: &[usize], m: usize) -> Vec<Vec<(usize, N)>>
+where
+	N: PartialOrd + Copy + Sync + Send,
+	M: ArrayAdapter<N> + Sync + Send,
+{
+	check_medoids(mat, meds);
+	(0..mat.len()).into_par_iter().map(|i| select_nearest(mat, meds, i, m)).collect()
+}
+
+/// Compute the graph of the clusters, with an edge for every pair of medoids
+/// with a distance of at most the threshold (parallel implementation).
+///
+/// The result is identical to [`medoid_graph`](crate::medoid_graph).
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+/// * `threshold` - the maximum distance of an edge, or `None` for all pairs
+///
+/// returns the edge list, with the two cluster labels and their medoid distance
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid index is not less than N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let edges = kmedoids::par_medoid_graph(&data, &[0, 2, 3], Some(3));
+/// assert_eq!(edges, vec![(0, 1, 2), (0, 2, 3)]);
+/// ```
+pub fn par_medoid_graph<M, N>(mat: &M, meds: &[usize], threshold: Option<N>) -> Vec<(usize, usize, N)>
+where
+	N: PartialOrd + Copy + Sync + Send,
+	M: ArrayAdapter<N> + Sync + Send,
+{
+	check_medoids(mat, meds);
+	(0..meds.len()).into_par_iter().flat_map_iter(|a| medoid_edges(mat, meds, threshold, a)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::LowerTriangle, medoid_graph, nearest_medoids, par_medoid_graph, par_nearest_medoids};
+
+	#[test]
+	fn test_par_nearest_medoids() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for m in 0..=4 {
+			let meds = vec![3, 0, 2];
+			assert_eq!(par_nearest_medoids(&data, &meds, m), nearest_medoids(&data, &meds, m), "not as expected");
+		}
+	}
+
+	#[test]
+	fn test_par_medoid_graph() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let meds = vec![3, 0, 2, 4];
+		for t in [None, Some(0), Some(4), Some(7)] {
+			assert_eq!(par_medoid_graph(&data, &meds, t), medoid_graph(&data, &meds, t), "not as expected");
+		}
+	}
+}
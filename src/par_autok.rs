@@ -0,0 +1,76 @@
+use crate::arrayadapter::ArrayAdapter;
+use crate::autok::{autok_runs, select_best_k, AutoKAlgorithm, AutoKResult};
+use crate::silhouette;
+use core::ops::{AddAssign, RangeInclusive};
+use num_traits::{Float, FromPrimitive, Signed};
+use rayon::prelude::*;
+use std::convert::From;
+
+/// Choose the number of clusters by the silhouette (parallel implementation).
+///
+/// The clusterings are computed as in [`autok_silhouette`](crate::autok_silhouette),
+/// sequentially because of the warm starts, but the silhouettes of the different k
+/// are computed in parallel. The result is the same as of the sequential version.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the loss (must be a float)
+/// * `mat` - a pairwise distance matrix
+/// * `ks` - the range of k to try
+/// * `maxiter` - the maximum number of iterations allowed for every k
+/// * `algorithm` - the clustering algorithm to use
+/// * `rng` - random number generator for the initial medoids
+///
+/// returns the chosen k, the silhouette of every k, and all clustering results
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when the range of k is empty, starts below 2, or ends above N
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let result: kmedoids::AutoKResult<f64> =
+///     kmedoids::par_autok_silhouette(&data, 2..=3, 100, kmedoids::AutoKAlgorithm::FasterMSC, &mut rand::thread_rng());
+/// println!("Best k is: {}", result.best_k);
+/// ```
+pub fn par_autok_silhouette<M, N, L>(
+	mat: &M,
+	ks: RangeInclusive<usize>,
+	maxiter: usize,
+	algorithm: AutoKAlgorithm,
+	rng: &mut impl rand::Rng,
+) -> AutoKResult<L>
+where
+	N: num_traits::Zero + PartialOrd + Copy + Sync + Send,
+	L: Float + Signed + AddAssign + From<N> + From<u32> + FromPrimitive + std::fmt::Display + Sync + Send,
+	M: ArrayAdapter<N> + Sync + Send,
+{
+	let results = autok_runs(mat, ks, maxiter, algorithm, rng);
+	let curve = results
+		.par_iter()
+		.map(|r| (r.medoids.len(), silhouette(mat, &r.assignment, false).0))
+		.collect::<Vec<(usize, L)>>();
+	AutoKResult { best_k: select_best_k(&curve), curve, results }
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::LowerTriangle, autok_silhouette, par_autok_silhouette, AutoKAlgorithm, AutoKResult};
+	use rand::{rngs::StdRng, SeedableRng};
+
+	#[test]
+	fn test_par_autok_silhouette() {
+		let points = [0, 1, 3, 4, 50, 51, 53, 100, 102, 103, 104];
+		let data = LowerTriangle {
+			n: points.len(),
+			data: (1..points.len()).flat_map(|i| (0..i).map(move |j| points[i] - points[j])).collect::<Vec<u32>>(),
+		};
+		let seq: AutoKResult<f64> = autok_silhouette(&data, 2..=5, 100, AutoKAlgorithm::FasterPAM, &mut StdRng::seed_from_u64(1));
+		let par: AutoKResult<f64> = par_autok_silhouette(&data, 2..=5, 100, AutoKAlgorithm::FasterPAM, &mut StdRng::seed_from_u64(1));
+		assert_eq!(par, seq, "parallel result not as expected");
+		assert_eq!(par.best_k, 3, "k not as expected");
+	}
+}
@@ -1,6 +1,7 @@
 #[cfg(feature = "parallel")]
 use crate::arrayadapter::ArrayAdapter;
 use crate::fasterpam::{update_removal_loss, update_second_nearest};
+use crate::profiling::{self, timed, Phase, Stopwatch};
 use crate::util::*;
 use core::ops::AddAssign;
 use ndarray::Array;
@@ -37,7 +38,7 @@ use std::sync::{Arc, Mutex};
 /// ```
 /// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
 /// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
-/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::par_fasterpam(&data, &mut meds, 100, &mut rand::thread_rng());
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::par_fasterpam(&data, &mut meds, 0, 100, &mut rand::thread_rng());
 /// println!("Loss is: {}", loss);
 /// ```
 pub fn par_fasterpam<M, N, L>(
@@ -52,13 +53,17 @@ where
 	L: AddAssign + Signed + Zero + PartialOrd + Copy + From<N> + Sync + Send + FromPrimitive + std::fmt::Display,
 	M: ArrayAdapter<N> + Sync + Send,
 {
+	profiling::reset();
 	let (n, k) = (mat.len(), med.len());
 	if k == 1 {
 		let assi = vec![0; n];
+		if maxiter == 0 {
+			return (single_medoid_loss(mat, med[0]), assi, 0, 0); // only evaluate the given medoid
+		}
 		let (swapped, loss) = choose_medoid_within_partition::<M, N, L>(mat, &assi, med, 0);
 		return (loss, assi, 1, if swapped { 1 } else { 0 });
 	}
-	let (mut loss, mut data) = par_initial_assignment(mat, med);
+	let (mut loss, mut data) = timed(Phase::InitialAssignment, || par_initial_assignment(mat, med));
 	debug_assert_assignment(mat, med, &data);
 
 	let mut removal_loss = vec![L::zero(); k];
@@ -67,6 +72,7 @@ where
 	let seq = rand::seq::index::sample(rng, n, n); // random shuffling
 	while iter < maxiter {
 		iter += 1;
+		let _iteration = Stopwatch::start(Phase::Iteration);
 		let (swaps_before, lastloss) = (n_swaps, loss);
 		for j in seq.iter() {
 			if j == lastswap {
@@ -75,14 +81,14 @@ where
 			if j == med[data[j].near.i as usize] {
 				continue; // This already is a medoid
 			}
-			let (change, b) = par_find_best_swap(mat, &removal_loss, &data, j);
+			let (change, b) = timed(Phase::FindBestSwap, || par_find_best_swap(mat, &removal_loss, &data, j));
 			if change >= L::zero() {
 				continue; // No improvement
 			}
 			n_swaps += 1;
 			lastswap = j;
 			// perform the swap
-			loss = par_do_swap(mat, med, &mut data, b, j);
+			loss = timed(Phase::DoSwap, || par_do_swap(mat, med, &mut data, b, j));
 			update_removal_loss(&data, &mut removal_loss, n_fixed_meds);
 		}
 		if n_swaps == swaps_before || loss >= lastloss {
@@ -113,13 +119,13 @@ where
 		.par_iter_mut()
 		.enumerate()
 		.map(|(i, cur)| {
-			*cur = Rec::new(0, mat.get(i, firstcenter), u32::MAX, N::zero());
+			*cur = Rec { near: DistancePair::new(0, mat.get(i, firstcenter)), seco: DistancePair::sentinel() };
 			for (m, &me) in med.iter().enumerate().skip(1) {
 				let d = mat.get(i, me);
 				if d < cur.near.d || i == me {
 					cur.seco = cur.near;
 					cur.near = DistancePair { i: m as u32, d };
-				} else if cur.seco.i == u32::MAX || d < cur.seco.d {
+				} else if cur.seco.is_sentinel() || d < cur.seco.d {
 					cur.seco = DistancePair { i: m as u32, d };
 				}
 			}
@@ -235,7 +241,7 @@ where
 #[cfg(test)]
 mod tests {
 	// TODO: use a larger, much more interesting example.
-	use crate::{arrayadapter::LowerTriangle, par_fasterpam, par_silhouette, util::assert_array};
+	use crate::{canonicalize, arrayadapter::LowerTriangle, par_fasterpam, par_silhouette, util::assert_array};
 	use rand::{rngs::StdRng, SeedableRng};
 
 	#[test]
@@ -246,8 +252,9 @@ mod tests {
 		};
 		let mut meds = vec![0, 1];
 		let mut rng = StdRng::seed_from_u64(1);
-		let (loss, assi, n_iter, n_swap): (i64, _, _, _) =
-			par_fasterpam(&data, &mut meds, 10, &mut rng);
+		let (loss, mut assi, n_iter, n_swap): (i64, _, _, _) =
+			par_fasterpam(&data, &mut meds, 0, 10, &mut rng);
+		canonicalize(&mut meds, &mut assi);
 		let sil: f64 = par_silhouette(&data, &assi);
 		assert_eq!(loss, 4, "loss not as expected");
 		assert_eq!(n_swap, 1, "swaps not as expected");
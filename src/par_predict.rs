@@ -0,0 +1,24 @@
+use crate::predict::Predictor;
+use rayon::prelude::*;
+
+impl<P: Sync, N: PartialOrd + Copy + Send + Sync> Predictor<P, N> {
+	/// Predict the clusters of a batch of points (parallel implementation)
+	///
+	/// returns for each point the cluster label (or [`NOISE_LABEL`](crate::NOISE_LABEL)) and
+	/// the distance to the nearest medoid, the same as [`predict_batch`](Predictor::predict_batch)
+	pub fn par_predict_batch(&self, xs: &[P]) -> Vec<(usize, N)> {
+		xs.par_iter().map(|x| self.predict(x)).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Predictor;
+
+	#[test]
+	fn test_par_predict_batch() {
+		let points = (0..100).map(|i| (i * 37 % 101) as f64).collect::<Vec<f64>>();
+		let predictor = Predictor::from_indices(&points, &[3, 10, 50], |a: &f64, b: &f64| (a - b).abs()).with_threshold(20.);
+		assert_eq!(predictor.par_predict_batch(&points), predictor.predict_batch(&points), "not as expected");
+	}
+}
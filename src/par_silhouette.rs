@@ -1,5 +1,5 @@
 use crate::arrayadapter::ArrayAdapter;
-use crate::silhouette::checked_div;
+use crate::silhouette::{check_evaluation_input, checked_div, combine_evaluation, evaluate_row, Evaluation};
 use core::ops::{AddAssign, Div, Sub};
 use num_traits::{Signed, Zero};
 use rayon::prelude::*;
@@ -93,3 +93,174 @@ where
 		.for_each(|x| lsum += *x);
 	lsum.div((assi.len() as u32).into())
 }
+
+/// Compute the Medoid Silhouette of a clustering (parallel implementation).
+///
+/// The Medoid Silhouette is an approximation to the original Silhouette where the
+/// distance to the cluster medoid is used instead of the average distance, hence reducing
+/// the run time from O(N²) to O(Nk). Here we assume that every object is assigned the
+/// nearest cluster, and hence only a distance matrix and a list of medoids is given.
+///
+/// The nearest and second nearest medoid distances are computed in parallel, the sum
+/// is computed sequentially, so the result is identical to [`medoid_silhouette`](crate::medoid_silhouette).
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the cost (use a float type)
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+/// * `samples` - whether to keep the individual samples, or not
+///
+/// returns a tuple containing:
+/// * the average medoid silhouette
+/// * the individual medoid silhouette values (empty if `samples = false`)
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid occurs more than once
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter): (f64, _, _) = kmedoids::alternating(&data, &mut meds, 100);
+/// let (sil, _): (f64, _) = kmedoids::par_medoid_silhouette(&data, &meds, false);
+/// println!("Silhouette is: {}", sil);
+/// ```
+#[cfg(feature = "parallel")]
+pub fn par_medoid_silhouette<M, N, L>(mat: &M, meds: &[usize], samples: bool) -> (L, Vec<L>)
+where
+	N: Zero + PartialOrd + Copy + Sync + Send,
+	L: AddAssign
+		+ Div<Output = L>
+		+ Sub<Output = L>
+		+ Signed
+		+ Zero
+		+ PartialOrd
+		+ Copy
+		+ From<N>
+		+ From<u32>,
+	M: ArrayAdapter<N> + Sync + Send,
+{
+	let (n, k) = (mat.len(), meds.len());
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(meds.iter().collect::<std::collections::HashSet<_>>().len() == k, "duplicate medoids");
+	let mut sil = vec![L::one(); if samples { n } else { 0 }];
+	if k == 1 { return (L::one(), sil); } // not really well-defined
+	assert!(k <= n, "invalid k, must be over 1 and at most N");
+	let mut pairs = vec![(N::zero(), N::zero()); n];
+	pairs.par_iter_mut().enumerate().for_each(|(i, best)| {
+		let (d1, d2) = (mat.get(i, meds[0]), mat.get(i, meds[1]));
+		*best = if d1 < d2 { (d1, d2) } else { (d2, d1) };
+		for &m in meds.iter().skip(2) {
+			let d = mat.get(i, m);
+			if d < best.0 {
+				*best = (d, best.0);
+			}
+			else if d < best.1 {
+				best.1 = d;
+			}
+		}
+	});
+	let mut loss = L::zero();
+	for (i, best) in pairs.iter().enumerate() {
+		if !N::is_zero(&best.0) {
+			let s = <L as From<N>>::from(best.0) / <L as From<N>>::from(best.1);
+			if samples { sil[i] = L::one() - s; }
+			loss += s;
+		}
+	}
+	loss = L::one() - loss / <L as From<u32>>::from(n as u32);
+	(loss, sil)
+}
+
+/// Evaluate a medoid clustering with multiple measures at once (parallel implementation).
+///
+/// The rows are evaluated in parallel, the sums are computed sequentially, so the
+/// result is identical to [`evaluate_all`](crate::evaluate_all).
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the cost (use a float type)
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+/// * `assi` - the cluster assignment, referring to the medoid list
+///
+/// returns the evaluation measures
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when the assignment does not refer to the medoid list
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter): (f64, _, _) = kmedoids::alternating(&data, &mut meds, 100);
+/// let eval: kmedoids::Evaluation<f64> = kmedoids::par_evaluate_all(&data, &meds, &assi);
+/// println!("Silhouette is: {}, Medoid Silhouette is: {}", eval.silhouette, eval.medoid_silhouette);
+/// ```
+#[cfg(feature = "parallel")]
+pub fn par_evaluate_all<M, N, L>(mat: &M, meds: &[usize], assi: &[usize]) -> Evaluation<L>
+where
+	N: Zero + PartialOrd + Copy + Sync + Send,
+	L: AddAssign
+		+ Div<Output = L>
+		+ Sub<Output = L>
+		+ Signed
+		+ Zero
+		+ PartialOrd
+		+ Copy
+		+ From<N>
+		+ From<u32>
+		+ Sync
+		+ Send,
+	M: ArrayAdapter<N> + Sync + Send,
+{
+	check_evaluation_input(mat, meds, assi);
+	let rows = (0..mat.len())
+		.into_par_iter()
+		.map_init(
+			|| (Vec::with_capacity(mat.len()), Vec::new()),
+			|(row, buf), i| evaluate_row(mat, meds, assi, i, row, buf),
+		)
+		.collect::<Vec<_>>();
+	combine_evaluation(meds.len(), assi, rows.into_iter())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::LowerTriangle, evaluate_all, medoid_silhouette, par_evaluate_all, par_medoid_silhouette, Evaluation};
+
+	#[test]
+	fn test_par_medoid_silhouette() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for meds in [vec![0], vec![0, 3], vec![3, 1], vec![0, 3, 2], vec![4, 2, 1, 0], vec![0, 1, 2, 3, 4]] {
+			let (msil, msils): (f64, _) = medoid_silhouette(&data, &meds, true);
+			let (pmsil, pmsils): (f64, _) = par_medoid_silhouette(&data, &meds, true);
+			assert_eq!(msil, pmsil, "Medoid Silhouette not as expected");
+			assert_eq!(msils, pmsils, "Medoid Silhouette samples not as expected");
+		}
+	}
+
+	#[test]
+	fn test_par_evaluate_all() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for (meds, assi) in [(vec![0], vec![0; 5]), (vec![0, 3], vec![0, 0, 0, 1, 1]), (vec![0, 3, 2], vec![0, 0, 2, 1, 1])] {
+			let eval: Evaluation<f64> = evaluate_all(&data, &meds, &assi);
+			let peval: Evaluation<f64> = par_evaluate_all(&data, &meds, &assi);
+			assert_eq!(eval, peval, "Evaluation not as expected");
+		}
+	}
+}
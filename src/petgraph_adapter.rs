@@ -0,0 +1,79 @@
+use crate::arrayadapter::LowerTriangle;
+use core::hash::BuildHasher;
+use num_traits::Float;
+use petgraph::matrix_graph::MatrixGraph;
+use petgraph::visit::IntoNodeIdentifiers;
+use petgraph::{graph::IndexType, EdgeType};
+
+/// Convert the edge weights of a graph into a lower triangular distance matrix.
+///
+/// The edge weights are used as the distances of the nodes, in the order of the node
+/// identifiers. For a directed graph, the smaller weight of both directions is used, as
+/// the distances must be symmetric. Missing edges have an infinite distance, and the
+/// diagonal (self loops) is ignored. These are not shortest path distances; for these,
+/// see [`crate::DijkstraMatrix`].
+///
+/// Requires the `petgraph` feature.
+///
+/// ## Example
+/// ```
+/// let mut graph = petgraph::matrix_graph::MatrixGraph::<(), f64>::new();
+/// let (a, b, c) = (graph.add_node(()), graph.add_node(()), graph.add_node(()));
+/// graph.add_edge(a, b, 1.);
+/// graph.add_edge(b, a, 2.);
+/// graph.add_edge(c, a, 3.);
+/// let data = kmedoids::arrayadapter::LowerTriangle::from(graph);
+/// assert_eq!(data.data, vec![1., 3., f64::INFINITY]);
+/// ```
+impl<V, W, S, Ty, Ix> From<MatrixGraph<V, W, S, Ty, Option<W>, Ix>> for LowerTriangle<W>
+where
+	W: Float,
+	S: BuildHasher,
+	Ty: EdgeType,
+	Ix: IndexType,
+{
+	fn from(graph: MatrixGraph<V, W, S, Ty, Option<W>, Ix>) -> Self {
+		let nodes = graph.node_identifiers().collect::<Vec<_>>();
+		let weight = |a, b| graph.get_edge_weight(a, b).copied().unwrap_or_else(W::infinity);
+		let mut data = Vec::with_capacity(nodes.len() * nodes.len().saturating_sub(1) / 2);
+		for (i, &a) in nodes.iter().enumerate() {
+			for &b in nodes[..i].iter() {
+				data.push(weight(a, b).min(weight(b, a)));
+			}
+		}
+		LowerTriangle { n: nodes.len(), data }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::LowerTriangle, pam};
+	use petgraph::matrix_graph::{DiMatrix, UnMatrix};
+
+	#[test]
+	fn test_matrix_graph() {
+		// two triangles of close nodes, with long edges between them
+		let mut graph = UnMatrix::<(), f64>::with_capacity(6);
+		let nodes = (0..6).map(|_| graph.add_node(())).collect::<Vec<_>>();
+		for i in 1..6 {
+			for j in 0..i {
+				graph.add_edge(nodes[i], nodes[j], if i / 3 == j / 3 { 1. + (i + j) as f64 * 0.1 } else { 10. });
+			}
+		}
+		let data = LowerTriangle::from(graph);
+		assert_eq!((data.n, data.data.len()), (6, 15), "size not as expected");
+		let (loss, assi, meds, _, _): (f64, _, _, _, _) = pam(&data, 2, 10);
+		assert_eq!(assi, vec![0, 0, 0, 1, 1, 1], "assignment not as expected");
+		assert_eq!(meds, vec![0, 3], "medoids not as expected");
+		assert!((loss - (1.1 + 1.2 + 1.7 + 1.8)).abs() < 1e-12, "loss not as expected");
+
+		// directed: the shorter direction, and infinity for missing edges
+		let mut graph = DiMatrix::<(), f32>::new();
+		let nodes = (0..3).map(|_| graph.add_node(())).collect::<Vec<_>>();
+		graph.add_edge(nodes[0], nodes[1], 4.);
+		graph.add_edge(nodes[1], nodes[0], 2.);
+		graph.add_edge(nodes[1], nodes[2], 5.);
+		graph.add_edge(nodes[2], nodes[2], 1.);
+		assert_eq!(LowerTriangle::from(graph).data, vec![2., f32::INFINITY, 5.], "distances not as expected");
+	}
+}
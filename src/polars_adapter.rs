@@ -0,0 +1,87 @@
+use crate::arrayadapter::ArrayAdapter;
+use polars::prelude::{polars_ensure, DataFrame, Float64Chunked, PolarsResult};
+
+/// Square distance matrix stored in a Polars DataFrame
+///
+/// Column `j` of the DataFrame contains the distances to object `j`, and row `i` the
+/// distances of object `i`, so `get(i, j)` reads the value at row `i` of column `j`.
+/// The columns are shared with the DataFrame (Polars columns are reference counted),
+/// so no intermediate distance matrix is materialized. For a `LazyFrame`, collect it first.
+///
+/// Requires the `polars` feature.
+///
+/// ## Example
+/// ```
+/// use polars::prelude::*;
+/// let df = df!("a" => [0., 1., 2.], "b" => [1., 0., 3.], "c" => [2., 3., 0.]).unwrap();
+/// let data = kmedoids::PolarsMatrix::new(&df).unwrap();
+/// let mut meds = vec![0, 1];
+/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 100);
+/// println!("Loss is {}", loss);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PolarsMatrix {
+	columns: Vec<Float64Chunked>,
+	height: usize,
+}
+impl PolarsMatrix {
+	/// Use the columns of a DataFrame as the columns of the distance matrix
+	///
+	/// returns the matrix, or an error if a column is not of type `f64` or contains nulls
+	pub fn new(df: &DataFrame) -> PolarsResult<Self> {
+		let columns = df
+			.get_columns()
+			.iter()
+			.map(|c| {
+				let c = c.f64()?;
+				polars_ensure!(c.null_count() == 0, ComputeError: "column {} contains null values", c.name());
+				Ok(c.rechunk()) // a single chunk for fast access
+			})
+			.collect::<PolarsResult<Vec<_>>>()?;
+		Ok(PolarsMatrix { columns, height: df.height() })
+	}
+}
+/// Adapter implementation for PolarsMatrix
+impl ArrayAdapter<f64> for PolarsMatrix {
+	#[inline]
+	fn len(&self) -> usize {
+		self.height
+	}
+	#[inline]
+	fn is_square(&self) -> bool {
+		self.columns.len() == self.height
+	}
+	#[inline]
+	fn get(&self, x: usize, y: usize) -> f64 {
+		self.columns[y].get(x).unwrap()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::PolarsMatrix;
+	use crate::{arrayadapter::{ArrayAdapter, LowerTriangle}, pam};
+	use polars::prelude::{Column, DataFrame};
+
+	#[test]
+	fn test_polars_matrix() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1., 2., 3., 4., 5., 6., 7., 8., 9., 1.],
+		};
+		let columns = (0..5)
+			.map(|j| Column::new(format!("p{}", j).into(), (0..5).map(|i| data.get(i, j)).collect::<Vec<f64>>()))
+			.collect::<Vec<_>>();
+		let mat = PolarsMatrix::new(&DataFrame::new(columns).unwrap()).unwrap();
+		assert_eq!(mat.len(), 5, "size not as expected");
+		assert!(mat.is_square(), "matrix not square");
+		let (loss, assi, meds, n_iter, n_swap): (f64, _, _, _, _) = pam(&mat, 2, 10);
+		let expect: (f64, _, _, _, _) = pam(&data, 2, 10);
+		assert_eq!((loss, assi, meds, n_iter, n_swap), expect, "result not as expected");
+		// only f64 columns without nulls are accepted
+		let ints = DataFrame::new(vec![Column::new("a".into(), [0i32])]).unwrap();
+		assert!(PolarsMatrix::new(&ints).is_err(), "integer column accepted");
+		let nulls = DataFrame::new(vec![Column::new("a".into(), [Some(0.), None])]).unwrap();
+		assert!(PolarsMatrix::new(&nulls).is_err(), "null value accepted");
+	}
+}
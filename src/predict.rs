@@ -0,0 +1,113 @@
+/// Cluster label returned by [`Predictor`] for points farther than the threshold.
+pub const NOISE_LABEL: usize = usize::MAX;
+
+/// Assign new points to the nearest medoid, using a distance function
+///
+/// This does not need a distance matrix, but only the medoids (e.g., their original
+/// feature vectors) and a distance function to compare new points with them. If a
+/// threshold is set, points with a larger distance to the nearest medoid are labeled
+/// as noise with [`NOISE_LABEL`]. If multiple medoids are equally near, the first wins.
+///
+/// ## Example
+/// ```
+/// let points = vec![0., 1., 2., 10., 11., 12.];
+/// let predictor = kmedoids::Predictor::from_indices(&points, &[1, 4], |a: &f64, b: &f64| (a - b).abs())
+///     .with_threshold(3.);
+/// assert_eq!(predictor.predict(&9.), (1, 2.));
+/// assert_eq!(predictor.predict_batch(&[0.5, 6.])[1].0, kmedoids::NOISE_LABEL);
+/// ```
+pub struct Predictor<P, N> {
+	medoids: Vec<P>,
+	metric: Metric<P, N>,
+	threshold: Option<N>,
+}
+/// Boxed distance function of a predictor
+type Metric<P, N> = Box<dyn Fn(&P, &P) -> N + Send + Sync>;
+impl<P, N: PartialOrd + Copy> Predictor<P, N> {
+	/// Wrap the medoid points and a distance function
+	///
+	/// ## Panics
+	///
+	/// * panics when no medoids are given
+	pub fn new(medoid_points: Vec<P>, metric: impl Fn(&P, &P) -> N + Send + Sync + 'static) -> Self {
+		assert!(!medoid_points.is_empty(), "at least one medoid is required");
+		Predictor { medoids: medoid_points, metric: Box::new(metric), threshold: None }
+	}
+	/// Copy the medoids, given as indexes, from the training points
+	///
+	/// ## Panics
+	///
+	/// * panics when no medoids are given, or a medoid index is not less than the number of points
+	pub fn from_indices(points: &[P], meds: &[usize], metric: impl Fn(&P, &P) -> N + Send + Sync + 'static) -> Self
+	where
+		P: Clone,
+	{
+		Self::new(meds.iter().map(|&m| points[m].clone()).collect(), metric)
+	}
+	/// Label points as noise if the distance to the nearest medoid exceeds the threshold
+	pub fn with_threshold(mut self, threshold: N) -> Self {
+		self.threshold = Some(threshold);
+		self
+	}
+	/// Get the medoid points
+	pub fn medoids(&self) -> &[P] {
+		&self.medoids
+	}
+	/// Predict the cluster of a point
+	///
+	/// returns the cluster label (or [`NOISE_LABEL`]) and the distance to the nearest medoid
+	pub fn predict(&self, x: &P) -> (usize, N) {
+		let mut best = (0, (self.metric)(x, &self.medoids[0]));
+		for (m, med) in self.medoids.iter().enumerate().skip(1) {
+			let d = (self.metric)(x, med);
+			if d < best.1 {
+				best = (m, d);
+			}
+		}
+		match self.threshold {
+			Some(t) if best.1 > t => (NOISE_LABEL, best.1),
+			_ => best,
+		}
+	}
+	/// Predict the clusters of a batch of points
+	///
+	/// returns for each point the cluster label (or [`NOISE_LABEL`]) and the distance to the nearest medoid
+	pub fn predict_batch(&self, xs: &[P]) -> Vec<(usize, N)> {
+		xs.iter().map(|x| self.predict(x)).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::LowerTriangle, fasterpam, Predictor, NOISE_LABEL};
+	use rand::{rngs::StdRng, Rng, SeedableRng};
+
+	fn euclidean(a: &(f64, f64), b: &(f64, f64)) -> f64 {
+		((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+	}
+
+	#[test]
+	fn test_predictor() {
+		let mut rng = StdRng::seed_from_u64(1);
+		let points = (0..50).map(|_| (rng.gen_range(0.0..10.0), rng.gen_range(0.0..10.0))).collect::<Vec<(f64, f64)>>();
+		let mut data = Vec::with_capacity(50 * 49 / 2);
+		for i in 1..50 {
+			for j in 0..i {
+				data.push(euclidean(&points[i], &points[j]));
+			}
+		}
+		let data = LowerTriangle { n: 50, data };
+		let mut meds = vec![0, 1, 2, 3];
+		let (_, assi, _, _): (f64, _, _, _) = fasterpam(&data, &mut meds, 0, 100);
+		let predictor = Predictor::from_indices(&points, &meds, euclidean);
+		let pred = predictor.predict_batch(&points);
+		assert_eq!(pred.iter().map(|p| p.0).collect::<Vec<_>>(), assi, "assignment not as expected");
+		for (i, p) in pred.iter().enumerate() {
+			assert_eq!(p.1, euclidean(&points[i], &points[meds[p.0]]), "distance not as expected");
+		}
+
+		let predictor = predictor.with_threshold(1.);
+		assert_eq!(predictor.predict(&(100., 100.)).0, NOISE_LABEL, "noise not as expected");
+		assert_eq!(predictor.predict(&points[meds[2]]), (2, 0.), "medoid not as expected");
+	}
+}
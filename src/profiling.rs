@@ -0,0 +1,163 @@
+//! Lightweight wall-time profiling of the swap-based algorithms.
+//!
+//! With the `profile` feature enabled, FasterPAM (including the randomized and
+//! parallel variants), FastPAM1, FastMSC, FasterMSC and DynMSC record the time spent in
+//! their main phases. The timings of the most recent call on the current thread
+//! can be obtained with [`last_run_profile`].
+//!
+//! PAM and PAMMEDSIL record only their SWAP phase, so the time of the initial assignment
+//! (and BUILD) is zero. The randomized and restarting PAM SWAP variants, the alternating
+//! algorithm, PAMSIL, and the other helpers of this crate do not record any timings, and
+//! leave the timings of the previous run.
+//!
+//! Without the feature, the timers compile to no-ops.
+//!
+//! ## Example
+//! ```
+//! # #[cfg(feature = "profile")] {
+//! let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+//! let mut meds = vec![0, 1];
+//! let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 100);
+//! let profile = kmedoids::profiling::last_run_profile();
+//! println!("Time spent finding swaps: {} ns", profile.swap_time_ns);
+//! # }
+//! ```
+#[cfg(feature = "profile")]
+use std::cell::Cell;
+#[cfg(feature = "profile")]
+use std::time::Instant;
+
+/// Wall time spent in the phases of the last algorithm run, in nanoseconds.
+#[cfg(feature = "profile")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunProfile {
+	/// time spent in the initial assignment to the medoids
+	pub initial_assignment_ns: u64,
+	/// time spent searching for the best swap (`find_best_swap`)
+	pub swap_time_ns: u64,
+	/// time spent performing swaps (`do_swap`)
+	pub do_swap_ns: u64,
+	/// remaining time of the iterations, e.g., updating the removal loss
+	pub iteration_overhead_ns: u64,
+}
+
+/// Phases of an algorithm run that can be timed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Phase {
+	InitialAssignment,
+	FindBestSwap,
+	DoSwap,
+	Iteration,
+}
+
+#[cfg(feature = "profile")]
+thread_local! {
+	/// Counters of the current run; the iteration counter includes the other phases.
+	static COUNTERS: Cell<(RunProfile, u64)> = Cell::new((RunProfile::default(), 0));
+}
+
+/// Get the timings of the last algorithm run on the current thread.
+///
+/// For the parallel variants, the time is measured on the calling thread,
+/// i.e., it is the wall time, not the accumulated CPU time of all workers.
+///
+/// returns the timings of the most recent run, or all zeros if no
+/// instrumented algorithm has been run on this thread yet.
+#[cfg(feature = "profile")]
+pub fn last_run_profile() -> RunProfile {
+	let (mut profile, iteration_ns) = COUNTERS.with(|c| c.get());
+	profile.iteration_overhead_ns = iteration_ns.saturating_sub(profile.swap_time_ns + profile.do_swap_ns);
+	profile
+}
+
+/// Reset the counters at the beginning of a run.
+#[inline]
+pub(crate) fn reset() {
+	#[cfg(feature = "profile")]
+	COUNTERS.with(|c| c.set((RunProfile::default(), 0)));
+}
+
+/// Stopwatch for timing a phase, which is recorded when it is dropped.
+///
+/// This is zero-sized without the `profile` feature.
+pub(crate) struct Stopwatch {
+	#[cfg(feature = "profile")]
+	start: Instant,
+	#[cfg(feature = "profile")]
+	phase: Phase,
+}
+
+impl Stopwatch {
+	/// Start timing the given phase.
+	#[inline]
+	#[allow(unused_variables)]
+	pub(crate) fn start(phase: Phase) -> Self {
+		Stopwatch {
+			#[cfg(feature = "profile")]
+			start: Instant::now(),
+			#[cfg(feature = "profile")]
+			phase,
+		}
+	}
+}
+
+#[cfg(feature = "profile")]
+impl Drop for Stopwatch {
+	fn drop(&mut self) {
+		let ns = self.start.elapsed().as_nanos() as u64;
+		COUNTERS.with(|c| {
+			let (mut p, mut it) = c.get();
+			match self.phase {
+				Phase::InitialAssignment => p.initial_assignment_ns += ns,
+				Phase::FindBestSwap => p.swap_time_ns += ns,
+				Phase::DoSwap => p.do_swap_ns += ns,
+				Phase::Iteration => it += ns,
+			}
+			c.set((p, it));
+		});
+	}
+}
+
+/// Time a single function call.
+#[inline]
+pub(crate) fn timed<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+	let _sw = Stopwatch::start(phase);
+	f()
+}
+
+#[cfg(all(test, feature = "profile"))]
+mod tests {
+	use crate::{arrayadapter::LowerTriangle, fasterpam, fastermsc, pam_swap, pammedsil_swap, profiling::last_run_profile};
+	use rand::{rngs::StdRng, Rng, SeedableRng};
+
+	#[test]
+	fn test_profile() {
+		let n = 500;
+		let mut rng = StdRng::seed_from_u64(1);
+		let data = LowerTriangle {
+			n,
+			data: (0..n * (n - 1) / 2).map(|_| rng.gen_range(1..1000)).collect::<Vec<u32>>(),
+		};
+		let mut meds = crate::random_initialization(n, 10, &mut rng);
+		let (_, _, _, n_swap): (i64, _, _, _) = fasterpam(&data, &mut meds, 0, 100);
+		let profile = last_run_profile();
+		assert!(n_swap > 0, "swaps not as expected");
+		assert!(profile.initial_assignment_ns > 0, "initial assignment time not as expected");
+		assert!(profile.swap_time_ns > 0, "swap time not as expected");
+		assert!(profile.do_swap_ns > 0, "do swap time not as expected");
+
+		let mut meds = crate::random_initialization(n, 10, &mut rng);
+		let _: (f64, _, _, _) = fastermsc(&data, &mut meds, 0, 100);
+		assert!(last_run_profile().swap_time_ns > 0, "swap time not as expected");
+
+		// only the SWAP phase of PAM and PAMMEDSIL
+		let n = 100;
+		let data = LowerTriangle { n, data: data.data[..n * (n - 1) / 2].to_vec() };
+		let (_, _, _, n_swap): (i64, _, _, _) = pam_swap(&data, &mut vec![0, 1, 2], 0, 100);
+		let profile = last_run_profile();
+		assert_eq!(profile.initial_assignment_ns, 0, "initial assignment time not as expected");
+		assert!(n_swap > 0 && profile.swap_time_ns > 0 && profile.do_swap_ns > 0, "PAM swap time not as expected");
+		let _: (f64, _, _, _) = pammedsil_swap(&data, &mut vec![0, 1, 2], 100);
+		assert!(last_run_profile().swap_time_ns > 0, "PAMMEDSIL swap time not as expected");
+	}
+}
@@ -0,0 +1,527 @@
+use crate::index::{ClusterIndex, PointIndex};
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::AddAssign;
+use num_traits::{Float, NumCast};
+use std::collections::hash_map::DefaultHasher;
+
+/// Result of a k-medoids clustering run.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KMedoidsResult<L> {
+	/// the final loss
+	pub loss: L,
+	/// the final cluster assignment
+	pub assignment: Vec<usize>,
+	/// the final medoids
+	pub medoids: Vec<usize>,
+	/// the number of iterations needed
+	pub n_iter: usize,
+	/// the number of swaps performed
+	pub n_swap: usize,
+}
+
+impl<L> KMedoidsResult<L> {
+	/// Collect the result of a run.
+	///
+	/// * `loss` - the final loss
+	/// * `assignment` - the final cluster assignment
+	/// * `medoids` - the final medoids
+	/// * `n_iter` - the number of iterations needed
+	/// * `n_swap` - the number of swaps performed
+	///
+	/// ## Example
+	/// ```
+	/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+	/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+	/// let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 100);
+	/// let result = kmedoids::KMedoidsResult::new(loss, assi, meds, n_iter, n_swap);
+	/// ```
+	pub fn new(loss: L, assignment: Vec<usize>, medoids: Vec<usize>, n_iter: usize, n_swap: usize) -> Self {
+		KMedoidsResult { loss, assignment, medoids, n_iter, n_swap }
+	}
+
+	/// Get the medoid of a cluster.
+	///
+	/// ## Panics
+	///
+	/// * panics when the cluster does not exist
+	///
+	/// ## Example
+	/// ```
+	/// let result = kmedoids::KMedoidsResult::new(0, vec![0, 0, 1], vec![0, 2], 1, 0);
+	/// assert_eq!(result.medoid(kmedoids::ClusterIndex(1)), kmedoids::PointIndex(2));
+	/// ```
+	pub fn medoid(&self, c: ClusterIndex) -> PointIndex {
+		PointIndex(self.medoids[*c])
+	}
+
+	/// Get the cluster a point is assigned to.
+	///
+	/// ## Panics
+	///
+	/// * panics when the point does not exist
+	pub fn cluster_of(&self, p: PointIndex) -> ClusterIndex {
+		ClusterIndex(self.assignment[*p])
+	}
+
+	/// Get the medoids as typed point indexes.
+	pub fn typed_medoids(&self) -> Vec<PointIndex> {
+		self.medoids.iter().map(|&m| PointIndex(m)).collect()
+	}
+
+	/// Get the cluster assignment as typed cluster indexes.
+	pub fn typed_assignment(&self) -> Vec<ClusterIndex> {
+		self.assignment.iter().map(|&c| ClusterIndex(c)).collect()
+	}
+
+	/// Get a fingerprint of the medoids and the assignment, see [`hash_result`].
+	pub fn fingerprint(&self) -> ClusteringFingerprint {
+		ClusteringFingerprint(hash_result(&self.medoids, &self.assignment))
+	}
+}
+
+/// Content-based fingerprint of a clustering, see [`hash_result`].
+///
+/// Displayed as 16 hexadecimal digits, e.g., for use in cache file names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+#[repr(transparent)]
+pub struct ClusteringFingerprint(pub u64);
+
+impl From<u64> for ClusteringFingerprint {
+	#[inline]
+	fn from(h: u64) -> Self {
+		ClusteringFingerprint(h)
+	}
+}
+
+impl fmt::Display for ClusteringFingerprint {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{:016x}", self.0)
+	}
+}
+
+/// Hash the medoids and the cluster assignment of a clustering.
+///
+/// Use this to cache expensive post-processing of a result, e.g., the silhouette, and to
+/// recompute it only when the clustering changed. The loss and the iteration counts are not
+/// included. The hash is deterministic, but uses the standard library hasher with fixed keys,
+/// which may change between Rust releases, so do not rely on it for long-term storage.
+///
+/// * `meds` - the medoids
+/// * `assi` - the cluster assignment
+///
+/// returns the hash value
+///
+/// ## Example
+/// ```
+/// let h = kmedoids::hash_result(&[0, 2], &[0, 0, 1]);
+/// assert_eq!(h, kmedoids::hash_result(&[0, 2], &[0, 0, 1]));
+/// println!("Fingerprint: {}", kmedoids::ClusteringFingerprint(h));
+/// ```
+pub fn hash_result(meds: &[usize], assi: &[usize]) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	// slices hash their length first, so the boundary between the two is unambiguous
+	meds.hash(&mut hasher);
+	assi.hash(&mut hasher);
+	hasher.finish()
+}
+
+/// Whether the loss of the runs is to be minimized or maximized, to choose the best run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Objective {
+	/// smaller is better, as for the total deviation of PAM and FasterPAM
+	#[default]
+	Minimize,
+	/// larger is better, as for the (medoid) silhouette of the MSC and PAMMEDSIL algorithms
+	Maximize,
+}
+
+impl Objective {
+	/// Whether loss `a` is strictly better than loss `b`.
+	///
+	/// ## Example
+	/// ```
+	/// assert!(kmedoids::Objective::Minimize.is_better(1., 2.));
+	/// assert!(kmedoids::Objective::Maximize.is_better(2., 1.));
+	/// ```
+	pub fn is_better<L: PartialOrd>(self, a: L, b: L) -> bool {
+		match self {
+			Objective::Minimize => a < b,
+			Objective::Maximize => a > b,
+		}
+	}
+}
+
+/// Summary statistics of multiple runs, see [`summarize_runs`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunSummary<L> {
+	/// the average loss
+	pub mean_loss: L,
+	/// the (population) standard deviation of the loss
+	pub std_loss: L,
+	/// the smallest loss
+	pub min_loss: L,
+	/// the largest loss
+	pub max_loss: L,
+	/// the average number of iterations
+	pub mean_n_iter: L,
+	/// the average number of swaps
+	pub mean_n_swap: L,
+	/// the index of the run with the best loss by the objective (the first, on ties)
+	pub best_run_index: usize,
+}
+
+/// Summarize the results of multiple runs, e.g., with different random initializations.
+///
+/// * type `L` - number data type such as `f64` for the loss (use a float type)
+/// * `results` - the results of the runs
+/// * `objective` - whether the loss is minimized, or maximized as the medoid silhouette
+///   of the MSC and PAMMEDSIL algorithms
+///
+/// returns the summary statistics
+///
+/// ## Panics
+///
+/// * panics when no results are given
+///
+/// ## Example
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut rng = rand::thread_rng();
+/// let results = (0..5).map(|_| {
+///     let mut meds = kmedoids::random_initialization(4, 2, &mut rng);
+///     let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 100);
+///     kmedoids::KMedoidsResult::new(loss, assi, meds, n_iter, n_swap)
+/// }).collect::<Vec<_>>();
+/// let summary = kmedoids::summarize_runs(&results, kmedoids::Objective::Minimize);
+/// println!("Loss: {} +- {}", summary.mean_loss, summary.std_loss);
+/// ```
+pub fn summarize_runs<L: Float + AddAssign>(results: &[KMedoidsResult<L>], objective: Objective) -> RunSummary<L> {
+	assert!(!results.is_empty(), "at least one result is required");
+	let n = <L as NumCast>::from(results.len()).unwrap();
+	let (mut sum, mut n_iter, mut n_swap) = (L::zero(), 0, 0);
+	let (mut min_loss, mut max_loss, mut best_run_index) = (results[0].loss, results[0].loss, 0);
+	for (i, r) in results.iter().enumerate() {
+		sum += r.loss;
+		n_iter += r.n_iter;
+		n_swap += r.n_swap;
+		if r.loss < min_loss {
+			min_loss = r.loss;
+		}
+		if r.loss > max_loss {
+			max_loss = r.loss;
+		}
+		if objective.is_better(r.loss, results[best_run_index].loss) {
+			best_run_index = i;
+		}
+	}
+	let mean_loss = sum / n;
+	let mut var = L::zero();
+	for r in results.iter() {
+		var += (r.loss - mean_loss) * (r.loss - mean_loss);
+	}
+	RunSummary {
+		mean_loss,
+		std_loss: (var / n).sqrt(),
+		min_loss,
+		max_loss,
+		mean_n_iter: <L as NumCast>::from(n_iter).unwrap() / n,
+		mean_n_swap: <L as NumCast>::from(n_swap).unwrap() / n,
+		best_run_index,
+	}
+}
+
+/// Stability of multiple runs, see [`restart_stability`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StabilityReport {
+	/// for each point, the fraction of runs with this point as medoid
+	pub medoid_frequency: Vec<f64>,
+	/// the adjusted Rand index of each pair of runs (symmetric, with ones on the diagonal)
+	pub pairwise_ari: Vec<Vec<f64>>,
+	/// the average adjusted Rand index of all pairs of different runs
+	pub mean_ari: f64,
+	/// the consensus assignment, using the cluster labels of the best run
+	pub consensus: Vec<usize>,
+	/// the index of the run with the best loss by the objective (the first, on ties)
+	pub best_run_index: usize,
+}
+
+/// Analyze the stability of multiple runs, e.g., with different random initializations.
+///
+/// Computes how often each point is chosen as medoid, the pairwise adjusted Rand index
+/// (ARI) of the assignments, and a consensus assignment. For the consensus, the cluster
+/// labels of each run are matched to the labels of the best run (by the objective)
+/// with the Hungarian algorithm, maximizing the number of points with the same label.
+/// Then each point is assigned to the label it has in most runs; on ties, the smaller
+/// label is chosen. If all pairs have an ARI of 1, the runs found the same clustering.
+///
+/// * type `L` - number data type such as `f64` for the loss
+/// * `results` - the results of the runs
+/// * `objective` - whether the loss is minimized, or maximized as the medoid silhouette
+///   of the MSC and PAMMEDSIL algorithms
+///
+/// returns the stability report
+///
+/// ## Panics
+///
+/// * panics when no results are given
+/// * panics when the assignments do not have the same length
+/// * panics when a cluster label is not less than the number of medoids
+///
+/// ## Example
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut rng = rand::thread_rng();
+/// let results = (0..5).map(|_| {
+///     let mut meds = kmedoids::random_initialization(4, 2, &mut rng);
+///     let (loss, assi, n_iter, n_swap): (f64, _, _, _) = kmedoids::fasterpam(&data, &mut meds, 0, 100);
+///     kmedoids::KMedoidsResult::new(loss, assi, meds, n_iter, n_swap)
+/// }).collect::<Vec<_>>();
+/// let report = kmedoids::restart_stability(&results, kmedoids::Objective::Minimize);
+/// println!("Average ARI: {}", report.mean_ari);
+/// ```
+pub fn restart_stability<L: PartialOrd + Copy>(results: &[KMedoidsResult<L>], objective: Objective) -> StabilityReport {
+	assert!(!results.is_empty(), "at least one result is required");
+	let (n, r) = (results[0].assignment.len(), results.len());
+	for res in results.iter() {
+		assert_eq!(res.assignment.len(), n, "assignments must have the same length");
+		assert!(res.assignment.iter().all(|&a| a < res.medoids.len()), "invalid cluster label");
+	}
+	let mut count = vec![0usize; n];
+	for res in results.iter() {
+		for &m in res.medoids.iter() {
+			count[m] += 1;
+		}
+	}
+	let medoid_frequency = count.iter().map(|&c| c as f64 / r as f64).collect();
+	let mut pairwise_ari = vec![vec![1.; r]; r];
+	let mut sum = 0.;
+	for i in 0..r {
+		for j in 0..i {
+			let ari = adjusted_rand_index(&results[i], &results[j]);
+			pairwise_ari[i][j] = ari;
+			pairwise_ari[j][i] = ari;
+			sum += ari;
+		}
+	}
+	let mean_ari = if r > 1 { sum / (r * (r - 1) / 2) as f64 } else { 1. };
+	let mut best_run_index = 0;
+	for (i, res) in results.iter().enumerate() {
+		if objective.is_better(res.loss, results[best_run_index].loss) {
+			best_run_index = i;
+		}
+	}
+	// Majority vote, after matching the labels to the best run
+	let best = &results[best_run_index];
+	let k = results.iter().map(|res| res.medoids.len()).max().unwrap();
+	let mut votes = vec![vec![0usize; k]; n];
+	for res in results.iter() {
+		let map = match_labels(&res.assignment, &best.assignment, k);
+		for (v, &a) in votes.iter_mut().zip(res.assignment.iter()) {
+			v[map[a]] += 1;
+		}
+	}
+	let consensus = votes
+		.iter()
+		.map(|v| v.iter().enumerate().fold((0, 0), |b, (c, &x)| if x > b.1 { (c, x) } else { b }).0)
+		.collect();
+	StabilityReport { medoid_frequency, pairwise_ari, mean_ari, consensus, best_run_index }
+}
+
+/// Adjusted Rand index of the assignments of two runs
+fn adjusted_rand_index<L>(a: &KMedoidsResult<L>, b: &KMedoidsResult<L>) -> f64 {
+	let (ka, kb) = (a.medoids.len(), b.medoids.len());
+	let mut table = vec![0usize; ka * kb];
+	for (&x, &y) in a.assignment.iter().zip(b.assignment.iter()) {
+		table[x * kb + y] += 1;
+	}
+	let pairs = |c: usize| (c * c.saturating_sub(1) / 2) as f64;
+	let index = table.iter().map(|&c| pairs(c)).sum::<f64>();
+	let sa = table.chunks(kb).map(|row| pairs(row.iter().sum())).sum::<f64>();
+	let sb = (0..kb).map(|y| pairs((0..ka).map(|x| table[x * kb + y]).sum())).sum::<f64>();
+	let expected = sa * sb / pairs(a.assignment.len());
+	let max = (sa + sb) / 2.;
+	if max == expected {
+		return 1.; // both are trivial partitions
+	}
+	(index - expected) / (max - expected)
+}
+
+/// Match the labels of one assignment to the labels of another, maximizing the overlap.
+///
+/// Uses the Hungarian algorithm on the k x k contingency table, in O(k³).
+fn match_labels(assi: &[usize], target: &[usize], k: usize) -> Vec<usize> {
+	let mut overlap = vec![vec![0i64; k]; k];
+	for (&a, &t) in assi.iter().zip(target.iter()) {
+		overlap[a][t] += 1;
+	}
+	// potentials u, v and matching p (of columns to rows), 1-based with a dummy 0
+	let (mut u, mut v, mut p, mut way) = (vec![0i64; k + 1], vec![0i64; k + 1], vec![0usize; k + 1], vec![0usize; k + 1]);
+	for i in 1..=k {
+		p[0] = i;
+		let mut j0 = 0;
+		let (mut minv, mut used) = (vec![i64::MAX; k + 1], vec![false; k + 1]);
+		loop {
+			used[j0] = true;
+			let (i0, mut delta, mut j1) = (p[j0], i64::MAX, 0);
+			for j in 1..=k {
+				if !used[j] {
+					let cur = -overlap[i0 - 1][j - 1] - u[i0] - v[j];
+					if cur < minv[j] {
+						minv[j] = cur;
+						way[j] = j0;
+					}
+					if minv[j] < delta {
+						delta = minv[j];
+						j1 = j;
+					}
+				}
+			}
+			for j in 0..=k {
+				if used[j] {
+					u[p[j]] += delta;
+					v[j] -= delta;
+				} else {
+					minv[j] -= delta;
+				}
+			}
+			j0 = j1;
+			if p[j0] == 0 {
+				break;
+			}
+		}
+		loop {
+			let j1 = way[j0];
+			p[j0] = p[j1];
+			j0 = j1;
+			if j0 == 0 {
+				break;
+			}
+		}
+	}
+	let mut map = vec![0; k];
+	for j in 1..=k {
+		map[p[j] - 1] = j - 1;
+	}
+	map
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{arrayadapter::LowerTriangle, fasterpam, hash_result, random_initialization, restart_stability, summarize_runs, ClusterIndex, ClusteringFingerprint, KMedoidsResult, Objective, PointIndex};
+
+	#[test]
+	fn test_summarize_runs() {
+		let results = [(4f64, 2, 1), (5., 3, 2), (3., 2, 2), (6., 4, 3), (3., 4, 2)]
+			.iter()
+			.map(|&(loss, n_iter, n_swap)| KMedoidsResult::new(loss, vec![0, 0, 1], vec![0, 2], n_iter, n_swap))
+			.collect::<Vec<_>>();
+		let summary = summarize_runs(&results, Objective::Minimize);
+		assert_eq!(summary.mean_loss, 4.2, "mean loss not as expected");
+		assert!((summary.std_loss - 1.1661903789690602).abs() < 1e-15, "std loss not as expected");
+		assert_eq!(summary.min_loss, 3., "min loss not as expected");
+		assert_eq!(summary.max_loss, 6., "max loss not as expected");
+		assert_eq!(summary.mean_n_iter, 3., "mean iterations not as expected");
+		assert_eq!(summary.mean_n_swap, 2., "mean swaps not as expected");
+		assert_eq!(summary.best_run_index, 2, "best run not as expected");
+		// the medoid silhouette is maximized
+		let maximized = summarize_runs(&results, Objective::Maximize);
+		assert_eq!(maximized.best_run_index, 3, "best run not as expected");
+		assert_eq!((maximized.min_loss, maximized.max_loss, maximized.mean_loss), (3., 6., 4.2), "statistics not as expected");
+	}
+
+	#[test]
+	fn test_typed_indexes() {
+		let result = KMedoidsResult::new(0, vec![0, 0, 1, 1], vec![1, 3], 1, 0);
+		assert_eq!(result.medoid(ClusterIndex(1)), PointIndex(3), "medoid not as expected");
+		assert_eq!(result.cluster_of(PointIndex(2)), ClusterIndex(1), "cluster not as expected");
+		assert_eq!(result.cluster_of(result.medoid(ClusterIndex(0))), ClusterIndex(0), "medoid not in its cluster");
+		assert_eq!(result.typed_medoids(), vec![PointIndex(1), PointIndex(3)], "medoids not as expected");
+		assert_eq!(result.typed_assignment().iter().map(|&c| *c).collect::<Vec<_>>(), result.assignment, "assignment not as expected");
+	}
+
+	#[test]
+	fn test_hash_result() {
+		let result = KMedoidsResult::new(1., vec![0, 0, 1, 1], vec![1, 3], 2, 1);
+		let again = KMedoidsResult::new(2., vec![0, 0, 1, 1], vec![1, 3], 5, 3);
+		assert_eq!(result.fingerprint(), again.fingerprint(), "fingerprint not deterministic");
+		assert_eq!(result.fingerprint(), ClusteringFingerprint(hash_result(&[1, 3], &[0, 0, 1, 1])), "fingerprint not as expected");
+		assert_eq!(result.fingerprint().to_string().len(), 16, "display not as expected");
+		let others = [
+			hash_result(&[1, 2], &[0, 0, 1, 1]),
+			hash_result(&[1, 3], &[0, 1, 1, 1]),
+			hash_result(&[1, 3], &[0, 0, 1, 1, 0]),
+			hash_result(&[1], &[3, 0, 0, 1, 1]),
+			hash_result(&[], &[]),
+		];
+		for (i, &h) in others.iter().enumerate() {
+			assert_ne!(h, result.fingerprint().0, "fingerprint collision");
+			assert!(others[..i].iter().all(|&o| o != h), "fingerprint collision");
+		}
+	}
+
+	#[test]
+	fn test_match_labels() {
+		assert_eq!(super::match_labels(&[2, 2, 0, 0, 1, 1], &[0, 0, 1, 1, 2, 2], 3), vec![1, 2, 0], "labels not as expected");
+		assert_eq!(super::match_labels(&[0, 0, 1, 1, 1, 2], &[1, 1, 0, 0, 2, 2], 3), vec![1, 0, 2], "labels not as expected");
+		let a = KMedoidsResult::new(1., vec![2, 2, 0, 0, 1, 1], vec![2, 4, 0], 1, 0);
+		let b = KMedoidsResult::new(2., vec![0, 0, 1, 1, 2, 2], vec![0, 2, 4], 1, 0);
+		let report = restart_stability(&[a, b], Objective::Minimize);
+		assert_eq!(report.pairwise_ari, vec![vec![1., 1.], vec![1., 1.]], "ARI not as expected");
+		assert_eq!(report.consensus, vec![2, 2, 0, 0, 1, 1], "consensus not as expected");
+		assert_eq!(report.medoid_frequency, vec![1., 0., 1., 0., 1., 0.], "frequencies not as expected");
+		// the consensus uses the labels of the best run
+		let a = KMedoidsResult::new(0.5, vec![0, 0, 0, 1, 1, 1], vec![0, 3], 1, 0);
+		let b = KMedoidsResult::new(0.8, vec![1, 1, 1, 0, 0, 0], vec![3, 0], 1, 0);
+		let report = restart_stability(&[a.clone(), b.clone()], Objective::Minimize);
+		assert_eq!((report.best_run_index, report.consensus), (0, a.assignment.clone()), "consensus not as expected");
+		let report = restart_stability(&[a, b.clone()], Objective::Maximize);
+		assert_eq!((report.best_run_index, report.consensus), (1, b.assignment), "consensus not as expected");
+	}
+
+	#[test]
+	fn test_restart_stability() {
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let mut rng = StdRng::seed_from_u64(1);
+		let run = |data: &LowerTriangle<u32>, k: usize, rng: &mut StdRng| {
+			(0..20)
+				.map(|_| {
+					let mut meds = random_initialization(data.n, k, rng);
+					let (loss, assi, n_iter, n_swap): (f64, _, _, _) = fasterpam(data, &mut meds, 0, 100);
+					KMedoidsResult::new(loss, assi, meds, n_iter, n_swap)
+				})
+				.collect::<Vec<_>>()
+		};
+		let to_matrix = |points: &[(i32, i32)]| {
+			let n = points.len();
+			let mut data = Vec::with_capacity(n * (n - 1) / 2);
+			for i in 1..n {
+				for j in 0..i {
+					data.push(((points[i].0 - points[j].0).abs() + (points[i].1 - points[j].1).abs()) as u32);
+				}
+			}
+			LowerTriangle { n, data }
+		};
+		// Well separated: three stars, with a center and four satellites each
+		let offsets = [(0, 0), (1, 0), (-1, 0), (0, 1), (0, -1)];
+		let points = (0..15).map(|i| (i as i32 / 5 * 100 + offsets[i % 5].0, offsets[i % 5].1)).collect::<Vec<_>>();
+		let results = run(&to_matrix(&points), 3, &mut rng);
+		let report = restart_stability(&results, Objective::Minimize);
+		assert_eq!(report.medoid_frequency.iter().filter(|&&f| f > 0.).count(), 3, "frequencies not concentrated");
+		assert!((report.mean_ari - 1.).abs() < 1e-12, "ARI not as expected");
+		assert_eq!(report.consensus, results[report.best_run_index].assignment, "consensus not as expected");
+
+		// Noise: uniform random points
+		let points = (0..60).map(|_| (rng.gen_range(0..100), rng.gen_range(0..100))).collect::<Vec<_>>();
+		let results = run(&to_matrix(&points), 8, &mut rng);
+		let report = restart_stability(&results, Objective::Minimize);
+		assert!(report.medoid_frequency.iter().filter(|&&f| f > 0.).count() > 8, "frequencies not dispersed");
+		assert!(report.mean_ari < 0.99, "ARI not as expected");
+		assert_eq!(report.consensus.len(), 60, "consensus not as expected");
+	}
+}
@@ -1,4 +1,4 @@
-use crate::arrayadapter::ArrayAdapter;
+use crate::arrayadapter::{ArrayAdapter, ClosureMatrix};
 use core::ops::{AddAssign, Div, Sub};
 use num_traits::{Signed, Zero};
 use std::convert::From;
@@ -39,6 +39,64 @@ use std::convert::From;
 /// println!("Silhouette is: {}", sil);
 /// ```
 pub fn silhouette<M, N, L>(mat: &M, assi: &[usize], samples: bool) -> (L, Vec<L>)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign
+		+ Div<Output = L>
+		+ Sub<Output = L>
+		+ Signed
+		+ Zero
+		+ PartialOrd
+		+ Copy
+		+ From<N>
+		+ From<u32>,
+	M: ArrayAdapter<N>,
+{
+	silhouette_with_mode(mat, assi, samples, SilhouetteMode::Mean)
+}
+
+/// How to average the individual silhouette values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SilhouetteMode {
+	/// the mean of all objects (the default), large clusters dominate the result
+	#[default]
+	Mean,
+	/// weight every object by 1/|cluster|, i.e., the mean of the cluster means,
+	/// so that small clusters contribute as much as large clusters
+	ClusterWeighted,
+}
+
+/// Compute the Silhouette of a strict partitional clustering, with the given averaging.
+///
+/// With [`SilhouetteMode::Mean`], this is the same as [`silhouette`]. With
+/// [`SilhouetteMode::ClusterWeighted`], each cluster has the same influence on the
+/// result, independent of its size, e.g., for clusterings with very unbalanced sizes.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the cost (use a float type)
+/// * `mat` - a pairwise distance matrix
+/// * `assi` - the cluster assignment
+/// * `samples` - whether to keep the individual samples, or not
+/// * `mode` - how to average the individual silhouette values
+///
+/// returns a tuple containing:
+/// * the average silhouette
+/// * the individual silhouette values (empty if `samples = false`)
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mode = kmedoids::SilhouetteMode::ClusterWeighted;
+/// let (sil, _): (f64, _) = kmedoids::silhouette_with_mode(&data, &[0, 0, 0, 1], false, mode);
+/// println!("Silhouette is: {}", sil);
+/// ```
+pub fn silhouette_with_mode<M, N, L>(mat: &M, assi: &[usize], samples: bool, mode: SilhouetteMode) -> (L, Vec<L>)
 where
 	N: Zero + PartialOrd + Copy,
 	L: AddAssign
@@ -58,7 +116,7 @@ where
 	} else {
 		vec![L::zero(); 0]
 	};
-	let mut lsum: L = L::zero();
+	let (mut lsum, mut wsum): (L, L) = (L::zero(), L::zero());
 	let mut buf = Vec::<(u32, L)>::new();
 	for (i, &ai) in assi.iter().enumerate() {
 		buf.clear();
@@ -91,12 +149,66 @@ where
 		if samples {
 			sil[i] = s;
 		}
-		lsum += s;
+		match mode {
+			SilhouetteMode::Mean => lsum += s,
+			SilhouetteMode::ClusterWeighted => {
+				let w = L::one() / (buf[ai].0 + 1).into(); // 1 / cluster size
+				lsum += s * w;
+				wsum += w;
+			}
+		}
 	}
 	if samples {
 		assert_eq!(sil.len(), assi.len(), "Length not as expected.");
 	}
-	(lsum.div((assi.len() as u32).into()), sil)
+	match mode {
+		SilhouetteMode::Mean => (lsum.div((assi.len() as u32).into()), sil),
+		SilhouetteMode::ClusterWeighted => (checked_div(lsum, wsum), sil),
+	}
+}
+
+/// Compute the Silhouette of a strict partitional clustering, with a distance function.
+///
+/// This computes the distances on demand, using a [`ClosureMatrix`], and hence needs
+/// no precomputed distance matrix, saving O(N²) memory. Every distance is computed twice.
+///
+/// * type `F` - distance function type
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the cost (use a float type)
+/// * `n` - the number of objects
+/// * `assi` - the cluster assignment
+/// * `dist_fn` - the distance function of two objects
+///
+/// returns a tuple containing:
+/// * the average silhouette
+/// * the individual silhouette values
+///
+/// ## Panics
+///
+/// * panics when the assignment does not have length n
+///
+/// ## Example
+/// ```
+/// let points = [0f64, 1., 2., 10., 11.];
+/// let (sil, _): (f64, _) = kmedoids::silhouette_from_assignments(5, &[0, 0, 0, 1, 1], |i, j| (points[i] - points[j]).abs());
+/// println!("Silhouette is: {}", sil);
+/// ```
+pub fn silhouette_from_assignments<F, N, L>(n: usize, assi: &[usize], dist_fn: F) -> (L, Vec<L>)
+where
+	F: Fn(usize, usize) -> N,
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign
+		+ Div<Output = L>
+		+ Sub<Output = L>
+		+ Signed
+		+ Zero
+		+ PartialOrd
+		+ Copy
+		+ From<N>
+		+ From<u32>,
+{
+	assert_eq!(assi.len(), n, "assignment must have length n");
+	silhouette(&ClosureMatrix::new(n, dist_fn), assi, true)
 }
 
 /// Compute the Medoid Silhouette of a clustering.
@@ -126,6 +238,7 @@ where
 /// ## Panics
 ///
 /// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid occurs more than once
 ///
 /// ## Example
 /// Given a dissimilarity matrix of size 4 x 4, use:
@@ -137,6 +250,55 @@ where
 /// println!("Silhouette is: {}", sil);
 /// ```
 pub fn medoid_silhouette<M, N, L>(mat: &M, meds: &[usize], samples: bool) -> (L, Vec<L>)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign
+		+ Div<Output = L>
+		+ Sub<Output = L>
+		+ Signed
+		+ Zero
+		+ PartialOrd
+		+ Copy
+		+ From<N>
+		+ From<u32>,
+	M: ArrayAdapter<N>,
+{
+	medoid_silhouette_with_mode(mat, meds, samples, SilhouetteMode::Mean)
+}
+
+/// Compute the Medoid Silhouette of a clustering, with the given averaging.
+///
+/// With [`SilhouetteMode::Mean`], this is the same as [`medoid_silhouette`]. With
+/// [`SilhouetteMode::ClusterWeighted`], each cluster of the nearest medoid has the same
+/// influence on the result, independent of its size. This needs O(N) additional memory
+/// for the individual silhouette values and cluster labels.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the cost (use a float type)
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+/// * `samples` - whether to keep the individual samples, or not
+/// * `mode` - how to average the individual silhouette values
+///
+/// returns a tuple containing:
+/// * the average medoid silhouette
+/// * the individual medoid silhouette values (empty if `samples = false`)
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a medoid occurs more than once
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mode = kmedoids::SilhouetteMode::ClusterWeighted;
+/// let (sil, _): (f64, _) = kmedoids::medoid_silhouette_with_mode(&data, &[0, 3], false, mode);
+/// println!("Silhouette is: {}", sil);
+/// ```
+pub fn medoid_silhouette_with_mode<M, N, L>(mat: &M, meds: &[usize], samples: bool, mode: SilhouetteMode) -> (L, Vec<L>)
 where
 	N: Zero + PartialOrd + Copy,
 	L: AddAssign
@@ -153,27 +315,46 @@ where
 	let (n, k) = (mat.len(), meds.len());
 	assert!(mat.is_square(), "Dissimilarity matrix is not square");
 	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(meds.iter().collect::<std::collections::HashSet<_>>().len() == k, "duplicate medoids");
 	let mut sil = vec![L::one(); if samples { n } else { 0 }];
 	if k == 1 { return (L::one(), sil); } // not really well-defined
 	assert!(k <= n, "invalid k, must be over 1 and at most N");
+	let weighted = mode == SilhouetteMode::ClusterWeighted;
+	// the nearest medoid and the loss of every object, for weighting by the cluster size
+	let mut near = Vec::<(usize, L)>::with_capacity(if weighted { n } else { 0 });
 	let mut loss = L::zero();
 	for i in 0..n {
 		let (d1, d2) = (mat.get(i, meds[0]), mat.get(i, meds[1]));
-		let mut best = if d1 < d2 { (d1, d2) } else { (d2, d1) };
-		for &m in meds.iter().skip(2) {
-			let d = mat.get(i, m);
+		let mut best = if d1 <= d2 { (d1, d2, 0) } else { (d2, d1, 1) };
+		for (m, &me) in meds.iter().enumerate().skip(2) {
+			let d = mat.get(i, me);
 			if d < best.0 {
-				best = (d, best.0);
+				best = (d, best.0, m);
 			}
 			else if d < best.1 {
-				best = (best.0, d);
+				best = (best.0, d, best.2);
 			}
 		}
-		if !N::is_zero(&best.0) {
+		let s = if !N::is_zero(&best.0) {
 			let s = <L as From<N>>::from(best.0) / <L as From<N>>::from(best.1);
 			if samples { sil[i] = L::one() - s; }
 			loss += s;
+			s
+		} else {
+			L::zero()
+		};
+		if weighted { near.push((best.2, s)); }
+	}
+	if weighted {
+		let mut size = vec![0u32; k];
+		near.iter().for_each(|&(m, _)| size[m] += 1);
+		let (mut lsum, mut wsum) = (L::zero(), L::zero());
+		for &(m, s) in near.iter() {
+			let w = L::one() / size[m].into();
+			lsum += s * w;
+			wsum += w;
 		}
+		return (L::one() - lsum / wsum, sil);
 	}
 	loss = L::one() - loss / <L as From<u32>>::from(n as u32);
 	(loss, sil)
@@ -190,3 +371,425 @@ where
 		L::zero()
 	}
 }
+
+/// Which silhouette to use for [`silhouette_plot_data`].
+#[derive(Debug, Clone, Copy)]
+pub enum SilhouetteKind<'a> {
+	/// the full silhouette, O(N²)
+	Full,
+	/// the simplified medoid silhouette, O(Nk), using the given medoids.
+	/// This assumes that every object is assigned to its nearest medoid.
+	Medoid(&'a [usize]),
+}
+
+/// Silhouette values of a single cluster, as used for a silhouette plot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterSilhouette<L> {
+	/// the cluster label
+	pub label: usize,
+	/// the number of cluster members
+	pub size: usize,
+	/// the average silhouette of the cluster members (zero for empty clusters)
+	pub mean: L,
+	/// the cluster members and their silhouette, sorted by descending silhouette
+	pub members: Vec<(usize, L)>,
+}
+
+/// Compute the data for a silhouette plot.
+///
+/// The classic silhouette plot shows, for each cluster, the silhouette values of its
+/// members as bars sorted in descending order. This computes the silhouette values,
+/// groups them by cluster, and sorts them. For large data sets, use the medoid
+/// silhouette, which only needs O(Nk) distance computations.
+///
+/// With the medoid silhouette, objects labeled as noise (`usize::MAX`) are not part of any
+/// cluster, but still included in the average silhouette. The full silhouette does not
+/// support noise labels.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the cost (use a float type)
+/// * `mat` - a pairwise distance matrix
+/// * `assi` - the cluster assignment
+/// * `kind` - whether to use the full or the medoid silhouette
+///
+/// returns a tuple containing:
+/// * the average silhouette
+/// * the silhouette values of each cluster, indexed by the cluster label
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when a label is `usize::MAX` (noise) with the full silhouette
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter): (f64, _, _) = kmedoids::alternating(&data, &mut meds, 100);
+/// let (sil, clusters): (f64, _) = kmedoids::silhouette_plot_data(&data, &assi, kmedoids::SilhouetteKind::Full);
+/// for c in clusters.iter() {
+///     println!("Cluster {} of size {}: {}", c.label, c.size, c.mean);
+/// }
+/// ```
+pub fn silhouette_plot_data<M, N, L>(mat: &M, assi: &[usize], kind: SilhouetteKind) -> (L, Vec<ClusterSilhouette<L>>)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign
+		+ Div<Output = L>
+		+ Sub<Output = L>
+		+ Signed
+		+ Zero
+		+ PartialOrd
+		+ Copy
+		+ From<N>
+		+ From<u32>,
+	M: ArrayAdapter<N>,
+{
+	let (sil, samples) = match kind {
+		SilhouetteKind::Full => {
+			assert!(!assi.contains(&usize::MAX), "noise labels are not supported by the full silhouette");
+			silhouette(mat, assi, true)
+		}
+		SilhouetteKind::Medoid(meds) => medoid_silhouette(mat, meds, true),
+	};
+	let is_noise = |a: usize| a == usize::MAX;
+	let k = assi.iter().filter(|&&a| !is_noise(a)).map(|&a| a + 1).max().unwrap_or(0);
+	let mut clusters = (0..k)
+		.map(|label| ClusterSilhouette { label, size: 0, mean: L::zero(), members: vec![] })
+		.collect::<Vec<_>>();
+	for (i, (&a, &s)) in assi.iter().zip(samples.iter()).enumerate() {
+		if !is_noise(a) {
+			clusters[a].members.push((i, s));
+		}
+	}
+	for c in clusters.iter_mut() {
+		c.size = c.members.len();
+		c.members.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+		let mut sum = L::zero();
+		c.members.iter().for_each(|x| sum += x.1);
+		c.mean = checked_div(sum, (c.size as u32).into());
+	}
+	(sil, clusters)
+}
+
+/// Evaluation measures of a medoid clustering, see [`evaluate_all`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Evaluation<L> {
+	/// the total deviation, i.e., the sum of distances to the assigned medoids
+	pub loss: L,
+	/// the average silhouette, as computed by [`silhouette`]
+	pub silhouette: L,
+	/// the average simplified silhouette, using the distance to the assigned medoid
+	/// and the nearest other medoid instead of the average distances
+	pub simplified_silhouette: L,
+	/// the average medoid silhouette, as computed by [`medoid_silhouette`]
+	pub medoid_silhouette: L,
+}
+
+/// Evaluate a medoid clustering with multiple measures at once.
+///
+/// This computes the total deviation, the silhouette, the simplified silhouette, and
+/// the medoid silhouette while reading every row of the distance matrix only once,
+/// instead of once per measure. The silhouette and the medoid silhouette are identical
+/// to [`silhouette`] and [`medoid_silhouette`]. Note that the medoid silhouette assumes
+/// that every object is assigned to the nearest medoid, while the other measures use the
+/// given assignment.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * type `L` - number data type such as `f64` for the cost (use a float type)
+/// * `mat` - a pairwise distance matrix
+/// * `meds` - the medoid list
+/// * `assi` - the cluster assignment, referring to the medoid list
+///
+/// returns the evaluation measures
+///
+/// ## Panics
+///
+/// * panics when the dissimilarity matrix is not square
+/// * panics when the assignment does not refer to the medoid list
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = kmedoids::random_initialization(4, 2, &mut rand::thread_rng());
+/// let (loss, assi, n_iter): (f64, _, _) = kmedoids::alternating(&data, &mut meds, 100);
+/// let eval: kmedoids::Evaluation<f64> = kmedoids::evaluate_all(&data, &meds, &assi);
+/// println!("Silhouette is: {}, Medoid Silhouette is: {}", eval.silhouette, eval.medoid_silhouette);
+/// ```
+pub fn evaluate_all<M, N, L>(mat: &M, meds: &[usize], assi: &[usize]) -> Evaluation<L>
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign
+		+ Div<Output = L>
+		+ Sub<Output = L>
+		+ Signed
+		+ Zero
+		+ PartialOrd
+		+ Copy
+		+ From<N>
+		+ From<u32>,
+	M: ArrayAdapter<N>,
+{
+	check_evaluation_input(mat, meds, assi);
+	let (mut row, mut buf) = (Vec::with_capacity(mat.len()), Vec::new());
+	let rows = (0..mat.len()).map(|i| evaluate_row(mat, meds, assi, i, &mut row, &mut buf));
+	combine_evaluation(meds.len(), assi, rows)
+}
+
+/// Validate the input of evaluate_all
+pub(crate) fn check_evaluation_input<M, N>(mat: &M, meds: &[usize], assi: &[usize])
+where
+	N: Zero + PartialOrd + Copy,
+	M: ArrayAdapter<N>,
+{
+	let (n, k) = (mat.len(), meds.len());
+	assert!(mat.is_square(), "Dissimilarity matrix is not square");
+	assert!(n <= u32::MAX as usize, "N is too large");
+	assert!(k > 0 && k <= n, "invalid k, must be over 0 and at most N");
+	assert_eq!(assi.len(), n, "assignment has the wrong length");
+	assert!(assi.iter().all(|&a| a < k), "assignment does not refer to the medoids");
+}
+
+/// Evaluate a single row of the distance matrix, not exposed (use evaluate_all)
+///
+/// returns the distance to the assigned medoid, the silhouette, the simplified silhouette,
+/// and the medoid silhouette "loss" term (nearest / second nearest) of object i
+#[inline]
+pub(crate) fn evaluate_row<M, N, L>(
+	mat: &M,
+	meds: &[usize],
+	assi: &[usize],
+	i: usize,
+	row: &mut Vec<N>,
+	buf: &mut Vec<(u32, L)>,
+) -> (L, L, L, L)
+where
+	N: Zero + PartialOrd + Copy,
+	L: AddAssign + Div<Output = L> + Sub<Output = L> + Signed + Zero + PartialOrd + Copy + From<N> + From<u32>,
+	M: ArrayAdapter<N>,
+{
+	row.clear();
+	row.extend((0..assi.len()).map(|j| mat.get(i, j)));
+	// silhouette, as in silhouette()
+	buf.clear();
+	for (j, (&aj, &d)) in assi.iter().zip(row.iter()).enumerate() {
+		while aj >= buf.len() {
+			buf.push((0, L::zero()));
+		}
+		if i != j {
+			buf[aj].0 += 1;
+			buf[aj].1 += d.into();
+		}
+	}
+	let ai = assi[i];
+	let sil = if buf[ai].0 > 0 {
+		let a = checked_div(buf[ai].1, buf[ai].0.into());
+		let mut tmp = buf
+			.iter()
+			.enumerate()
+			.filter(|&(i, _)| i != ai)
+			.map(|(_, p)| checked_div(p.1, p.0.into()));
+		let tmp2 = tmp.next().unwrap_or_else(L::zero);
+		let b = tmp.fold(tmp2, |x, y| if y < x { y } else { x });
+		checked_div(b - a, if a > b { a } else { b })
+	} else {
+		L::zero() // singleton
+	};
+	let a = row[meds[ai]];
+	if meds.len() == 1 {
+		return (a.into(), sil, L::zero(), L::zero());
+	}
+	// simplified silhouette
+	let b = meds.iter()
+		.enumerate()
+		.filter(|&(c, _)| c != ai)
+		.map(|(_, &m)| row[m])
+		.reduce(|x, y| if y < x { y } else { x })
+		.unwrap();
+	let (la, lb) = (<L as From<N>>::from(a), <L as From<N>>::from(b));
+	let ssil = checked_div(lb - la, if la > lb { la } else { lb });
+	// medoid silhouette, as in medoid_silhouette()
+	let (d1, d2) = (row[meds[0]], row[meds[1]]);
+	let mut best = if d1 < d2 { (d1, d2) } else { (d2, d1) };
+	for &m in meds.iter().skip(2) {
+		let d = row[m];
+		if d < best.0 {
+			best = (d, best.0);
+		}
+		else if d < best.1 {
+			best = (best.0, d);
+		}
+	}
+	let msil = if N::is_zero(&best.0) { L::zero() } else { <L as From<N>>::from(best.0) / <L as From<N>>::from(best.1) };
+	(la, sil, ssil, msil)
+}
+
+/// Sum up the results of evaluate_row in order, not exposed (use evaluate_all)
+pub(crate) fn combine_evaluation<L>(k: usize, assi: &[usize], rows: impl Iterator<Item = (L, L, L, L)>) -> Evaluation<L>
+where
+	L: AddAssign + Div<Output = L> + Sub<Output = L> + Signed + Zero + PartialOrd + Copy + From<u32>,
+{
+	let mut sum = (L::zero(), L::zero(), L::zero(), L::zero());
+	for (td, sil, ssil, msil) in rows {
+		sum.0 += td;
+		sum.1 += sil;
+		sum.2 += ssil;
+		sum.3 += msil;
+	}
+	let n: L = (assi.len() as u32).into();
+	let single = assi.iter().all(|&a| a == 0); // silhouette() returns 0 for a single cluster
+	Evaluation {
+		loss: sum.0,
+		silhouette: if single { L::zero() } else { sum.1 / n },
+		simplified_silhouette: sum.2 / n,
+		medoid_silhouette: if k == 1 { L::one() } else { L::one() - sum.3 / n }, // k = 1 not really well-defined
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		arrayadapter::{ArrayAdapter, ClosureMatrix, LowerTriangle}, assign_to_medoids, evaluate_all, medoid_silhouette, medoid_silhouette_with_mode, silhouette,
+		silhouette_from_assignments, silhouette_plot_data, silhouette_with_mode, SilhouetteKind, SilhouetteMode,
+	};
+
+	#[test]
+	fn test_silhouette_plot_data() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let (assi, meds) = (vec![0, 0, 0, 1, 1], vec![0, 3]);
+		let (sil, _): (f64, _) = silhouette(&data, &assi, false);
+		let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
+		for (kind, expect) in [(SilhouetteKind::Full, sil), (SilhouetteKind::Medoid(&meds), msil)] {
+			let (psil, clusters): (f64, _) = silhouette_plot_data(&data, &assi, kind);
+			assert_eq!(psil, expect, "Silhouette not as expected");
+			assert_eq!(clusters.len(), 2, "number of clusters not as expected");
+			let all = clusters.iter().flat_map(|c| c.members.iter().map(|x| x.1)).collect::<Vec<_>>();
+			assert_eq!(all.len(), 5, "number of samples not as expected");
+			assert!((all.iter().sum::<f64>() / 5. - expect).abs() < 1e-15, "Silhouette not as expected");
+			for (label, c) in clusters.iter().enumerate() {
+				assert_eq!(c.label, label, "label not as expected");
+				assert_eq!(c.size, c.members.len(), "size not as expected");
+				assert!(c.members.iter().all(|&(i, _)| assi[i] == label), "members not as expected");
+				assert!(c.members.windows(2).all(|w| w[0].1 >= w[1].1), "not sorted descending");
+				assert!((c.members.iter().map(|x| x.1).sum::<f64>() / c.size as f64 - c.mean).abs() < 1e-15, "mean not as expected");
+			}
+		}
+	}
+
+	#[test]
+	fn test_silhouette_plot_data_noise() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let meds = vec![0, 3];
+		let (msil, samples): (f64, _) = medoid_silhouette(&data, &meds, true);
+		let (psil, clusters): (f64, _) = silhouette_plot_data(&data, &[0, usize::MAX, 0, 1, usize::MAX], SilhouetteKind::Medoid(&meds));
+		assert_eq!(psil, msil, "Silhouette not as expected");
+		assert_eq!(clusters.len(), 2, "number of clusters not as expected");
+		assert_eq!(clusters[0].members, vec![(0, samples[0]), (2, samples[2])], "members not as expected");
+		assert_eq!(clusters[1].members, vec![(3, samples[3])], "members not as expected");
+		let (_, clusters): (f64, _) = silhouette_plot_data(&data, &[usize::MAX; 5], SilhouetteKind::Medoid(&meds));
+		assert!(clusters.is_empty(), "noise not as expected");
+	}
+
+	#[test]
+	#[should_panic(expected = "noise labels")]
+	fn test_silhouette_plot_data_full_noise() {
+		let data = LowerTriangle {
+			n: 3,
+			data: vec![1, 2, 3],
+		};
+		let _: (f64, _) = silhouette_plot_data(&data, &[0, usize::MAX, 1], SilhouetteKind::Full);
+	}
+
+	#[cfg(feature = "rand")]
+	#[test]
+	fn test_evaluate_all() {
+		use crate::arrayadapter::ArrayAdapter;
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let mut rng = StdRng::seed_from_u64(1);
+		for &(n, k) in [(5, 1), (10, 2), (50, 3), (100, 7)].iter() {
+			let data = LowerTriangle {
+				n,
+				data: (0..n * (n - 1) / 2).map(|_| rng.gen_range(1..1000)).collect::<Vec<u32>>(),
+			};
+			let meds = crate::random_initialization(n, k, &mut rng);
+			let (td, assi): (f64, _) = assign_to_medoids(&data, &meds);
+			let eval: crate::Evaluation<f64> = evaluate_all(&data, &meds, &assi);
+			let (sil, _): (f64, _) = silhouette(&data, &assi, false);
+			let (msil, _): (f64, _) = medoid_silhouette(&data, &meds, false);
+			assert_eq!(eval.loss, td, "loss not as expected");
+			assert_eq!(eval.silhouette, sil, "Silhouette not as expected");
+			assert_eq!(eval.medoid_silhouette, msil, "Medoid Silhouette not as expected");
+			// with the nearest assignment, the simplified silhouette is 1 - a / b
+			let mut ssil = 0.;
+			for (i, &a) in assi.iter().enumerate() {
+				let da = data.get(i, meds[a]) as f64;
+				let db = (0..k).filter(|&c| c != a).map(|c| data.get(i, meds[c]) as f64).fold(f64::INFINITY, f64::min);
+				if k > 1 && db > 0. {
+					ssil += (db - da) / db;
+				}
+			}
+			assert!((eval.simplified_silhouette - ssil / n as f64).abs() < 1e-12, "Simplified Silhouette not as expected");
+		}
+	}
+
+	#[test]
+	fn test_silhouette_from_assignments() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		for assi in [vec![0, 0, 0, 1, 1], vec![0, 0, 1, 2, 2], vec![1, 0, 1, 0, 1]] {
+			let (sil, samples): (f64, _) = silhouette(&data, &assi, true);
+			let (sil2, samples2): (f64, _) = silhouette_from_assignments(5, &assi, |i, j| data.get(i, j));
+			assert_eq!(sil2, sil, "Silhouette not as expected");
+			assert_eq!(samples2, samples, "samples not as expected");
+		}
+	}
+
+	#[test]
+	#[should_panic(expected = "duplicate medoids")]
+	fn test_medoid_silhouette_duplicate_medoids() {
+		let data = LowerTriangle {
+			n: 5,
+			data: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+		};
+		let _: (f64, _) = medoid_silhouette(&data, &[0, 0, 2], false);
+	}
+
+	#[test]
+	fn test_silhouette_cluster_weighted() {
+		// a large tight cluster, and a small loose cluster
+		let points = [0., 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 10., 14.];
+		let data = ClosureMatrix::new(10, |i: usize, j: usize| f64::abs(points[i] - points[j]));
+		let assi = vec![0, 0, 0, 0, 0, 0, 0, 0, 1, 1];
+		let (sil, samples): (f64, _) = silhouette(&data, &assi, true);
+		let (msil, _): (f64, _) = silhouette_with_mode(&data, &assi, false, SilhouetteMode::Mean);
+		let (wsil, wsamples): (f64, _) = silhouette_with_mode(&data, &assi, true, SilhouetteMode::ClusterWeighted);
+		assert_eq!(msil, sil, "Silhouette not as expected");
+		assert_eq!(wsamples, samples, "samples not as expected");
+		assert!(wsil < sil, "weighted Silhouette not lower: {} vs. {}", wsil, sil);
+		let (_, clusters): (f64, _) = silhouette_plot_data(&data, &assi, SilhouetteKind::Full);
+		let expect = (clusters[0].mean + clusters[1].mean) / 2.;
+		assert!((wsil - expect).abs() < 1e-12, "not the mean of the cluster means");
+		// the same for the medoid silhouette
+		let meds = [4, 9];
+		let (sil, samples): (f64, _) = medoid_silhouette(&data, &meds, true);
+		let (msil, _): (f64, _) = medoid_silhouette_with_mode(&data, &meds, false, SilhouetteMode::Mean);
+		let (wsil, wsamples): (f64, _) = medoid_silhouette_with_mode(&data, &meds, true, SilhouetteMode::ClusterWeighted);
+		assert_eq!(msil, sil, "Medoid Silhouette not as expected");
+		assert_eq!(wsamples, samples, "samples not as expected");
+		assert!(wsil < sil, "weighted Medoid Silhouette not lower: {} vs. {}", wsil, sil);
+		let expect = (samples[..8].iter().sum::<f64>() / 8. + samples[8..].iter().sum::<f64>() / 2.) / 2.;
+		assert!((wsil - expect).abs() < 1e-12, "not the mean of the cluster means");
+	}
+}
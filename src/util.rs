@@ -1,57 +1,112 @@
 use crate::arrayadapter::ArrayAdapter;
+use core::fmt;
 use core::ops::AddAssign;
-use num_traits::{Signed, Zero, FromPrimitive};
+use num_traits::{Float, Signed, Zero, FromPrimitive};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-/// Object id and distance pair
+/// Medoid and distance pair
 #[derive(Debug, Copy, Clone)]
-pub(crate) struct DistancePair<N> {
-	pub(crate) i: u32,
-	pub(crate) d: N,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DistancePair<N> {
+	/// the position of the medoid in the list of medoids, `u32::MAX` if missing
+	pub i: u32,
+	/// the distance to the medoid
+	pub d: N,
 }
-impl<N> DistancePair<N> {
+impl<N: Zero + PartialOrd> DistancePair<N> {
 	pub(crate) fn new(i: u32, d: N) -> Self {
+		debug_assert!(i != u32::MAX, "medoid index must not be the sentinel");
+		debug_assert!(d.partial_cmp(&N::zero()) != Some(core::cmp::Ordering::Less), "distances must be non-negative");
 		DistancePair { i, d }
 	}
 }
 impl<N: Zero> DistancePair<N> {
-	pub(crate) fn empty() -> Self {
+	/// The pair used for a missing medoid, e.g., the second nearest if k = 1
+	pub(crate) fn sentinel() -> Self {
 		DistancePair {
 			i: u32::MAX,
 			d: N::zero(),
 		}
 	}
 }
+impl<N> DistancePair<N> {
+	/// Whether this pair is missing a medoid, e.g., the second nearest if k = 1
+	#[inline]
+	pub fn is_sentinel(&self) -> bool {
+		self.i == u32::MAX
+	}
+}
+
+/// Ratio of two distances, with zero if either is zero.
+///
+/// This is the loss of a point in the Medoid Silhouette, the distance to the nearest
+/// medoid divided by the distance to the second nearest. If the nearest distance is
+/// zero, the ratio is zero. If the second nearest distance is zero, so is the nearest
+/// distance (the point has two medoids at distance zero), and the ratio is zero as well,
+/// rather than a NaN or infinity from dividing by zero.
+///
+/// ## Example
+/// ```
+/// assert_eq!(kmedoids::safe_ratio::<u32, f64>(1, 4), 0.25);
+/// assert_eq!(kmedoids::safe_ratio::<f64, f64>(2., 0.), 0.);
+/// ```
+#[inline]
+pub fn safe_ratio<N, L>(a: N, b: N) -> L
+	where
+		N: Zero,
+		L: Float + From<N>,
+{
+	if N::is_zero(&a) || N::is_zero(&b) { L::zero() } else { <L as From<N>>::from(a) / <L as From<N>>::from(b) }
+}
+
+/// Ratio of two distances, without checking for zero.
+///
+/// Unlike [`safe_ratio`], this divides by zero if `b` is zero, which gives infinity or NaN
+/// for floats. Use it only where `b` is known to be non-zero, e.g., when all distances of
+/// distinct points are positive, to avoid the comparisons. This is not `unsafe` in the
+/// Rust sense: the result is only wrong, not undefined.
+///
+/// ## Example
+/// ```
+/// assert_eq!(kmedoids::unsafe_ratio::<u32, f64>(1, 4), 0.25);
+/// assert!(kmedoids::unsafe_ratio::<f64, f64>(0., 0.).is_nan());
+/// ```
+#[inline]
+pub fn unsafe_ratio<N, L>(a: N, b: N) -> L
+	where
+		L: Float + From<N>,
+{
+	<L as From<N>>::from(a) / <L as From<N>>::from(b)
+}
 
 /// Information kept for each point: two such pairs
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct Rec<N> {
 	pub(crate) near: DistancePair<N>,
 	pub(crate) seco: DistancePair<N>,
 }
-impl<N> Rec<N> {
-	pub(crate) fn new(i1: u32, d1: N, i2: u32, d2: N) -> Rec<N> {
-		Rec {
-			near: DistancePair { i: i1, d: d1 },
-			seco: DistancePair { i: i2, d: d2 },
-		}
-	}
-}
 impl<N: Zero> Rec<N> {
 	pub(crate) fn empty() -> Self {
 		Rec {
-			near: DistancePair::empty(),
-			seco: DistancePair::empty(),
+			near: DistancePair::sentinel(),
+			seco: DistancePair::sentinel(),
 		}
 	}
 }
 
-/// Information kept for each point: three such pairs
+/// Information kept for each point: the three nearest medoids
 #[derive(Debug, Copy, Clone)]
-pub(crate) struct Reco<N> {
-	pub(crate) near: DistancePair<N>,
-	pub(crate) seco: DistancePair<N>,
-	pub(crate) third: DistancePair<N>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reco<N> {
+	/// the nearest medoid
+	pub near: DistancePair<N>,
+	/// the second nearest medoid
+	pub seco: DistancePair<N>,
+	/// the third nearest medoid
+	pub third: DistancePair<N>,
 }
+#[cfg(test)]
 impl<N> Reco<N> {
 	pub(crate) fn new(i1: u32, d1: N, i2: u32, d2: N, i3: u32, d3: N) -> Reco<N> {
 		Reco {
@@ -64,9 +119,9 @@ impl<N> Reco<N> {
 impl<N: Zero> Reco<N> {
 	pub(crate) fn empty() -> Self {
 		Reco {
-			near: DistancePair::empty(),
-			seco: DistancePair::empty(),
-			third: DistancePair::empty(),
+			near: DistancePair::sentinel(),
+			seco: DistancePair::sentinel(),
+			third: DistancePair::sentinel(),
 		}
 	}
 }
@@ -105,6 +160,59 @@ pub(crate) fn find_max<'a, L: 'a, I: 'a>(a: &mut I) -> (usize, L)
 	best
 }
 
+/// Order in which the swap candidates are scanned.
+pub(crate) enum ScanOrder<'a> {
+	/// 0..n in every iteration
+	Sequential,
+	/// the given permutation in every iteration
+	Fixed(&'a [usize]),
+	/// a new random permutation in every iteration
+	#[cfg(feature = "rand")]
+	Shuffled(&'a mut dyn rand::RngCore),
+}
+
+impl ScanOrder<'_> {
+	/// Initial candidate order
+	pub(crate) fn initial(&self, n: usize) -> Vec<usize> {
+		match self {
+			ScanOrder::Fixed(order) => {
+				assert_eq!(order.len(), n, "order must be a permutation of 0..n");
+				let mut seen = vec![false; n];
+				for &j in order.iter() {
+					assert!(j < n && !seen[j], "order must be a permutation of 0..n");
+					seen[j] = true;
+				}
+				order.to_vec()
+			}
+			_ => (0..n).collect(),
+		}
+	}
+
+	/// Prepare the next iteration, returns whether the order changed
+	#[inline]
+	pub(crate) fn next_iteration(&mut self, _seq: &mut [usize]) -> bool {
+		match self {
+			#[cfg(feature = "rand")]
+			ScanOrder::Shuffled(rng) => {
+				rand::seq::SliceRandom::shuffle(_seq, rng);
+				true
+			}
+			_ => false,
+		}
+	}
+}
+
+/// Loss of a single cluster with the given medoid, i.e., without choosing a better medoid.
+/// Used when a single cluster is requested with maxiter = 0.
+pub(crate) fn single_medoid_loss<M, N, L>(mat: &M, med: usize) -> L
+	where
+		N: Copy,
+		L: Zero + Copy + From<N>,
+		M: ArrayAdapter<N>,
+{
+	(0..mat.len()).map(|i| L::from(mat.get(i, med))).fold(L::zero(), L::add)
+}
+
 /// Choose the best medoid within a partition
 /// Used by ther alternating algorithm, or when a single cluster is requested.
 pub(crate) fn choose_medoid_within_partition<M, N, L>(
@@ -144,6 +252,28 @@ pub(crate) fn choose_medoid_within_partition<M, N, L>(
 	(best != first, sumb)
 }
 
+/// Check the (optional) cancellation flag.
+///
+/// This uses relaxed ordering, as we only need to eventually observe the flag.
+#[inline]
+pub(crate) fn is_cancelled(cancel: Option<&AtomicBool>) -> bool {
+	matches!(cancel, Some(c) if c.load(Ordering::Relaxed))
+}
+
+/// Find the point farthest from its nearest medoid, that is not a medoid yet.
+///
+/// Used to fill up BUILD when there is no further improvement. On ties, the first
+/// point is chosen.
+pub(crate) fn farthest_non_medoid<N: PartialOrd + Copy>(meds: &[usize], near: impl Iterator<Item = N>) -> usize {
+	let mut best: Option<(usize, N)> = None;
+	for (i, d) in near.enumerate() {
+		if !meds.contains(&i) && match best { Some(b) => d > b.1, None => true } {
+			best = Some((i, d));
+		}
+	}
+	best.expect("no non-medoid point left").0
+}
+
 /// Debug helper function
 pub(crate) fn debug_assert_assignment<M, N>(_mat: &M, _med: &[usize], _data: &[Rec<N>])
 	where
@@ -167,37 +297,160 @@ pub(crate) fn debug_assert_assignment<M, N>(_mat: &M, _med: &[usize], _data: &[R
 	}
 }
 
+/// Debug helper function, check that the distance d of i,j is the same as of j,i
+#[inline]
+pub(crate) fn debug_assert_symmetric<M, N>(_mat: &M, _i: usize, _j: usize, _d: N)
+	where
+		N: PartialOrd + Copy,
+		M: ArrayAdapter<N>,
+{
+	#[cfg(feature = "assertions")]
+	debug_assert!(_mat.get(_j, _i) == _d, "dissimilarity matrix is not symmetric at {},{}", _i, _j);
+}
+
+/// Check the invariants of the three nearest medoids of a point, for use in `debug_assert!`
+///
+/// The indexes must be less than k, or `u32::MAX` for missing entries (k < 3), which must
+/// come last. The indexes must be distinct, and the distances ascending.
+pub(crate) fn assert_reco_valid<N: PartialOrd>(reco: &Reco<N>, k: usize) -> bool {
+	let pairs = [&reco.near, &reco.seco, &reco.third];
+	let m = pairs.iter().take_while(|p| !p.is_sentinel()).count(); // present entries
+	pairs[m..].iter().all(|p| p.is_sentinel())
+		&& pairs[..m].iter().all(|p| (p.i as usize) < k)
+		&& (0..m).all(|a| (a + 1..m).all(|b| pairs[a].i != pairs[b].i))
+		&& pairs[..m].windows(2).all(|w| w[0].d <= w[1].d)
+}
+
+/// An inconsistency in the nearest medoids of a point, see [`verify_assignment`]
+#[derive(Debug, Copy, Clone)]
+pub struct AssignmentError<N> {
+	/// the point with the inconsistent record
+	pub point: usize,
+	/// which entry is inconsistent: 0 for the nearest, 1 for the second, 2 for the third nearest medoid
+	pub rank: usize,
+	/// the correct entry, from the distance matrix
+	pub expected_near: DistancePair<N>,
+	/// the stored entry
+	pub actual_near: DistancePair<N>,
+}
+
+impl<N: fmt::Debug> fmt::Display for AssignmentError<N> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"point {}: medoid of rank {} is {:?}, expected {:?}",
+			self.point, self.rank, self.actual_near, self.expected_near
+		)
+	}
+}
+
+impl<N: fmt::Debug> std::error::Error for AssignmentError<N> {}
+
+/// Verify the nearest, second and third nearest medoids of every point.
+///
+/// The algorithms keep these records up to date after every swap. Use this to check them
+/// after modifying the medoids or records, e.g., with [`crate::apply_swap`]. Each stored
+/// entry must refer to a distinct medoid at the stored distance, which must be the nearest
+/// (second, third nearest) distance of all medoids; with ties, any of the equally near
+/// medoids is accepted. With fewer than three medoids, the remaining entries must be missing.
+/// This takes O(nk log k) time.
+///
+/// * type `M` - matrix data type such as `ndarray::Array2` or `kmedoids::arrayadapter::LowerTriangle`
+/// * type `N` - number data type such as `u32` or `f64`
+/// * `mat` - a pairwise distance matrix
+/// * `med` - the list of medoids
+/// * `data` - the nearest medoids of every point
+///
+/// returns `None` if all records are consistent, or the first inconsistency found
+///
+/// ## Panics
+///
+/// * panics when `data` does not have one record for every point
+///
+/// ## Example
+/// Given a dissimilarity matrix of size 4 x 4, use:
+/// ```
+/// let data = ndarray::arr2(&[[0,1,2,3],[1,0,4,5],[2,4,0,6],[3,5,6,0]]);
+/// let mut meds = vec![0, 1, 2];
+/// let (_, mut reco): (f64, _) = kmedoids::initial_reco(&data, &meds);
+/// assert!(kmedoids::verify_assignment(&data, &meds, &reco).is_none());
+/// meds[1] = 3; // without updating the records
+/// assert_eq!(kmedoids::verify_assignment(&data, &meds, &reco).map(|e| e.point), Some(0));
+/// ```
+pub fn verify_assignment<M, N>(mat: &M, med: &[usize], data: &[Reco<N>]) -> Option<AssignmentError<N>>
+	where
+		N: Zero + PartialOrd + Copy,
+		M: ArrayAdapter<N>,
+{
+	assert_eq!(data.len(), mat.len(), "one record per point is required");
+	let k = med.len();
+	let mut dist = Vec::with_capacity(k);
+	for (o, reco) in data.iter().enumerate() {
+		dist.clear();
+		dist.extend(med.iter().enumerate().map(|(i, &m)| DistancePair { i: i as u32, d: mat.get(o, m) }));
+		dist.sort_by(|a, b| a.d.partial_cmp(&b.d).unwrap_or(core::cmp::Ordering::Equal));
+		let pairs = [reco.near, reco.seco, reco.third];
+		for (rank, &actual) in pairs.iter().enumerate() {
+			let expected = dist.get(rank).copied().unwrap_or_else(DistancePair::sentinel);
+			let consistent = if expected.is_sentinel() {
+				actual.is_sentinel()
+			} else {
+				(actual.i as usize) < k
+					&& pairs[..rank].iter().all(|p| p.i != actual.i)
+					&& actual.d == expected.d
+					&& mat.get(o, med[actual.i as usize]) == actual.d
+			};
+			if !consistent {
+				return Some(AssignmentError { point: o, rank, expected_near: expected, actual_near: actual });
+			}
+		}
+	}
+	None
+}
+
 /// Debug helper function, for methods with three nearest medoids
 pub(crate) fn debug_assert_assignment_th<M, N>(_mat: &M, _med: &[usize], _data: &[Reco<N>])
 	where
-		N: PartialOrd + Copy,
+		N: Zero + PartialOrd + Copy,
 		M: ArrayAdapter<N>,
 {
+	for r in _data.iter() {
+		debug_assert!(assert_reco_valid(r, _med.len()), "invalid nearest medoids record");
+	}
 	#[cfg(feature = "assertions")]
-	for o in 0.._mat.len() {
-		debug_assert!(
-			_mat.get(o, _med[_data[o].near.i as usize]) == _data[o].near.d,
-			"primary assignment inconsistent"
-		);
-		debug_assert!(
-			_mat.get(o, _med[_data[o].seco.i as usize]) == _data[o].seco.d,
-			"secondary assignment inconsistent"
-		);
-		debug_assert!(
-			_mat.get(o, _med[_data[o].third.i as usize]) == _data[o].third.d,
-			"third assignment inconsistent"
-		);
-		debug_assert!(
-			_data[o].near.d <= _data[o].seco.d,
-			"nearest is farther than second nearest"
-		);
-		debug_assert!(
-			_data[o].seco.d <= _data[o].third.d,
-			"second nearest is farther than third nearest"
+	debug_assert!(verify_assignment(_mat, _med, _data).is_none(), "assignment inconsistent");
+}
+
+/// test that a loss history never increases, used in tests only
+#[cfg(test)]
+pub(crate) fn assert_monotone_decreasing<L: PartialOrd + std::fmt::Debug>(history: &[L], name: &str) {
+	for w in history.windows(2) {
+		assert!(
+			w[1].partial_cmp(&w[0]) != Some(core::cmp::Ordering::Greater),
+			"{}: loss increased from {:?} to {:?} in {:?}",
+			name, w[0], w[1], history
 		);
 	}
 }
 
+/// the loss after every iteration, from running with maxiter = 0, 1, 2, ... until convergence,
+/// also testing that the last entry is exactly the loss of an unrestricted run, used in tests only
+#[cfg(test)]
+pub(crate) fn loss_history<L: PartialEq + std::fmt::Debug>(mut run: impl FnMut(usize) -> (L, usize), name: &str) -> Vec<L> {
+	let mut history = Vec::new();
+	for maxiter in 0.. {
+		let (loss, n_iter) = run(maxiter);
+		history.push(loss);
+		if n_iter < maxiter {
+			break; // converged
+		}
+		assert!(maxiter < 100, "{}: no convergence", name);
+	}
+	let (loss, _) = run(1000);
+	assert_eq!(history.last(), Some(&loss), "{}: final loss not as in the history", name);
+	history
+}
+
 /// test two arrays for equality, used in tests only
 #[cfg(test)]
 pub(crate) fn assert_array(result: Vec<usize>, expect: Vec<usize>, msg: &'static str) {
@@ -207,3 +460,240 @@ pub(crate) fn assert_array(result: Vec<usize>, expect: Vec<usize>, msg: &'static
 		msg
 	);
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{assert_monotone_decreasing, assert_reco_valid, loss_history, safe_ratio, unsafe_ratio, verify_assignment, AssignmentError, DistancePair, Rec, Reco};
+	use crate::{arrayadapter::{AlignedLowerTriangle, CachingClosureMatrix, ClosureMatrix, DynArrayAdapter, JaggedMatrix, LowerTriangle, SquareMatrix}, AutoKAlgorithm, AutoKResult, BuildShortfall, ClusterIndex, ClusteringFingerprint, ConvergenceStats, DijkstraMatrix, Evaluation, FasterMSCState, FasterPAMState, InitStrategy, KMedoidsResult, PointIndex, Predictor, RunSummary, SilhouetteMode, StabilityReport, StepOutcome, SwapEvent};
+	use static_assertions::assert_impl_all;
+
+	// All data types must be usable from multiple threads
+	assert_impl_all!(LowerTriangle<f64>: Send, Sync);
+	assert_impl_all!(LowerTriangle<u32>: Send, Sync);
+	assert_impl_all!(AlignedLowerTriangle<f64, 64>: Send, Sync);
+	assert_impl_all!(crate::arrayadapter::StaticLowerTriangle<f64, 10>: Send, Sync);
+	assert_impl_all!(DistancePair<f64>: Send, Sync);
+	assert_impl_all!(DistancePair<u32>: Send, Sync);
+	assert_impl_all!(Rec<f64>: Send, Sync);
+	assert_impl_all!(Rec<u32>: Send, Sync);
+	assert_impl_all!(Reco<f64>: Send, Sync);
+	assert_impl_all!(Reco<u32>: Send, Sync);
+	assert_impl_all!(AssignmentError<f64>: Send, Sync);
+	assert_impl_all!(FasterPAMState<f64, f64>: Send, Sync);
+	assert_impl_all!(FasterPAMState<u32, i64>: Send, Sync);
+	assert_impl_all!(FasterMSCState<f64, f64>: Send, Sync);
+	assert_impl_all!(FasterMSCState<u32, f64>: Send, Sync);
+	assert_impl_all!(StepOutcome: Send, Sync);
+	assert_impl_all!(SwapEvent<u32, f64>: Send, Sync);
+	assert_impl_all!(SquareMatrix<f64>: Send, Sync);
+	assert_impl_all!(SquareMatrix<u32>: Send, Sync);
+	assert_impl_all!(JaggedMatrix<f64>: Send, Sync);
+	assert_impl_all!(JaggedMatrix<u32>: Send, Sync);
+	assert_impl_all!(DynArrayAdapter<f64>: Send, Sync);
+	assert_impl_all!(DynArrayAdapter<u32>: Send, Sync);
+	assert_impl_all!(CachingClosureMatrix<fn(usize, usize) -> f64, f64>: Send, Sync);
+	assert_impl_all!(ClosureMatrix<fn(usize, usize) -> f64>: Send, Sync);
+	#[cfg(feature = "lru")]
+	assert_impl_all!(crate::arrayadapter::LruCachedMatrix<fn(usize, usize) -> f64, f64>: Send, Sync);
+	#[cfg(feature = "polars")]
+	assert_impl_all!(crate::PolarsMatrix: Send, Sync);
+	assert_impl_all!(Evaluation<f64>: Send, Sync);
+	assert_impl_all!(KMedoidsResult<f64>: Send, Sync);
+	assert_impl_all!(RunSummary<f64>: Send, Sync);
+	assert_impl_all!(StabilityReport: Send, Sync);
+	assert_impl_all!(Predictor<(f64, f64), f64>: Send, Sync);
+	assert_impl_all!(AutoKAlgorithm: Send, Sync);
+	assert_impl_all!(SilhouetteMode: Send, Sync);
+	assert_impl_all!(AutoKResult<f64>: Send, Sync);
+	assert_impl_all!(BuildShortfall: Send, Sync);
+	assert_impl_all!(InitStrategy: Send, Sync);
+	assert_impl_all!(ConvergenceStats: Send, Sync);
+	assert_impl_all!(PointIndex: Send, Sync);
+	assert_impl_all!(ClusterIndex: Send, Sync);
+	assert_impl_all!(ClusteringFingerprint: Send, Sync);
+	assert_impl_all!(DijkstraMatrix<f64>: Send, Sync);
+	#[cfg(feature = "profile")]
+	assert_impl_all!(crate::profiling::RunProfile: Send, Sync);
+
+	/// The 5 point example, with distance type N
+	macro_rules! fixture {
+		($n:ty) => {
+			LowerTriangle { n: 5, data: [1u8, 2, 3, 4, 5, 6, 7, 8, 9, 1].iter().map(|&x| x as $n).collect::<Vec<$n>>() }
+		};
+	}
+
+	/// Total deviation algorithms, with distance type N and loss type L
+	macro_rules! test_td_types {
+		($($name:ident: $n:ty => $l:ty,)*) => {$(
+			#[test]
+			fn $name() {
+				let data = fixture!($n);
+				let (loss, assi, meds, _, _): ($l, _, _, _, _) = crate::pam(&data, 2, 10);
+				assert_eq!((loss as f64, assi, meds), (4., vec![0, 0, 0, 1, 1], vec![0, 3]), "PAM not as expected");
+				let mut meds = vec![0, 1];
+				let (loss, assi, _, _): ($l, _, _, _) = crate::fasterpam(&data, &mut meds, 0, 10);
+				assert_eq!((loss as f64, assi, meds), (4., vec![0, 0, 0, 1, 1], vec![0, 3]), "FasterPAM not as expected");
+			}
+		)*};
+	}
+
+	/// Silhouette based algorithms, with distance type N and (float) loss type L
+	macro_rules! test_sil_types {
+		($($name:ident: $n:ty => $l:ty,)*) => {$(
+			#[test]
+			fn $name() {
+				let data = fixture!($n);
+				let mut meds = vec![0, 1];
+				let (loss, assi, _, _): ($l, _, _, _) = crate::fastermsc(&data, &mut meds, 0, 10);
+				assert_eq!((loss as f64, assi, meds), (0.8805555555555555, vec![0, 0, 0, 1, 1], vec![0, 4]), "FasterMSC not as expected");
+				let (loss, assi, meds, _, _): ($l, _, _, _, _) = crate::pammedsil(&data, 2, 10);
+				assert_eq!((loss as f64, assi, meds), (0.8805555555555555, vec![0, 0, 0, 1, 1], vec![0, 4]), "PAMMEDSIL not as expected");
+				let (sil, _): ($l, _) = crate::silhouette(&data, &[0, 0, 0, 1, 1], false);
+				assert_eq!(sil as f64, 0.7522494172494172, "Silhouette not as expected");
+			}
+		)*};
+	}
+
+	// Supported combinations of distance and loss types
+	test_td_types! {
+		test_td_u8: u8 => i64,
+		test_td_u16: u16 => i64,
+		test_td_u32: u32 => i64,
+		test_td_u64: u64 => i128,
+		test_td_i32: i32 => i64,
+		test_td_i64: i64 => i64,
+		test_td_f32: f32 => f64,
+		test_td_f64: f64 => f64,
+	}
+	test_sil_types! {
+		test_sil_u8: u8 => f64,
+		test_sil_u16: u16 => f64,
+		test_sil_u32: u32 => f64,
+		test_sil_i32: i32 => f64,
+		test_sil_f32: f32 => f64,
+		test_sil_f64: f64 => f64,
+	}
+
+	#[test]
+	fn test_verify_assignment() {
+		let data = fixture!(f64);
+		let meds = vec![0, 1, 3];
+		let (_, reco): (f64, _) = crate::initial_reco(&data, &meds);
+		assert!(verify_assignment(&data, &meds, &reco).is_none(), "valid records rejected");
+		let mut bad = reco.clone();
+		bad[2].near.d += 1.;
+		let err = verify_assignment(&data, &meds, &bad).expect("wrong distance not detected");
+		assert_eq!((err.point, err.rank, err.actual_near.d, err.expected_near.d), (2, 0, 3., 2.), "error not as expected");
+		let mut bad = reco.clone();
+		let r = &mut bad[4];
+		core::mem::swap(&mut r.near.i, &mut r.seco.i);
+		let err = verify_assignment(&data, &meds, &bad).expect("wrong medoid not detected");
+		assert_eq!((err.point, err.rank), (4, 0), "error not as expected");
+		let mut bad = reco.clone();
+		bad[1].third = DistancePair::sentinel();
+		let err = verify_assignment(&data, &meds, &bad).expect("missing medoid not detected");
+		assert_eq!((err.point, err.rank, err.expected_near.i), (1, 2, 2), "error not as expected");
+		assert!(err.to_string().starts_with("point 1: medoid of rank 2"), "message not as expected");
+		// with two medoids, the third entry must be missing
+		let (_, mut reco): (f64, _) = crate::initial_reco(&data, &meds[..2]);
+		assert!(verify_assignment(&data, &meds[..2], &reco).is_none(), "valid records rejected");
+		reco[0].third = DistancePair::new(1, 1.);
+		let err = verify_assignment(&data, &meds[..2], &reco).expect("extra medoid not detected");
+		assert!((err.point, err.rank) == (0, 2) && err.expected_near.is_sentinel(), "error not as expected");
+	}
+
+	#[test]
+	fn test_ratio() {
+		assert_eq!(safe_ratio::<u32, f64>(0, 0), 0., "ratio of zeros not as expected");
+		assert_eq!(safe_ratio::<f64, f64>(2., 0.), 0., "ratio with zero denominator not as expected");
+		assert_eq!(safe_ratio::<f64, f64>(0., 2.), 0., "ratio with zero numerator not as expected");
+		assert_eq!(safe_ratio::<u8, f32>(3, 4), 0.75, "ratio not as expected");
+		assert_eq!(unsafe_ratio::<u8, f32>(3, 4), 0.75, "ratio not as expected");
+		assert_eq!(unsafe_ratio::<f64, f64>(2., 0.), f64::INFINITY, "ratio with zero denominator not as expected");
+	}
+
+	#[test]
+	fn test_distance_pair_sentinel() {
+		assert!(DistancePair::<f64>::sentinel().is_sentinel(), "sentinel not recognized");
+		assert!(!DistancePair::new(0, 1.).is_sentinel(), "pair recognized as sentinel");
+		assert!(Rec::<u32>::empty().seco.is_sentinel(), "empty record not recognized");
+		let reco = Reco::<u32>::empty();
+		assert!(reco.near.is_sentinel() && reco.seco.is_sentinel() && reco.third.is_sentinel(), "empty record not recognized");
+	}
+
+	#[test]
+	#[should_panic]
+	#[cfg(debug_assertions)]
+	fn test_distance_pair_negative() {
+		DistancePair::new(0, -1.);
+	}
+
+	#[test]
+	fn test_assert_reco_valid() {
+		assert!(assert_reco_valid(&Reco::new(1, 0., 0, 2., 2, 2.), 3), "valid record rejected");
+		assert!(assert_reco_valid(&Reco::new(0, 1., 1, 2., u32::MAX, 0.), 2), "missing third rejected");
+		assert!(assert_reco_valid(&Reco::new(0, 1., u32::MAX, 0., u32::MAX, 0.), 1), "missing second rejected");
+		assert!(!assert_reco_valid(&Reco::new(0, 3., 1, 2., 2, 4.), 3), "unsorted distances accepted");
+		assert!(!assert_reco_valid(&Reco::new(0, 1., 0, 2., 2, 4.), 3), "duplicate index accepted");
+		assert!(!assert_reco_valid(&Reco::new(0, 1., 1, 2., 3, 4.), 3), "index out of range accepted");
+		assert!(!assert_reco_valid(&Reco::new(0, 1., u32::MAX, 0., 1, 2.), 2), "gap accepted");
+		// all records of the algorithms on the example data
+		let data = fixture!(u32);
+		for k in 1..=4 {
+			let meds = (0..k).collect::<Vec<_>>();
+			let (_, reco): (f64, Vec<Reco<u32>>) = crate::fastermsc::initial_assignment(&data, &meds);
+			assert!(reco.iter().all(|r| assert_reco_valid(r, k)), "initial assignment not valid");
+		}
+	}
+
+	#[test]
+	fn test_assert_monotone_decreasing() {
+		assert_monotone_decreasing(&[3, 2, 2, 1], "decreasing");
+		assert_monotone_decreasing::<f64>(&[], "empty");
+		assert!(std::panic::catch_unwind(|| assert_monotone_decreasing(&[3, 2, 3], "increasing")).is_err(), "increase not detected");
+	}
+
+	/// An algorithm with the initial medoids fixed, run with the given maxiter
+	type Run<'a> = (&'a str, Box<dyn Fn(usize) -> (f64, usize) + 'a>);
+
+	/// The loss must never get worse with more iterations, on the example and a random data set
+	#[test]
+	fn test_monotone_loss_histories() {
+		use rand::{rngs::StdRng, Rng, SeedableRng};
+		let mut rng = StdRng::seed_from_u64(1);
+		let random = LowerTriangle { n: 40, data: (0..780).map(|_| rng.gen_range(1..100) as f64).collect::<Vec<f64>>() };
+		for (data, k) in [(&fixture!(f64), 2), (&random, 3), (&random, 5)] {
+			let init = (0..k).collect::<Vec<usize>>();
+			let seeded = || StdRng::seed_from_u64(0);
+			// minimizing the total deviation
+			#[allow(unused_mut)]
+			let mut td: Vec<Run> = vec![
+				("PAM SWAP", Box::new(|i| { let r = crate::pam_swap(data, &mut init.clone(), 0, i); (r.0, r.2) })),
+				("FastPAM1", Box::new(|i| { let r = crate::fastpam1(data, &mut init.clone(), 0, i); (r.0, r.2) })),
+				("FasterPAM", Box::new(|i| { let r = crate::fasterpam(data, &mut init.clone(), 0, i); (r.0, r.2) })),
+				("Random FasterPAM", Box::new(|i| { let r = crate::rand_fasterpam(data, &mut init.clone(), 0, i, &mut seeded()); (r.0, r.2) })),
+				("Alternating", Box::new(|i| { let r = crate::alternating(data, &mut init.clone(), i); (r.0, r.2) })),
+			];
+			#[cfg(feature = "parallel")]
+			{
+				td.push(("Parallel FasterPAM", Box::new(|i| { let r = crate::par_fasterpam(data, &mut init.clone(), 0, i, &mut seeded()); (r.0, r.2) })));
+				td.push(("Parallel Alternating", Box::new(|i| { let r = crate::par_alternating(data, &mut init.clone(), i); (r.0, r.2) })));
+			}
+			for (name, run) in td {
+				assert_monotone_decreasing(&loss_history(run, name), name);
+			}
+			// maximizing the (medoid) silhouette
+			let sil: [Run; 5] = [
+				("FastMSC", Box::new(|i| { let r = crate::fastmsc(data, &mut init.clone(), 0, i); (r.0, r.2) })),
+				("FasterMSC", Box::new(|i| { let r = crate::fastermsc(data, &mut init.clone(), 0, i); (r.0, r.2) })),
+				("Random FasterMSC", Box::new(|i| { let r = crate::rand_fastermsc(data, &mut init.clone(), 0, i, &mut seeded()); (r.0, r.2) })),
+				("PAMMEDSIL SWAP", Box::new(|i| { let r = crate::pammedsil_swap(data, &mut init.clone(), i); (r.0, r.2) })),
+				("PAMSIL SWAP", Box::new(|i| { let r = crate::pamsil_swap(data, &mut init.clone(), i); (r.0, r.2) })),
+			];
+			for (name, run) in sil {
+				let history = loss_history(run, name).iter().map(|l| -l).collect::<Vec<_>>();
+				assert_monotone_decreasing(&history, name);
+			}
+		}
+	}
+}
@@ -0,0 +1,60 @@
+//! Use the main API with only the prelude in scope.
+use kmedoids::prelude::*;
+
+/// Two well-separated pairs of points
+fn data() -> LowerTriangle<u32> {
+	LowerTriangle { n: 4, data: vec![1, 10, 10, 10, 10, 1] }
+}
+
+#[test]
+fn test_prelude_pam_family() {
+	let data = data();
+	let mut meds = first_k(2);
+	let (loss, mut assi, n_iter, n_swap): (i64, _, _, _) = fasterpam(&data, &mut meds, 0, 10);
+	canonicalize(&mut meds, &mut assi);
+	let (loss2, assi2): (i64, _) = assign_to_medoids(&data, &[0, 2]);
+	assert_eq!((loss, loss2), (2, 2), "loss not as expected");
+	assert_eq!(assi, assi2, "assignment not as expected");
+	let result = KMedoidsResult::new(loss, assi, meds, n_iter, n_swap);
+	assert_eq!(result.medoids.len(), 2, "medoids not as expected");
+
+	let (loss, _, _, _): (i64, _, _, _) = fastpam1(&data, &mut first_k(2), 0, 10);
+	assert_eq!(loss, 2, "fastpam1 loss not as expected");
+	let (loss, _, _, _, _): (i64, _, _, _, _) = pam(&data, 2, 10);
+	assert_eq!(loss, 2, "pam loss not as expected");
+	let (loss, _, _): (i64, _, _) = alternating(&data, &mut [0, 2], 10);
+	assert_eq!(loss, 2, "alternating loss not as expected");
+	#[cfg(feature = "rand")]
+	{
+		let mut rng = rand::thread_rng();
+		let mut meds = random_initialization(4, 2, &mut rng);
+		let (loss, _, _, _): (i64, _, _, _) = rand_fasterpam(&data, &mut meds, 0, 10, &mut rng);
+		assert_eq!(loss, 2, "rand_fasterpam loss not as expected");
+		#[cfg(feature = "parallel")]
+		{
+			let mut meds = random_initialization(4, 2, &mut rng);
+			let (loss, _, _, _): (i64, _, _, _) = par_fasterpam(&data, &mut meds, 0, 10, &mut rng);
+			assert_eq!(loss, 2, "par_fasterpam loss not as expected");
+		}
+	}
+}
+
+#[test]
+fn test_prelude_silhouette_family() {
+	let data = data();
+	let (loss, assi, _, _): (f64, _, _, _) = fastermsc(&data, &mut first_k(2), 0, 10);
+	let (sil, _): (f64, _) = silhouette(&data, &assi, false);
+	let (msil, _): (f64, _) = medoid_silhouette(&data, &[0, 2], false);
+	assert_eq!(loss, msil, "medoid silhouette not as expected");
+	assert!(sil > 0.8, "silhouette not as expected");
+	let (loss, _, _, _): (f64, _, _, _) = fastmsc(&data, &mut first_k(2), 0, 10);
+	assert_eq!(loss, msil, "fastmsc loss not as expected");
+	let (loss, _, _, _, _): (f64, _, _, _, _) = pammedsil(&data, 2, 10);
+	assert_eq!(loss, msil, "pammedsil loss not as expected");
+	let (loss, _, _, _, _): (f64, _, _, _, _) = pamsil(&data, 2, 10);
+	assert_eq!(loss, sil, "pamsil loss not as expected");
+	let (loss, _, _, _, _, _): (f64, _, _, _, _, _) = dynmsc(&data, &vec![0, 1, 2], 0, 10);
+	assert!(loss >= msil, "dynmsc loss not as expected");
+	#[cfg(feature = "parallel")]
+	assert_eq!(par_silhouette::<_, _, f64>(&data, &assi), sil, "par_silhouette not as expected");
+}